@@ -3,12 +3,12 @@ use crate::tables::Challenge;
 use crate::tables::EvaluationArgumentHint;
 use crate::vm::compile;
 use crate::BrainfuckClaim;
-use ark_ff::Field;
 use ark_ff::One;
 use ark_ff::Zero;
 use ark_poly::EvaluationDomain;
 use ark_poly::Radix2EvaluationDomain;
 use ministark::air::AirConfig;
+use ministark::arguments::EvaluationArgument;
 use ministark::challenges::Challenges;
 use ministark::constraints::AlgebraicItem;
 use ministark::constraints::Constraint;
@@ -25,6 +25,7 @@ pub struct BrainfuckAirConfig;
 impl AirConfig for BrainfuckAirConfig {
     const NUM_BASE_COLUMNS: usize = 17;
     const NUM_EXTENSION_COLUMNS: usize = 9;
+    const NAME: &'static str = "brainfuck";
 
     type Fp = Fp;
     type Fq = Fq3;
@@ -35,7 +36,6 @@ impl AirConfig for BrainfuckAirConfig {
         execution_info: &BrainfuckClaim,
         challenges: &Challenges<Self::Fq>,
     ) -> Hints<Self::Fq> {
-        use Challenge::*;
         use EvaluationArgumentHint::*;
         let BrainfuckClaim {
             source_code,
@@ -43,18 +43,26 @@ impl AirConfig for BrainfuckAirConfig {
             output,
         } = execution_info;
 
-        let (input_eval_arg, input_eval_offset) =
-            io_terminal_helper(input, challenges[Gamma.index()], trace_len);
-        let (output_eval_arg, output_eval_offset) =
-            io_terminal_helper(output, challenges[Delta.index()], trace_len);
+        let (input_eval_arg, padded_input_eval_arg) = io_terminals(
+            &tables::InputExtensionColumn::evaluation_argument(),
+            input,
+            challenges,
+            trace_len,
+        );
+        let (output_eval_arg, padded_output_eval_arg) = io_terminals(
+            &tables::OutputExtensionColumn::evaluation_argument(),
+            output,
+            challenges,
+            trace_len,
+        );
         let instruction_eval_arg = compute_instruction_evaluation_argument(source_code, challenges);
 
         Hints::new(vec![
             (Instruction.index(), instruction_eval_arg),
             (Input.index(), input_eval_arg),
-            (InputOffset.index(), input_eval_offset),
+            (PaddedInput.index(), padded_input_eval_arg),
             (Output.index(), output_eval_arg),
-            (OutputOffset.index(), output_eval_offset),
+            (PaddedOutput.index(), padded_output_eval_arg),
         ])
     }
 
@@ -72,8 +80,6 @@ impl AirConfig for BrainfuckAirConfig {
             tables::MemoryExtensionColumn::transition_constraints(),
             tables::InstructionBaseColumn::transition_constraints(),
             tables::InstructionExtensionColumn::transition_constraints(),
-            tables::InputExtensionColumn::transition_constraints(),
-            tables::OutputExtensionColumn::transition_constraints(),
         ]
         .into_iter()
         .flatten()
@@ -91,8 +97,6 @@ impl AirConfig for BrainfuckAirConfig {
             tables::MemoryBaseColumn::boundary_constraints(),
             tables::InstructionBaseColumn::boundary_constraints(),
             tables::InstructionExtensionColumn::boundary_constraints(),
-            tables::InputExtensionColumn::boundary_constraints(),
-            tables::OutputExtensionColumn::boundary_constraints(),
         ]
         .into_iter()
         .flatten()
@@ -105,8 +109,6 @@ impl AirConfig for BrainfuckAirConfig {
         let terminal_constraints = [
             tables::ProcessorExtensionColumn::terminal_constraints(),
             tables::InstructionExtensionColumn::terminal_constraints(),
-            tables::InputExtensionColumn::terminal_constraints(),
-            tables::OutputExtensionColumn::terminal_constraints(),
         ]
         .into_iter()
         .flatten()
@@ -117,10 +119,22 @@ impl AirConfig for BrainfuckAirConfig {
             constraint / (X - last_trace_x)
         });
 
+        let input_evaluation = tables::InputExtensionColumn::evaluation_argument();
+        let output_evaluation = tables::OutputExtensionColumn::evaluation_argument();
+        let argument_constraints = [
+            input_evaluation.constraints(trace_len),
+            vec![input_evaluation
+                .terminal_constraint(trace_len, EvaluationArgumentHint::PaddedInput)],
+            output_evaluation.constraints(trace_len),
+            vec![output_evaluation
+                .terminal_constraint(trace_len, EvaluationArgumentHint::PaddedOutput)],
+        ];
+
         transition_constraints
             .chain(boundary_constraints)
             .chain(terminal_constraints)
             .map(Constraint::from)
+            .chain(argument_constraints.into_iter().flatten())
             .collect()
     }
 }
@@ -146,19 +160,25 @@ fn compute_instruction_evaluation_argument(source_code: &str, challenges: &Chall
     acc
 }
 
-// Computes the evaluation terminal for the input and output table
-// output is of the form `(evaluatoin_argument, evaluation_offset)`
-fn io_terminal_helper<F: Field>(symbols: &[u8], challenge: F, trace_len: usize) -> (F, F) {
-    let mut acc = F::zero();
-    for symbol in symbols {
-        acc = challenge * acc + F::from(*symbol as u64);
-    }
-    let evaluation_argument = acc;
+// Computes the evaluation terminals for the input and output table
+// output is of the form `(evaluation_argument, padded_evaluation_argument)`
+fn io_terminals(
+    argument: &EvaluationArgument<Fp, Fq3>,
+    symbols: &[u8],
+    challenges: &Challenges<Fq3>,
+    trace_len: usize,
+) -> (Fq3, Fq3) {
+    let mut rows = symbols
+        .iter()
+        .map(|&symbol| [Fq3::from(u64::from(symbol))])
+        .collect::<Vec<_>>();
+    let evaluation_argument = argument.terminal(challenges, rows.iter().map(|row| row.as_slice()));
     // from BrainSTARK
     // In every additional row, the running evaluation variable is
-    // multiplied by another `challenge` factor. So we multiply by
-    // `challenge^(trace_len - num_symbols)` to get the value of
-    // the evaluation terminal after all 2^k trace rows.
-    let offset = challenge.pow([(trace_len - symbols.len()) as u64]);
-    (evaluation_argument, offset)
+    // multiplied by another `challenge` factor. Padding rows hold zero so
+    // they are included in the terminal of the table after all 2^k trace rows.
+    rows.resize(trace_len, [Fq3::zero()]);
+    let padded_evaluation_argument =
+        argument.terminal(challenges, rows.iter().map(|row| row.as_slice()));
+    (evaluation_argument, padded_evaluation_argument)
 }
@@ -12,6 +12,7 @@ use crate::tables::ProcessorBaseColumn;
 use crate::tables::ProcessorExtensionColumn;
 use crate::vm::OpCode;
 use ark_ff::FftField;
+use ministark::arguments::EvaluationArgument;
 use ministark::constraints::AlgebraicItem;
 use ministark::constraints::ExecutionTraceColumn;
 use ministark::constraints::Hint;
@@ -424,55 +425,30 @@ impl InstructionExtensionColumn {
 }
 
 impl InputExtensionColumn {
-    pub fn boundary_constraints<Fp: GpuFftField + FftField, Fq: StarkExtensionOf<Fp>>(
-    ) -> Vec<Expr<AlgebraicItem<FieldVariant<Fp, Fq>>>> {
-        use InputBaseColumn::*;
-        use InputExtensionColumn::*;
-        vec![Evaluation.curr() - Value.curr()]
-    }
-
-    pub fn terminal_constraints<Fp: GpuFftField + FftField, Fq: StarkExtensionOf<Fp>>(
-    ) -> Vec<Expr<AlgebraicItem<FieldVariant<Fp, Fq>>>> {
-        use InputExtensionColumn::*;
-        vec![
-            Evaluation.curr()
-                - EvaluationArgumentHint::Input.hint() * EvaluationArgumentHint::InputOffset.hint(),
-        ]
-    }
-
-    pub fn transition_constraints<Fp: GpuFftField + FftField, Fq: StarkExtensionOf<Fp>>(
-    ) -> Vec<Expr<AlgebraicItem<FieldVariant<Fp, Fq>>>> {
-        use Challenge::Gamma;
-        use InputBaseColumn::*;
-        use InputExtensionColumn::*;
-        vec![Evaluation.curr() * Gamma.challenge() + Value.next() - Evaluation.next()]
+    /// Running evaluation of the symbols on the input tape
+    pub fn evaluation_argument<Fp: GpuFftField + FftField, Fq: StarkExtensionOf<Fp>>(
+    ) -> EvaluationArgument<Fp, Fq> {
+        // symbols aren't tuples so the compression challenge is never used
+        EvaluationArgument::new(
+            Challenge::Gamma,
+            Challenge::Gamma,
+            InputExtensionColumn::Evaluation,
+            [InputBaseColumn::Value.curr()],
+        )
     }
 }
 
 impl OutputExtensionColumn {
-    pub fn boundary_constraints<Fp: GpuFftField + FftField, Fq: StarkExtensionOf<Fp>>(
-    ) -> Vec<Expr<AlgebraicItem<FieldVariant<Fp, Fq>>>> {
-        use OutputBaseColumn::*;
-        use OutputExtensionColumn::*;
-        vec![Evaluation.curr() - Value.curr()]
-    }
-
-    pub fn terminal_constraints<Fp: GpuFftField + FftField, Fq: StarkExtensionOf<Fp>>(
-    ) -> Vec<Expr<AlgebraicItem<FieldVariant<Fp, Fq>>>> {
-        use OutputExtensionColumn::*;
-        vec![
-            Evaluation.curr()
-                - EvaluationArgumentHint::Output.hint()
-                    * EvaluationArgumentHint::OutputOffset.hint(),
-        ]
-    }
-
-    pub fn transition_constraints<Fp: GpuFftField + FftField, Fq: StarkExtensionOf<Fp>>(
-    ) -> Vec<Expr<AlgebraicItem<FieldVariant<Fp, Fq>>>> {
-        use Challenge::Delta;
-        use OutputBaseColumn::*;
-        use OutputExtensionColumn::*;
-        vec![Evaluation.curr() * Delta.challenge() + Value.next() - Evaluation.next()]
+    /// Running evaluation of the symbols on the output tape
+    pub fn evaluation_argument<Fp: GpuFftField + FftField, Fq: StarkExtensionOf<Fp>>(
+    ) -> EvaluationArgument<Fp, Fq> {
+        // symbols aren't tuples so the compression challenge is never used
+        EvaluationArgument::new(
+            Challenge::Delta,
+            Challenge::Delta,
+            OutputExtensionColumn::Evaluation,
+            [OutputBaseColumn::Value.curr()],
+        )
     }
 }
 
@@ -3,10 +3,8 @@
 use air::BrainfuckAirConfig;
 use ark_serialize::CanonicalDeserialize;
 use ark_serialize::CanonicalSerialize;
-use ministark::hash::HashFn;
 use ministark::hash::Sha256HashFn;
 use ministark::merkle::MatrixMerkleTreeImpl;
-use ministark::random::PublicCoin;
 use ministark::random::PublicCoinImpl;
 use ministark::stark::Stark;
 use ministark::utils::SerdeOutput;
@@ -69,14 +67,6 @@ impl Stark for BrainfuckClaim {
     type Witness = BrainfuckTrace;
     type Trace = BrainfuckTrace;
 
-    fn gen_public_coin(&self, air: &ministark::Air<Self::AirConfig>) -> Self::PublicCoin {
-        let mut seed = Vec::new();
-        air.public_inputs().serialize_compressed(&mut seed).unwrap();
-        air.trace_len().serialize_compressed(&mut seed).unwrap();
-        air.options().serialize_compressed(&mut seed).unwrap();
-        PublicCoinImpl::new(Sha256HashFn::hash_chunks([&*seed]))
-    }
-
     fn get_public_inputs(&self) -> Self {
         self.clone()
     }
@@ -147,9 +137,8 @@ fn prove(source_code_path: PathBuf, input: String, output_path: PathBuf) {
     println!("Proof security (conjectured): {security_level}bit",);
 
     let mut proof_bytes = Vec::new();
-    (claim, proof)
-        .serialize_compressed(&mut proof_bytes)
-        .unwrap();
+    claim.serialize_compressed(&mut proof_bytes).unwrap();
+    proof_bytes.extend(proof.to_bytes());
     println!("Proof size: {:?}KB", proof_bytes.len() / 1024);
     let mut f = File::create(&output_path).unwrap();
     f.write_all(proof_bytes.as_slice()).unwrap();
@@ -160,8 +149,9 @@ fn prove(source_code_path: PathBuf, input: String, output_path: PathBuf) {
 fn verify(source_code_path: PathBuf, input: String, output: String, proof_path: PathBuf) {
     let source_code = fs::read_to_string(source_code_path).unwrap();
     let proof_bytes = fs::read(proof_path).unwrap();
-    let (execution_info, proof): (BrainfuckClaim, Proof<BrainfuckClaim>) =
-        <_>::deserialize_compressed(proof_bytes.as_slice()).unwrap();
+    let mut proof_bytes = proof_bytes.as_slice();
+    let execution_info = BrainfuckClaim::deserialize_compressed(&mut proof_bytes).unwrap();
+    let proof = Proof::<BrainfuckClaim>::from_bytes(proof_bytes).unwrap();
     assert_eq!(input.as_bytes(), execution_info.input);
     assert_eq!(output.as_bytes(), execution_info.output);
     assert_eq!(source_code, execution_info.source_code);
@@ -172,3 +162,79 @@ fn verify(source_code_path: PathBuf, input: String, output: String, proof_path:
         .expect("verification failed");
     println!("Proof verified in: {:?}", now.elapsed());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ministark::merkle::CompactMatrixMerkleTreeImpl;
+    use ministark::tamper::check_tampered_proofs;
+
+    /// Brainfuck claim that generates proofs that omit recomputable merkle
+    /// leaves
+    struct CompactBrainfuckClaim(BrainfuckClaim);
+
+    impl Stark for CompactBrainfuckClaim {
+        type Fp = Fp;
+        type Fq = Fq3;
+        type AirConfig = BrainfuckAirConfig;
+        type Digest = SerdeOutput<Sha256>;
+        type PublicCoin = PublicCoinImpl<Fq3, Sha256HashFn>;
+        type MerkleTree = CompactMatrixMerkleTreeImpl<Sha256HashFn>;
+        type Witness = BrainfuckTrace;
+        type Trace = BrainfuckTrace;
+
+        fn get_public_inputs(&self) -> BrainfuckClaim {
+            self.0.clone()
+        }
+
+        fn generate_trace(&self, witness: BrainfuckTrace) -> BrainfuckTrace {
+            witness
+        }
+    }
+
+    #[test]
+    fn compact_merkle_proofs_reduce_proof_size() {
+        let source_code = include_str!("hello_world.bf").to_string();
+        let claim = {
+            let mut output = Vec::new();
+            simulate(&source_code, &mut "".as_bytes(), &mut output);
+            BrainfuckClaim {
+                source_code: source_code.clone(),
+                input: Vec::new(),
+                output,
+            }
+        };
+        let compact_claim = CompactBrainfuckClaim(claim.clone());
+        let trace = || simulate(&source_code, &mut "".as_bytes(), &mut Vec::new());
+        let options = ProofOptions::new(19, 16, 0, 16, 16);
+
+        let proof = pollster::block_on(claim.prove(options, trace())).unwrap();
+        let compact_proof = pollster::block_on(compact_claim.prove(options, trace())).unwrap();
+
+        let size = proof.compressed_size();
+        let compact_size = compact_proof.compressed_size();
+        println!(
+            "proof size: {size} bytes, compact proof size: {compact_size} bytes ({:.1}% smaller)",
+            100.0 * (size - compact_size) as f64 / size as f64
+        );
+        assert!(compact_size < size);
+        compact_claim.verify(compact_proof, 0).unwrap();
+    }
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let source_code = include_str!("hello_world.bf").to_string();
+        let mut output = Vec::new();
+        let trace = simulate(&source_code, &mut "".as_bytes(), &mut output);
+        let claim = BrainfuckClaim {
+            source_code,
+            input: Vec::new(),
+            output,
+        };
+        let options = ProofOptions::new(8, 16, 4, 16, 16);
+
+        let proof = pollster::block_on(claim.prove(options, trace)).unwrap();
+
+        check_tampered_proofs(&claim, &proof);
+    }
+}
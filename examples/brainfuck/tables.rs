@@ -29,9 +29,9 @@ impl ministark::constraints::VerifierChallenge for Challenge {
 pub enum EvaluationArgumentHint {
     Instruction,
     Input,
-    InputOffset,
+    PaddedInput,
     Output,
-    OutputOffset,
+    PaddedOutput,
 }
 
 impl ministark::constraints::Hint for EvaluationArgumentHint {
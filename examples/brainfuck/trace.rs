@@ -1,12 +1,10 @@
 use crate::tables::BrainfuckColumn;
 use crate::tables::Challenge;
-use crate::tables::InputBaseColumn;
 use crate::tables::InputExtensionColumn;
 use crate::tables::InstructionBaseColumn;
 use crate::tables::InstructionExtensionColumn;
 use crate::tables::MemoryBaseColumn;
 use crate::tables::MemoryExtensionColumn;
-use crate::tables::OutputBaseColumn;
 use crate::tables::OutputExtensionColumn;
 use crate::tables::ProcessorBaseColumn;
 use crate::tables::ProcessorExtensionColumn;
@@ -30,8 +28,6 @@ pub struct BrainfuckTrace {
     processor_base_trace: Matrix<Fp>,
     memory_base_trace: Matrix<Fp>,
     instruction_base_trace: Matrix<Fp>,
-    input_base_trace: Matrix<Fp>,
-    output_base_trace: Matrix<Fp>,
     base_trace: Matrix<Fp>,
 }
 
@@ -47,15 +43,13 @@ impl BrainfuckTrace {
             processor_base_trace.clone(),
             memory_base_trace.clone(),
             instruction_base_trace.clone(),
-            input_base_trace.clone(),
-            output_base_trace.clone(),
+            input_base_trace,
+            output_base_trace,
         ]);
         Self {
             processor_base_trace,
             memory_base_trace,
             instruction_base_trace,
-            input_base_trace,
-            output_base_trace,
             base_trace,
         }
     }
@@ -73,8 +67,6 @@ impl Trace for BrainfuckTrace {
             processor_base_trace,
             memory_base_trace,
             instruction_base_trace,
-            input_base_trace,
-            output_base_trace,
             ..
         } = self;
 
@@ -88,8 +80,10 @@ impl Trace for BrainfuckTrace {
         let memory_matrix = gen_memory_ext_matrix(mem_initial, challenges, memory_base_trace);
         let instruction_matrix =
             gen_instruction_ext_matrix(instr_initial, challenges, instruction_base_trace);
-        let input_matrix = gen_input_ext_matrix(challenges, input_base_trace);
-        let output_matrix = gen_output_ext_matrix(challenges, output_base_trace);
+        let input_matrix =
+            InputExtensionColumn::evaluation_argument().build_column(self, challenges, &[]);
+        let output_matrix =
+            OutputExtensionColumn::evaluation_argument().build_column(self, challenges, &[]);
 
         Some(Matrix::join(vec![
             processor_matrix,
@@ -244,50 +238,6 @@ fn gen_instruction_ext_matrix(
     Matrix::new(into_columns(extension_rows))
 }
 
-fn gen_input_ext_matrix(challenges: &Challenges<Fq3>, base_matrix: &Matrix<Fp>) -> Matrix<Fq3> {
-    use Challenge::*;
-    use InputBaseColumn::*;
-    use InputExtensionColumn::*;
-
-    // prepare
-    let mut running_evaluation = Fq3::zero();
-
-    // loop over all rows
-    let mut extension_rows = Vec::new();
-    for row in 0..base_matrix.num_rows() {
-        let base_row = base_matrix.get_row(row).unwrap();
-        let mut extension_row = [Fq3::zero(); InputExtensionColumn::NUM_TRACE_COLUMNS];
-        running_evaluation =
-            running_evaluation * challenges[Gamma.index()] + base_row[Value as usize];
-        extension_row[Evaluation as usize] = running_evaluation;
-        extension_rows.push(extension_row);
-    }
-
-    Matrix::new(into_columns(extension_rows))
-}
-
-fn gen_output_ext_matrix(challenges: &Challenges<Fq3>, base_matrix: &Matrix<Fp>) -> Matrix<Fq3> {
-    use Challenge::*;
-    use OutputBaseColumn::*;
-    use OutputExtensionColumn::*;
-
-    // prepare
-    let mut running_evaluation = Fq3::zero();
-
-    // loop over all rows
-    let mut extension_rows = Vec::new();
-    for row in 0..base_matrix.num_rows() {
-        let base_row = base_matrix.get_row(row).unwrap();
-        let mut extension_row = [Fq3::zero(); OutputExtensionColumn::NUM_TRACE_COLUMNS];
-        running_evaluation =
-            running_evaluation * challenges[Delta.index()] + base_row[Value as usize];
-        extension_row[Evaluation as usize] = running_evaluation;
-        extension_rows.push(extension_row);
-    }
-
-    Matrix::new(into_columns(extension_rows))
-}
-
 pub fn into_columns<F: Field, const N: usize>(rows: Vec<[F; N]>) -> Vec<GpuVec<F>> {
     let mut cols = (0..N)
         .map(|_| Vec::new_in(GpuAllocator))
@@ -3,16 +3,13 @@
 use ark_ff::One;
 use ark_poly::EvaluationDomain;
 use ark_poly::Radix2EvaluationDomain;
-use ark_serialize::CanonicalSerialize;
 use ministark::air::AirConfig;
 use ministark::constraints::AlgebraicItem;
 use ministark::constraints::Constraint;
 use ministark::constraints::ExecutionTraceColumn;
-use ministark::hash::HashFn;
 use ministark::hash::Sha256HashFn;
 use ministark::hints::Hints;
 use ministark::merkle::MatrixMerkleTreeImpl;
-use ministark::random::PublicCoin;
 use ministark::random::PublicCoinImpl;
 use ministark::stark::Stark;
 use ministark::utils::FieldVariant;
@@ -55,6 +52,7 @@ struct FibAirConfig;
 
 impl AirConfig for FibAirConfig {
     const NUM_BASE_COLUMNS: usize = 8;
+    const NAME: &'static str = "fibonacci";
     type Fp = Fp;
     type Fq = Fp;
     type PublicInputs = Fp;
@@ -162,14 +160,6 @@ impl Stark for FibClaim {
     fn generate_trace(&self, witness: FibTrace) -> Self::Trace {
         witness
     }
-
-    fn gen_public_coin(&self, air: &ministark::Air<Self::AirConfig>) -> Self::PublicCoin {
-        let mut seed = Vec::new();
-        air.public_inputs().serialize_compressed(&mut seed).unwrap();
-        air.trace_len().serialize_compressed(&mut seed).unwrap();
-        air.options().serialize_compressed(&mut seed).unwrap();
-        PublicCoinImpl::new(Sha256HashFn::hash_chunks([&*seed]))
-    }
 }
 
 fn gen_trace(n: usize) -> FibTrace {
@@ -241,3 +231,19 @@ fn main() {
         .expect("verification failed");
     println!("Proof generated in: {:?}", now.elapsed());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ministark::tamper::check_tampered_proofs;
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let trace = gen_trace(2048);
+        let claim = FibClaim(trace.last_value());
+
+        let proof = pollster::block_on(claim.prove(OPTIONS, trace)).unwrap();
+
+        check_tampered_proofs(&claim, &proof);
+    }
+}
@@ -21,6 +21,7 @@ struct RescueAirConfig;
 
 impl AirConfig for RescueAirConfig {
     const NUM_BASE_COLUMNS: usize = 0;
+    const NAME: &'static str = "rescue";
     type Fp = Fp;
     type Fq = Fp;
     type PublicInputs = ();
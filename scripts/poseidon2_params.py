@@ -0,0 +1,159 @@
+#!/usr/bin/env python3
+"""Generates the Poseidon2 round constants and known-answer test vectors used
+in src/hash/poseidon2/.
+
+Round constants are sampled with the Grain LFSR procedure of the Horizen Labs
+parameter script:
+https://github.com/HorizenLabs/poseidon2/blob/main/poseidon2_rust_params.sage
+
+The script first checks itself against the published Horizen Labs Goldilocks
+instance (round constants and test vector) then prints the Stark252 instance.
+Output is committed alongside in poseidon2_stark252.txt:
+
+    python3 scripts/poseidon2_params.py > scripts/poseidon2_stark252.txt
+"""
+
+GOLDILOCKS = 2**64 - 2**32 + 1
+STARK252 = 2**251 + 17 * 2**192 + 1
+
+
+def grain_round_constants(p, alpha, t, r_f, r_p):
+    """Returns the external and internal round constants of an instance"""
+    assert alpha > 1, "only x^alpha S-boxes are supported"
+    n = p.bit_length()
+    field, sbox = 1, 0
+    init = (
+        format(field, "02b")
+        + format(sbox, "04b")
+        + format(n, "012b")
+        + format(t, "012b")
+        + format(r_f, "010b")
+        + format(r_p, "010b")
+    )
+    bits = [int(b) for b in init] + [1] * 30
+
+    def step():
+        bit = bits[62] ^ bits[51] ^ bits[38] ^ bits[23] ^ bits[13] ^ bits[0]
+        bits.pop(0)
+        bits.append(bit)
+        return bit
+
+    for _ in range(160):
+        step()
+
+    def next_bit():
+        # self-shrinking: output the second bit of each pair starting with 1
+        while step() == 0:
+            step()
+        return step()
+
+    def next_element():
+        while True:
+            value = int("".join(str(next_bit()) for _ in range(n)), 2)
+            if value < p:
+                return value
+
+    half = r_f // 2
+    beginning = [next_element() for _ in range(half * t)]
+    internal = [next_element() for _ in range(r_p)]
+    end = [next_element() for _ in range(half * t)]
+    return beginning + end, internal
+
+
+def matmul_m4(p, state):
+    out = []
+    for i in range(0, len(state), 4):
+        x0, x1, x2, x3 = state[i : i + 4]
+        out += [
+            (5 * x0 + 7 * x1 + x2 + 3 * x3) % p,
+            (4 * x0 + 6 * x1 + x2 + x3) % p,
+            (x0 + 3 * x1 + 5 * x2 + 7 * x3) % p,
+            (x0 + x1 + 4 * x2 + 6 * x3) % p,
+        ]
+    return out
+
+
+def matmul_external(p, state):
+    t = len(state)
+    if t in (2, 3):
+        total = sum(state)
+        return [(s + total) % p for s in state]
+    if t == 4:
+        return matmul_m4(p, state)
+    assert t % 4 == 0
+    state = matmul_m4(p, state)
+    sums = [sum(state[j] for j in range(i, t, 4)) for i in range(4)]
+    return [(s + sums[i % 4]) % p for i, s in enumerate(state)]
+
+
+def matmul_internal(p, state, diag_m_1):
+    t = len(state)
+    total = sum(state)
+    if t == 2:
+        return [(state[0] + total) % p, (2 * state[1] + total) % p]
+    if t == 3:
+        return [(state[0] + total) % p, (state[1] + total) % p, (2 * state[2] + total) % p]
+    return [(s * d + total) % p for s, d in zip(state, diag_m_1)]
+
+
+def permute(p, alpha, r_f, external, internal, diag_m_1, state):
+    t = len(state)
+    half = r_f // 2 * t
+
+    def full_round(state, rc):
+        state = [pow(s + c, alpha, p) for s, c in zip(state, rc)]
+        return matmul_external(p, state)
+
+    state = matmul_external(p, state)
+    for i in range(0, half, t):
+        state = full_round(state, external[i : i + t])
+    for c in internal:
+        state[0] = pow(state[0] + c, alpha, p)
+        state = matmul_internal(p, state, diag_m_1)
+    for i in range(half, len(external), t):
+        state = full_round(state, external[i : i + t])
+    return state
+
+
+def check_goldilocks():
+    # values from the Horizen Labs reference implementation:
+    # plain_implementations/src/poseidon2/poseidon2_instance_goldilocks.rs
+    diag_m_1 = [
+        0xC3B6C08E23BA9300, 0xD84B5DE94A324FB6, 0x0D0C371C5B35B84F,
+        0x7964F570E7188037, 0x5DAF18BBD996604B, 0x6743BC47B9595257,
+        0x5528B9362C59BB70, 0xAC45E25B7127B68B, 0xA2077D7DFBB606B5,
+        0xF3FAAC6FAEE378AE, 0x0C6388B51545E883, 0xD27DBB6944917B60,
+    ]
+    expected = [
+        0x01EAEF96BDF1C0C1, 0x1F0D2CC525B2540C, 0x6282C1DFE1E0358D,
+        0xE780D721F698E1E6, 0x280C0B6F753D833B, 0x1B942DD5023156AB,
+        0x43F0DF3FCCCB8398, 0xE8E8190585489025, 0x56BDBF72F77ADA22,
+        0x7911C32BF9DCD705, 0xEC467926508FBE67, 0x6A50450DDF85A6ED,
+    ]
+    external, internal = grain_round_constants(GOLDILOCKS, 7, 12, 8, 22)
+    assert external[0] == 0x13DCF33ABA214F46
+    assert internal[0] == 0x4ADF842AA75D4316
+    state = permute(GOLDILOCKS, 7, 8, external, internal, diag_m_1, list(range(12)))
+    assert state == expected, "Goldilocks test vector mismatch"
+
+
+def main():
+    check_goldilocks()
+
+    # t = 3, d = 3, R_F = 8 and R_P = 83 as in the Starknet Poseidon instance
+    alpha, t, r_f, r_p = 3, 3, 8, 83
+    external, internal = grain_round_constants(STARK252, alpha, t, r_f, r_p)
+    print("# Poseidon2 Stark252: t = 3, d = 3, R_F = 8, R_P = 83")
+    print("# EXTERNAL_ROUND_CONSTANTS")
+    for c in external:
+        print(c)
+    print("# INTERNAL_ROUND_CONSTANTS")
+    for c in internal:
+        print(c)
+    print("# permutation of [0, 1, 2]")
+    for s in permute(STARK252, alpha, r_f, external, internal, [], [0, 1, 2]):
+        print(s)
+
+
+if __name__ == "__main__":
+    main()
@@ -19,11 +19,40 @@ use ark_serialize::CanonicalDeserialize;
 use ark_serialize::CanonicalSerialize;
 use ministark_gpu::GpuFftField;
 use num_traits::Pow;
-use std::time::Instant;
+use snafu::Snafu;
+
+/// Layout of an extension trace segment. See [`AirConfig::EXTENSION_SEGMENTS`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceSegment {
+    pub num_columns: usize,
+    /// Number of challenges drawn after the previous segment is committed to
+    /// and before this segment is built
+    pub num_challenges: usize,
+}
+
+impl TraceSegment {
+    pub const fn new(num_columns: usize, num_challenges: usize) -> Self {
+        Self {
+            num_columns,
+            num_challenges,
+        }
+    }
+}
 
 pub trait AirConfig: Send + Sync + Sized + 'static {
     const NUM_BASE_COLUMNS: usize;
     const NUM_EXTENSION_COLUMNS: usize = 0;
+    /// Extension trace segments in the order they're committed to. Columns of
+    /// a segment follow the columns of the previous segment and each segment
+    /// has its own commitment. Challenges are indexed in the order they're
+    /// drawn and challenges that aren't drawn for a segment are drawn after
+    /// the last segment is committed to.
+    ///
+    /// Defaults to a single segment with all extension columns that is built
+    /// with all challenges.
+    const EXTENSION_SEGMENTS: &'static [TraceSegment] = &[];
+    /// Identifies the AIR in serialized proofs
+    const NAME: &'static str;
 
     type Fp: GpuFftField<FftField = Self::Fp> + FftField;
     type Fq: StarkExtensionOf<Self::Fp>;
@@ -74,11 +103,7 @@ pub trait AirConfig: Send + Sync + Sized + 'static {
                 &constraint * (x.clone().pow(degree_adjustment) * alpha + beta)
             })
             .sum::<Expr<CompositionItem<FieldVariant<Self::Fp, Self::Fq>>>>();
-        // TODO: remove log and timing
-        let now = Instant::now();
-        let expr = expr.reuse_shared_nodes();
-        println!("Reuse took: {:?}", now.elapsed());
-        CompositionConstraint::new(expr)
+        CompositionConstraint::new(expr.reuse_shared_nodes())
     }
 
     // TODO: maybe move this into a constraint evaluator
@@ -132,36 +157,180 @@ pub fn trace_domain<A: AirConfig>(trace_len: usize) -> Radix2EvaluationDomain<A:
     Radix2EvaluationDomain::new(trace_len).unwrap()
 }
 
+/// AIR error
+#[derive(Debug, PartialEq, Eq, Snafu)]
+pub enum Error {
+    #[snafu(display("trace length must be a power of two, but `{trace_len}` was provided"))]
+    TraceLengthNotPowerOfTwo { trace_len: usize },
+    #[snafu(display(
+        "lde blowup factor `{lde_blowup_factor}` is less than the constraint evaluation blowup \
+         factor `{ce_blowup_factor}`"
+    ))]
+    BlowupFactorTooSmall {
+        ce_blowup_factor: usize,
+        lde_blowup_factor: usize,
+    },
+    #[snafu(display(
+        "`{num_queries}` queries exceeds the size of the lde domain `{lde_domain_size}`"
+    ))]
+    TooManyQueries {
+        num_queries: usize,
+        lde_domain_size: usize,
+    },
+    #[snafu(display(
+        "extension trace segments must be non-empty and have `{num_extension_columns}` columns \
+         in total"
+    ))]
+    InvalidExtensionSegments { num_extension_columns: usize },
+    #[snafu(display(
+        "zero knowledge requires a fri blowup factor of at least 2, but the fri blowup factor is \
+         `{fri_blowup_factor}`"
+    ))]
+    ZeroKnowledgeBlowupFactorTooSmall { fri_blowup_factor: usize },
+    #[snafu(display(
+        "zero knowledge hides at most `{trace_len}` openings of each trace polynomial, but \
+         `{num_openings}` are opened"
+    ))]
+    TooManyZeroKnowledgeOpenings {
+        num_openings: usize,
+        trace_len: usize,
+    },
+}
+
+/// Returns the number of columns in each extension trace segment
+pub fn extension_segment_widths<A: AirConfig>() -> Vec<usize> {
+    if A::EXTENSION_SEGMENTS.is_empty() {
+        let num_columns = A::NUM_EXTENSION_COLUMNS;
+        (num_columns != 0)
+            .then_some(num_columns)
+            .into_iter()
+            .collect()
+    } else {
+        A::EXTENSION_SEGMENTS
+            .iter()
+            .map(|s| s.num_columns)
+            .collect()
+    }
+}
+
 pub struct Air<AC: AirConfig> {
     constraints: Vec<Constraint<FieldVariant<AC::Fp, AC::Fq>>>,
     composition_constraint: CompositionConstraint<FieldVariant<AC::Fp, AC::Fq>>,
     ce_blowup_factor: usize,
     trace_len: usize,
+    padded_trace_len: usize,
+    extension_segments: Vec<TraceSegment>,
     options: ProofOptions,
     public_inputs: AC::PublicInputs,
 }
 
 impl<C: AirConfig> Air<C> {
-    pub fn new(trace_len: usize, public_inputs: C::PublicInputs, options: ProofOptions) -> Self {
+    pub fn new(
+        trace_len: usize,
+        public_inputs: C::PublicInputs,
+        options: ProofOptions,
+    ) -> Result<Self, Error> {
+        if !trace_len.is_power_of_two() {
+            return TraceLengthNotPowerOfTwoSnafu { trace_len }.fail();
+        }
+
+        // in zero knowledge mode the trace polynomials are interpolated over the
+        // trace and an equal number of random rows
+        let padded_trace_len = if options.zero_knowledge {
+            trace_len * 2
+        } else {
+            trace_len
+        };
+
         let constraints = C::constraints(trace_len);
-        let composition_constraint = C::composition_constraint(trace_len, &constraints);
-        let ce_blowup_factor = composition_constraint.blowup_factor(trace_len);
-        assert!(ce_blowup_factor <= options.lde_blowup_factor.into());
+        let composition_constraint = C::composition_constraint(padded_trace_len, &constraints);
+        let ce_blowup_factor = composition_constraint.blowup_factor(padded_trace_len);
+        let lde_blowup_factor: usize = options.lde_blowup_factor.into();
+        let ce_domain_size = padded_trace_len * ce_blowup_factor;
+        if ce_domain_size > trace_len * lde_blowup_factor {
+            return BlowupFactorTooSmallSnafu {
+                ce_blowup_factor: ce_domain_size / trace_len,
+                lde_blowup_factor,
+            }
+            .fail();
+        }
 
-        Self {
+        // queries are distinct positions in the lde domain
+        let num_queries = usize::from(options.num_queries);
+        let lde_domain_size = trace_len * lde_blowup_factor;
+        if num_queries > lde_domain_size {
+            return TooManyQueriesSnafu {
+                num_queries,
+                lde_domain_size,
+            }
+            .fail();
+        }
+
+        if options.zero_knowledge {
+            // FRI queries are only sound if the codeword has redundancy
+            let fri_blowup_factor = options.fri_blowup_factor();
+            if fri_blowup_factor < 2 {
+                return ZeroKnowledgeBlowupFactorTooSmallSnafu { fri_blowup_factor }.fail();
+            }
+
+            // the randomizers have `trace_len` random coefficients so they only hide
+            // the trace and composition polynomials at up to `trace_len` points
+            let num_openings = num_queries + num_trace_offsets(&constraints);
+            if num_openings > trace_len {
+                return TooManyZeroKnowledgeOpeningsSnafu {
+                    num_openings,
+                    trace_len,
+                }
+                .fail();
+            }
+        }
+
+        let extension_segments = if C::EXTENSION_SEGMENTS.is_empty() {
+            let num_challenges = num_challenges(&constraints);
+            extension_segment_widths::<C>()
+                .into_iter()
+                .map(|num_columns| TraceSegment::new(num_columns, num_challenges))
+                .collect()
+        } else {
+            C::EXTENSION_SEGMENTS.to_vec()
+        };
+        let num_extension_columns = C::NUM_EXTENSION_COLUMNS;
+        if extension_segments.iter().any(|s| s.num_columns == 0)
+            || extension_segments
+                .iter()
+                .map(|s| s.num_columns)
+                .sum::<usize>()
+                != num_extension_columns
+        {
+            return InvalidExtensionSegmentsSnafu {
+                num_extension_columns,
+            }
+            .fail();
+        }
+
+        Ok(Self {
             constraints,
             composition_constraint,
             ce_blowup_factor,
             trace_len,
+            padded_trace_len,
+            extension_segments,
             options,
             public_inputs,
-        }
+        })
     }
 
     pub const fn trace_len(&self) -> usize {
         self.trace_len
     }
 
+    /// Returns the number of rows the trace polynomials are interpolated over.
+    /// This is twice the trace length in zero knowledge mode since the trace is
+    /// padded with random rows and the same as the trace length otherwise.
+    pub const fn padded_trace_len(&self) -> usize {
+        self.padded_trace_len
+    }
+
     pub const fn options(&self) -> ProofOptions {
         self.options
     }
@@ -170,26 +339,50 @@ impl<C: AirConfig> Air<C> {
         &self.public_inputs
     }
 
+    /// Returns the blowup factor of the composition polynomial relative to the
+    /// degree of the trace polynomials
     pub const fn ce_blowup_factor(&self) -> usize {
         self.ce_blowup_factor
     }
 
+    /// Returns the number of segments the composition polynomial is split
+    /// into. Segment `i` holds the `trace_len` coefficients starting at
+    /// `i * trace_len` so `H(x) = Σ x^(i*trace_len) * h_i(x)`.
+    pub const fn num_composition_segments(&self) -> usize {
+        (self.composition_degree() + 1) / self.trace_len
+    }
+
+    /// Returns the number of columns in the composition trace. In zero
+    /// knowledge mode an extra column stores the DEEP composition mask.
+    pub const fn num_composition_trace_cols(&self) -> usize {
+        self.num_composition_segments() + self.options.zero_knowledge as usize
+    }
+
     /// Returns a degree that all constraint polynomials must be normalized to.
     pub const fn composition_degree(&self) -> usize {
-        let ce_domain_size = self.trace_len * self.ce_blowup_factor();
+        let ce_domain_size = self.padded_trace_len * self.ce_blowup_factor();
         ce_domain_size - 1
     }
 
+    pub const fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
     pub fn num_challenges(&self) -> usize {
-        let mut num_challenges = 0;
-        for constraint in &self.constraints {
-            constraint.traverse(&mut |node| {
-                if let Expr::Leaf(AlgebraicItem::Challenge(i)) = node {
-                    num_challenges = core::cmp::max(num_challenges, *i + 1);
-                }
-            });
-        }
-        num_challenges
+        num_challenges(&self.constraints)
+    }
+
+    /// Returns the extension trace segments in the order they're committed to
+    pub fn extension_segments(&self) -> &[TraceSegment] {
+        &self.extension_segments
+    }
+
+    /// Returns the number of challenges drawn after the last extension trace
+    /// segment is committed to
+    pub fn num_trailing_challenges(&self) -> usize {
+        let num_segment_challenges = self.extension_segments.iter().map(|s| s.num_challenges);
+        self.num_challenges()
+            .saturating_sub(num_segment_challenges.sum())
     }
 
     pub fn gen_hints(&self, challenges: &Challenges<C::Fq>) -> Hints<C::Fq> {
@@ -221,9 +414,9 @@ impl<C: AirConfig> Air<C> {
     /// Constraint evaluation domain
     pub fn ce_domain(&self) -> Radix2EvaluationDomain<C::Fp> {
         let offset = C::domain_offset();
-        let trace_len = self.trace_len();
+        let padded_trace_len = self.padded_trace_len();
         let blowup_factor = self.ce_blowup_factor();
-        Radix2EvaluationDomain::new_coset(trace_len * blowup_factor, offset).unwrap()
+        Radix2EvaluationDomain::new_coset(padded_trace_len * blowup_factor, offset).unwrap()
     }
 
     /// Low degree extension domain
@@ -239,9 +432,35 @@ impl<C: AirConfig> Air<C> {
     }
 
     pub fn trace_arguments(&self) -> BTreeSet<(usize, isize)> {
-        self.constraints
-            .iter()
-            .map(Constraint::trace_arguments)
-            .fold(BTreeSet::new(), |a, b| &a | &b)
+        trace_arguments(&self.constraints)
+    }
+}
+
+/// Returns the `(column, offset)` pairs the constraints reference
+pub(crate) fn trace_arguments<T>(constraints: &[Constraint<T>]) -> BTreeSet<(usize, isize)> {
+    constraints
+        .iter()
+        .map(Constraint::trace_arguments)
+        .fold(BTreeSet::new(), |a, b| &a | &b)
+}
+
+/// Returns the number of distinct row offsets the trace is opened at
+fn num_trace_offsets<T>(constraints: &[Constraint<T>]) -> usize {
+    let offsets = constraints
+        .iter()
+        .flat_map(Constraint::trace_arguments)
+        .map(|(_, offset)| offset);
+    offsets.collect::<BTreeSet<isize>>().len()
+}
+
+fn num_challenges<T>(constraints: &[Constraint<T>]) -> usize {
+    let mut num_challenges = 0;
+    for constraint in constraints {
+        constraint.traverse(&mut |node| {
+            if let Expr::Leaf(AlgebraicItem::Challenge(i)) = node {
+                num_challenges = core::cmp::max(num_challenges, *i + 1);
+            }
+        });
     }
+    num_challenges
 }
@@ -0,0 +1,407 @@
+//! Multiset permutation and evaluation arguments
+//!
+//! A permutation argument keeps a running product of `α - c` over the rows of a
+//! table where `c` is a compressed tuple of values. Two tables hold the same
+//! multiset of tuples if their running products end with the same value.
+//!
+//! An evaluation argument keeps a running evaluation `e' = γ*e + c` over the
+//! rows of a table. The final value is the evaluation of a polynomial, with the
+//! table's tuples as coefficients, at `γ`. This checks the table holds a
+//! sequence which is usually public e.g. the inputs or outputs of a program.
+//!
+//! Tuples are compressed into a single value `v_0 + β*v_1 + β^2*v_2 + ...`.
+//! An optional selector, which must be zero or one, chooses the rows that are
+//! part of an argument.
+use crate::challenges::Challenges;
+use crate::constraints::AlgebraicItem;
+use crate::constraints::Constraint;
+use crate::constraints::ExecutionTraceColumn;
+use crate::constraints::Hint;
+use crate::constraints::VerifierChallenge;
+use crate::expression::Expr;
+use crate::selectors::every_row_except_last;
+use crate::selectors::first_row;
+use crate::selectors::last_row;
+use crate::trace::Trace;
+use crate::utils::FieldVariant;
+use crate::utils::GpuAllocator;
+use crate::utils::GpuVec;
+use crate::Matrix;
+use crate::StarkExtensionOf;
+use alloc::vec::Vec;
+use ark_ff::FftField;
+use ark_ff::Field;
+use ark_ff::Zero;
+use ark_poly::EvaluationDomain;
+use ark_poly::Radix2EvaluationDomain;
+use ministark_gpu::GpuFftField;
+
+/// Expression over the execution trace that is part of an argument
+pub type ArgumentExpr<Fp, Fq> = Expr<AlgebraicItem<FieldVariant<Fp, Fq>>>;
+
+/// Running product of the tuples in a table
+#[derive(Clone)]
+pub struct PermutationArgument<Fp: 'static, Fq: 'static> {
+    alpha: usize,
+    beta: usize,
+    column: usize,
+    values: Vec<ArgumentExpr<Fp, Fq>>,
+    selector: Option<ArgumentExpr<Fp, Fq>>,
+}
+
+impl<Fp, Fq> PermutationArgument<Fp, Fq> {
+    /// The running product of `α - (v_0 + β*v_1 + ...)` over the tuple `values`
+    /// is stored in `column`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tuple is empty
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(
+        alpha: impl VerifierChallenge,
+        beta: impl VerifierChallenge,
+        column: impl ExecutionTraceColumn,
+        values: impl IntoIterator<Item = ArgumentExpr<Fp, Fq>>,
+    ) -> Self {
+        let values = Vec::from_iter(values);
+        assert!(!values.is_empty(), "permuted tuple is empty");
+        Self {
+            alpha: alpha.index(),
+            beta: beta.index(),
+            column: column.index(),
+            values,
+            selector: None,
+        }
+    }
+
+    /// Only includes rows where `selector` is one. The selector must be zero
+    /// or one in every row.
+    pub fn with_selector(mut self, selector: ArgumentExpr<Fp, Fq>) -> Self {
+        self.selector = Some(selector);
+        self
+    }
+
+    /// Returns the index of the column that holds the running product
+    pub const fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl<Fp: GpuFftField<FftField = Fp> + FftField, Fq: StarkExtensionOf<Fp>>
+    PermutationArgument<Fp, Fq>
+{
+    /// Returns the running product of a table that isn't in the trace. Used as
+    /// the hint of [`PermutationArgument::terminal_constraint`].
+    pub fn terminal<'a>(
+        &self,
+        challenges: &Challenges<Fq>,
+        rows: impl IntoIterator<Item = &'a [Fq]>,
+    ) -> Fq {
+        let alpha = challenges[self.alpha];
+        (rows.into_iter())
+            .map(|row| alpha - compress_values(challenges, self.beta, row))
+            .product()
+    }
+
+    /// Returns the constraints that check the running product. The running
+    /// product includes the tuple of its own row.
+    pub fn constraints(&self, trace_len: usize) -> Vec<Constraint<FieldVariant<Fp, Fq>>> {
+        let column = self.column;
+        vec![
+            Constraint::new((column.curr() - self.factor(0)) * first_row()),
+            Constraint::new(
+                (column.next() - column.curr() * self.factor(1)) * every_row_except_last(trace_len),
+            ),
+        ]
+    }
+
+    /// Returns the constraint that the running product ends with `terminal`
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn terminal_constraint(
+        &self,
+        trace_len: usize,
+        terminal: impl Hint,
+    ) -> Constraint<FieldVariant<Fp, Fq>> {
+        Constraint::new((self.column.curr() - terminal.hint()) * last_row(trace_len))
+    }
+
+    /// Returns the constraint that the tuples of this table are a permutation
+    /// of the tuples of `other`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the arguments use different challenges
+    pub fn permutation_constraint(
+        &self,
+        trace_len: usize,
+        other: &Self,
+    ) -> Constraint<FieldVariant<Fp, Fq>> {
+        assert_eq!((self.alpha, self.beta), (other.alpha, other.beta));
+        Constraint::new((self.column.curr() - other.column.curr()) * last_row(trace_len))
+    }
+
+    /// Builds the running product column. Trace columns after the base columns
+    /// are read from the extension trace `segments` built so far.
+    pub fn build_column(
+        &self,
+        trace: &impl Trace<Fp = Fp, Fq = Fq>,
+        challenges: &Challenges<Fq>,
+        segments: &[Matrix<Fq>],
+    ) -> Matrix<Fq> {
+        let rows = RowEvaluator::new(trace, challenges, segments);
+        let factor = self.factor(0);
+        let mut acc = Fq::one();
+        let mut column = Vec::with_capacity_in(trace.len(), GpuAllocator);
+        for row in 0..trace.len() {
+            acc *= rows.eval(row, &factor);
+            column.push(acc);
+        }
+        Matrix::new(vec![column])
+    }
+
+    /// Returns the factor the running product is multiplied by in a row
+    fn factor(&self, offset: isize) -> ArgumentExpr<Fp, Fq> {
+        let values = self.values.iter().map(|v| shift(v, offset));
+        let factor = self.alpha.challenge() - compress(self.beta, &values.collect::<Vec<_>>());
+        match &self.selector {
+            Some(selector) => {
+                let selector = shift(selector, offset);
+                let one = AlgebraicItem::Constant(FieldVariant::Fp(Fp::one()));
+                &selector * &factor - selector + one
+            }
+            None => factor,
+        }
+    }
+}
+
+/// Running evaluation of the tuples in a table
+#[derive(Clone)]
+pub struct EvaluationArgument<Fp: 'static, Fq: 'static> {
+    gamma: usize,
+    beta: usize,
+    column: usize,
+    values: Vec<ArgumentExpr<Fp, Fq>>,
+    selector: Option<ArgumentExpr<Fp, Fq>>,
+}
+
+impl<Fp, Fq> EvaluationArgument<Fp, Fq> {
+    /// The running evaluation `e' = γ*e + (v_0 + β*v_1 + ...)` over the tuple
+    /// `values` is stored in `column`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tuple is empty
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(
+        gamma: impl VerifierChallenge,
+        beta: impl VerifierChallenge,
+        column: impl ExecutionTraceColumn,
+        values: impl IntoIterator<Item = ArgumentExpr<Fp, Fq>>,
+    ) -> Self {
+        let values = Vec::from_iter(values);
+        assert!(!values.is_empty(), "evaluated tuple is empty");
+        Self {
+            gamma: gamma.index(),
+            beta: beta.index(),
+            column: column.index(),
+            values,
+            selector: None,
+        }
+    }
+
+    /// Only includes rows where `selector` is one. The selector must be zero
+    /// or one in every row.
+    pub fn with_selector(mut self, selector: ArgumentExpr<Fp, Fq>) -> Self {
+        self.selector = Some(selector);
+        self
+    }
+
+    /// Returns the index of the column that holds the running evaluation
+    pub const fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl<Fp: GpuFftField<FftField = Fp> + FftField, Fq: StarkExtensionOf<Fp>>
+    EvaluationArgument<Fp, Fq>
+{
+    /// Returns the running evaluation of a sequence of tuples e.g. the public
+    /// inputs of a program. Used as the hint of
+    /// [`EvaluationArgument::terminal_constraint`].
+    pub fn terminal<'a>(
+        &self,
+        challenges: &Challenges<Fq>,
+        rows: impl IntoIterator<Item = &'a [Fq]>,
+    ) -> Fq {
+        let gamma = challenges[self.gamma];
+        (rows.into_iter()).fold(Fq::zero(), |acc, row| {
+            acc * gamma + compress_values(challenges, self.beta, row)
+        })
+    }
+
+    /// Returns the constraints that check the running evaluation. The running
+    /// evaluation includes the tuple of its own row.
+    pub fn constraints(&self, trace_len: usize) -> Vec<Constraint<FieldVariant<Fp, Fq>>> {
+        let column = self.column;
+        let (first, next) = (self.compressed(0), self.compressed(1));
+        let gamma = self.gamma.challenge();
+        let (first, update) = match &self.selector {
+            Some(selector) => (
+                selector.clone() * first,
+                shift(selector, 1) * (gamma * column.curr() + next - column.curr()),
+            ),
+            None => (first, gamma * column.curr() + next - column.curr()),
+        };
+        vec![
+            Constraint::new((column.curr() - first) * first_row()),
+            Constraint::new(
+                (column.next() - column.curr() - update) * every_row_except_last(trace_len),
+            ),
+        ]
+    }
+
+    /// Returns the constraint that the running evaluation ends with `terminal`
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn terminal_constraint(
+        &self,
+        trace_len: usize,
+        terminal: impl Hint,
+    ) -> Constraint<FieldVariant<Fp, Fq>> {
+        Constraint::new((self.column.curr() - terminal.hint()) * last_row(trace_len))
+    }
+
+    /// Builds the running evaluation column. Trace columns after the base
+    /// columns are read from the extension trace `segments` built so far.
+    pub fn build_column(
+        &self,
+        trace: &impl Trace<Fp = Fp, Fq = Fq>,
+        challenges: &Challenges<Fq>,
+        segments: &[Matrix<Fq>],
+    ) -> Matrix<Fq> {
+        let rows = RowEvaluator::new(trace, challenges, segments);
+        let gamma = challenges[self.gamma];
+        let compressed = self.compressed(0);
+        let mut acc = Fq::zero();
+        let mut column = Vec::with_capacity_in(trace.len(), GpuAllocator);
+        for row in 0..trace.len() {
+            // every row is selected without a selector (avoids the newer `is_none_or`)
+            #[allow(clippy::unnecessary_map_or)]
+            let is_selected =
+                (self.selector.as_ref()).map_or(true, |s| !rows.eval(row, s).is_zero());
+            if is_selected {
+                acc = acc * gamma + rows.eval(row, &compressed);
+            }
+            column.push(acc);
+        }
+        Matrix::new(vec![column])
+    }
+
+    /// Returns the compressed tuple of a row
+    fn compressed(&self, offset: isize) -> ArgumentExpr<Fp, Fq> {
+        let values = self.values.iter().map(|v| shift(v, offset));
+        compress(self.beta, &values.collect::<Vec<_>>())
+    }
+}
+
+/// Compresses a tuple into a single value `v_0 + β*v_1 + β^2*v_2 + ...`
+///
+/// # Panics
+///
+/// Panics if the tuple is empty
+pub(crate) fn compress<Fp: GpuFftField<FftField = Fp> + FftField, Fq: StarkExtensionOf<Fp>>(
+    beta: usize,
+    values: &[ArgumentExpr<Fp, Fq>],
+) -> ArgumentExpr<Fp, Fq> {
+    // β is only referenced, and therefore only drawn, for tuples
+    let (last, rest) = values.split_last().expect("tuple is empty");
+    let beta = beta.challenge();
+    (rest.iter()).rfold(last.clone(), |acc, value| acc * &beta + value)
+}
+
+/// Compresses a tuple of values in the same way as [`compress`]
+///
+/// # Panics
+///
+/// Panics if the tuple is empty
+pub(crate) fn compress_values<F: Field>(
+    challenges: &Challenges<F>,
+    beta: usize,
+    values: &[F],
+) -> F {
+    let (&last, rest) = values.split_last().expect("tuple is empty");
+    (rest.iter()).rfold(last, |acc, &value| acc * challenges[beta] + value)
+}
+
+/// Returns an expression that references the trace `offset` rows later
+fn shift<Fp: GpuFftField<FftField = Fp> + FftField, Fq: StarkExtensionOf<Fp>>(
+    expr: &ArgumentExpr<Fp, Fq>,
+    offset: isize,
+) -> ArgumentExpr<Fp, Fq> {
+    expr.map_leaves(&mut |&leaf| match leaf {
+        AlgebraicItem::Trace(column, row_offset) => {
+            AlgebraicItem::Trace(column, row_offset + offset)
+        }
+        leaf => leaf,
+    })
+}
+
+/// Evaluates expressions at the rows of an execution trace
+pub(crate) struct RowEvaluator<'a, Fp: FftField, Fq: Field> {
+    base_columns: &'a Matrix<Fp>,
+    extension_columns: Vec<&'a GpuVec<Fq>>,
+    challenges: &'a Challenges<Fq>,
+    trace_xs: Radix2EvaluationDomain<Fp>,
+}
+
+impl<'a, Fp: GpuFftField<FftField = Fp> + FftField, Fq: StarkExtensionOf<Fp>>
+    RowEvaluator<'a, Fp, Fq>
+{
+    /// Trace columns after the base columns are read from `segments`
+    pub(crate) fn new(
+        trace: &'a impl Trace<Fp = Fp, Fq = Fq>,
+        challenges: &'a Challenges<Fq>,
+        segments: &'a [Matrix<Fq>],
+    ) -> Self {
+        Self {
+            base_columns: trace.base_columns(),
+            extension_columns: segments.iter().flat_map(|segment| &segment.0).collect(),
+            challenges,
+            trace_xs: Radix2EvaluationDomain::new(trace.len()).unwrap(),
+        }
+    }
+
+    /// Evaluates `expr` at `row`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the expression references a hint
+    pub(crate) fn eval(&self, row: usize, expr: &ArgumentExpr<Fp, Fq>) -> Fq {
+        expr.eval(&mut |leaf| self.leaf(row, leaf)).as_fq()
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn leaf(&self, row: usize, leaf: &AlgebraicItem<FieldVariant<Fp, Fq>>) -> FieldVariant<Fp, Fq> {
+        use AlgebraicItem::*;
+        let trace_len = self.base_columns.num_rows();
+        let num_base_columns = self.base_columns.num_cols();
+        match leaf {
+            X => FieldVariant::Fp(self.trace_xs.element(row)),
+            &Constant(c) => c,
+            &Challenge(i) => FieldVariant::Fq(self.challenges[i]),
+            &Trace(column, offset) => {
+                let row = (row as isize + offset).rem_euclid(trace_len as isize) as usize;
+                if column < num_base_columns {
+                    FieldVariant::Fp(self.base_columns.0[column][row])
+                } else {
+                    FieldVariant::Fq(self.extension_columns[column - num_base_columns][row])
+                }
+            }
+            Periodic(column) => {
+                let x = self.trace_xs.element(row);
+                let point = FieldVariant::Fp(x.pow([(trace_len / column.interval_size()) as u64]));
+                (column.coeffs().iter()).rfold(FieldVariant::zero(), |acc, &c| acc * point + c)
+            }
+            Hint(_) => panic!("hints can't be evaluated over the trace"),
+        }
+    }
+}
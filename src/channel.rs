@@ -2,6 +2,8 @@ use crate::challenges::Challenges;
 use crate::fri;
 use crate::fri::FriProof;
 use crate::hints::Hints;
+use crate::merkle::MerkleTree;
+use crate::prover::ProvingError;
 use crate::random::PublicCoin;
 use crate::stark::Stark;
 use crate::trace::Queries;
@@ -16,10 +18,10 @@ use std::collections::BTreeSet;
 pub struct ProverChannel<'a, S: Stark> {
     air: &'a Air<S::AirConfig>,
     pub public_coin: S::PublicCoin,
-    base_trace_commitment: S::Digest,
-    extension_trace_commitment: Option<S::Digest>,
-    composition_trace_commitment: S::Digest,
-    fri_layer_commitments: Vec<S::Digest>,
+    base_trace_commitment: <S::MerkleTree as MerkleTree>::Root,
+    extension_trace_commitments: Vec<<S::MerkleTree as MerkleTree>::Root>,
+    composition_trace_commitment: <S::MerkleTree as MerkleTree>::Root,
+    fri_layer_commitments: Vec<<S::MerkleTree as MerkleTree>::Root>,
     fri_remainder_coeffs: Vec<S::Fq>,
     execution_trace_ood_evals: Vec<S::Fq>,
     composition_trace_ood_evals: Vec<S::Fq>,
@@ -32,9 +34,9 @@ impl<'a, S: Stark> ProverChannel<'a, S> {
         ProverChannel {
             air,
             public_coin,
-            extension_trace_commitment: None,
-            base_trace_commitment: S::Digest::default(),
-            composition_trace_commitment: S::Digest::default(),
+            extension_trace_commitments: Vec::new(),
+            base_trace_commitment: Default::default(),
+            composition_trace_commitment: Default::default(),
             execution_trace_ood_evals: Vec::new(),
             composition_trace_ood_evals: Vec::new(),
             fri_layer_commitments: Vec::new(),
@@ -43,18 +45,19 @@ impl<'a, S: Stark> ProverChannel<'a, S> {
         }
     }
 
-    pub fn commit_base_trace(&mut self, commitment: S::Digest) {
-        self.public_coin.reseed_with_digest(&commitment);
+    pub fn commit_base_trace(&mut self, commitment: <S::MerkleTree as MerkleTree>::Root) {
+        self.public_coin.reseed_with_commitment(&commitment);
         self.base_trace_commitment = commitment;
     }
 
-    pub fn commit_extension_trace(&mut self, commitment: S::Digest) {
-        self.public_coin.reseed_with_digest(&commitment);
-        self.extension_trace_commitment = Some(commitment);
+    /// Commits to the next extension trace segment
+    pub fn commit_extension_trace(&mut self, commitment: <S::MerkleTree as MerkleTree>::Root) {
+        self.public_coin.reseed_with_commitment(&commitment);
+        self.extension_trace_commitments.push(commitment);
     }
 
-    pub fn commit_composition_trace(&mut self, commitment: S::Digest) {
-        self.public_coin.reseed_with_digest(&commitment);
+    pub fn commit_composition_trace(&mut self, commitment: <S::MerkleTree as MerkleTree>::Root) {
+        self.public_coin.reseed_with_commitment(&commitment);
         self.composition_trace_commitment = commitment;
     }
 
@@ -73,27 +76,28 @@ impl<'a, S: Stark> ProverChannel<'a, S> {
         self.composition_trace_ood_evals = composition_trace_oods;
     }
 
-    pub fn grind_fri_commitments(&mut self) {
+    pub fn grind_fri_commitments(&mut self) -> Result<(), ProvingError> {
         let grinding_factor = self.air.options().grinding_factor;
         if grinding_factor == 0 {
             // skip if there is no grinding required
-            return;
+            return Ok(());
         }
 
         let nonce = self
             .public_coin
             .grind_proof_of_work(grinding_factor)
-            .expect("nonce not found");
-        assert!(self
-            .public_coin
-            .verify_proof_of_work(grinding_factor, nonce));
+            .filter(|&nonce| {
+                self.public_coin
+                    .verify_proof_of_work(grinding_factor, nonce)
+            })
+            .ok_or(ProvingError::ProofOfWorkNotFound { grinding_factor })?;
 
         self.pow_nonce = nonce;
         self.public_coin.reseed_with_int(self.pow_nonce);
+        Ok(())
     }
 
     pub fn get_fri_query_positions(&mut self) -> BTreeSet<usize> {
-        // TODO: voulnerability if multiple positions are the same
         let lde_domain_size = self.air.trace_len() * self.air.lde_blowup_factor();
         let num_queries = self.air.options().num_queries as usize;
         self.public_coin.draw_queries(num_queries, lde_domain_size)
@@ -102,13 +106,13 @@ impl<'a, S: Stark> ProverChannel<'a, S> {
     pub fn build_proof(
         self,
         trace_queries: Queries<S>,
-        fri_proof: FriProof<S::Fq, S::Digest, S::MerkleTree>,
+        fri_proof: FriProof<S::Fq, <S::MerkleTree as MerkleTree>::Root, S::MerkleTree>,
     ) -> Proof<S> {
         Proof {
             options: self.air.options(),
             trace_len: self.air.trace_len(),
             base_trace_commitment: self.base_trace_commitment,
-            extension_trace_commitment: self.extension_trace_commitment,
+            extension_trace_commitments: self.extension_trace_commitments,
             composition_trace_commitment: self.composition_trace_commitment,
             execution_trace_ood_evals: self.execution_trace_ood_evals,
             composition_trace_ood_evals: self.composition_trace_ood_evals,
@@ -122,11 +126,11 @@ impl<'a, S: Stark> ProverChannel<'a, S> {
 // FRI prover channel implementation
 // Inspired by Winterfell: https://github.com/facebook/winterfell/blob/main/fri/src/prover/channel.rs
 impl<'a, S: Stark> fri::ProverChannel for ProverChannel<'a, S> {
-    type Digest = S::Digest;
+    type Commitment = <S::MerkleTree as MerkleTree>::Root;
     type Field = S::Fq;
 
-    fn commit_fri_layer(&mut self, commitment: S::Digest) {
-        self.public_coin.reseed_with_digest(&commitment);
+    fn commit_fri_layer(&mut self, commitment: Self::Commitment) {
+        self.public_coin.reseed_with_commitment(&commitment);
         self.fri_layer_commitments.push(commitment);
     }
 
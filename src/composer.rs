@@ -20,6 +20,7 @@ pub struct DeepPolyComposer<'a, A: AirConfig> {
     base_trace_polys: Matrix<A::Fp>,
     extension_trace_polys: Option<Matrix<A::Fq>>,
     composition_trace_polys: Matrix<A::Fq>,
+    deep_mask: Option<GpuVec<A::Fq>>,
 }
 
 impl<'a, A: AirConfig> DeepPolyComposer<'a, A> {
@@ -29,6 +30,7 @@ impl<'a, A: AirConfig> DeepPolyComposer<'a, A> {
         base_trace_polys: Matrix<A::Fp>,
         extension_trace_polys: Option<Matrix<A::Fq>>,
         composition_trace_polys: Matrix<A::Fq>,
+        deep_mask: Option<GpuVec<A::Fq>>,
     ) -> Self {
         Self {
             z,
@@ -36,6 +38,7 @@ impl<'a, A: AirConfig> DeepPolyComposer<'a, A> {
             base_trace_polys,
             extension_trace_polys,
             composition_trace_polys,
+            deep_mask,
         }
     }
 
@@ -47,6 +50,7 @@ impl<'a, A: AirConfig> DeepPolyComposer<'a, A> {
             base_trace_polys,
             extension_trace_polys,
             composition_trace_polys,
+            ..
         } = self;
 
         let trace_domain = air.trace_domain();
@@ -77,9 +81,8 @@ impl<'a, A: AirConfig> DeepPolyComposer<'a, A> {
             .collect();
 
         // generate ood evaluations for the composition trace polynomials
-        let z_n = self.z.pow([composition_trace_polys.num_cols() as u64]);
         let composition_trace_evals = ark_std::cfg_iter!(composition_trace_polys)
-            .map(|column| horner_evaluate(column, &z_n))
+            .map(|column| horner_evaluate(column, &*z))
             .collect();
 
         (execution_trace_evals, composition_trace_evals)
@@ -93,6 +96,7 @@ impl<'a, A: AirConfig> DeepPolyComposer<'a, A> {
             base_trace_polys,
             extension_trace_polys,
             composition_trace_polys,
+            deep_mask,
         } = self;
 
         let DeepCompositionCoeffs {
@@ -106,11 +110,10 @@ impl<'a, A: AirConfig> DeepPolyComposer<'a, A> {
         let g_inv = trace_domain.group_gen_inv();
 
         // divide out OOD point from composition trace polys
-        let z_n = self.z.pow([composition_trace_polys.num_cols() as u64]);
         let composition_trace_quotients = ark_std::cfg_into_iter!(composition_trace_polys.0)
             .zip(composition_trace_alphas)
             .map(|(mut coeffs, alpha)| {
-                divide_out_point_into(&mut coeffs, &z_n, &alpha);
+                divide_out_point_into(&mut coeffs, &z, &alpha);
                 coeffs
             });
 
@@ -184,6 +187,16 @@ impl<'a, A: AirConfig> DeepPolyComposer<'a, A> {
             }
         }
 
+        // mask the DEEP composition polynomial (zero knowledge mode only)
+        if let Some(mask) = deep_mask {
+            if combined_coeffs.len() < mask.len() {
+                combined_coeffs.resize(mask.len(), A::Fq::zero());
+            }
+            for (coeff, mask_coeff) in combined_coeffs.iter_mut().zip(mask) {
+                *coeff += mask_coeff;
+            }
+        }
+
         Matrix::new(vec![combined_coeffs])
     }
 }
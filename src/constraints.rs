@@ -1,8 +1,11 @@
 use crate::expression::Expr;
+use crate::hash::HashFn;
 use crate::utils;
 use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
 use ark_ff::One;
 use ark_ff::Zero;
+use ark_serialize::CanonicalSerialize;
 use core::iter::Product;
 use core::iter::Sum;
 use core::ops::Add;
@@ -327,6 +330,52 @@ impl<T: Clone + Copy + Zero + Ord + Hash> CompositionConstraint<T> {
     }
 }
 
+impl<T: CanonicalSerialize> CompositionConstraint<T> {
+    /// Returns a hash of a canonical encoding of the composition constraint.
+    /// The expression is encoded in postfix order so the encoding is unique.
+    pub fn canonical_hash<H: HashFn>(&self) -> H::Digest {
+        let mut bytes = Vec::new();
+        self.0.traverse(&mut |node| encode_node(node, &mut bytes));
+        H::hash_chunks([&*bytes])
+    }
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn encode_node<T: CanonicalSerialize>(node: &Expr<CompositionItem<T>>, bytes: &mut Vec<u8>) {
+    use AlgebraicItem::*;
+    let mut write = |tag: u8, values: &[u64]| {
+        bytes.push(tag);
+        for value in values {
+            bytes.extend(value.to_le_bytes());
+        }
+    };
+    match node {
+        Expr::Leaf(CompositionItem::CompositionCoeff(i)) => write(0, &[*i as u64]),
+        Expr::Leaf(CompositionItem::Item(X)) => write(1, &[]),
+        Expr::Leaf(CompositionItem::Item(Constant(v))) => {
+            write(2, &[]);
+            v.serialize_compressed(&mut *bytes).unwrap();
+        }
+        Expr::Leaf(CompositionItem::Item(Challenge(i))) => write(3, &[*i as u64]),
+        Expr::Leaf(CompositionItem::Item(Periodic(col))) => {
+            let coeffs = col.coeffs();
+            write(4, &[col.interval_size() as u64, coeffs.len() as u64]);
+            for coeff in coeffs {
+                coeff.serialize_compressed(&mut *bytes).unwrap();
+            }
+        }
+        Expr::Leaf(CompositionItem::Item(Hint(i))) => write(5, &[*i as u64]),
+        Expr::Leaf(CompositionItem::Item(Trace(column, offset))) => {
+            write(6, &[*column as u64, *offset as u64]);
+        }
+        Expr::Neg(_) => write(7, &[]),
+        Expr::Add(_, _) => write(8, &[]),
+        Expr::Mul(_, _) => write(9, &[]),
+        Expr::Div(_, _) => write(10, &[]),
+        Expr::Pow(_, exponent) => write(11, &[*exponent as u64]),
+    }
+}
+
 impl<T> Deref for CompositionConstraint<T> {
     type Target = Expr<CompositionItem<T>>;
 
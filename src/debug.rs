@@ -1,128 +1,238 @@
 //! Tools for debugging issues that may arrive with AIR or STARK
-//! TODO:
 
+use crate::air::trace_domain;
+use crate::air::AirConfig;
 use crate::challenges::Challenges;
+use crate::constraints::AlgebraicItem;
+use crate::expression::Expr;
 use crate::hints::Hints;
 use crate::stark::Stark;
+use crate::utils::FieldVariant;
 use crate::Matrix;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use ark_ff::Field;
+use ark_ff::Zero;
+use ark_poly::EvaluationDomain;
+use core::fmt::Display;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use snafu::Snafu;
 
-/// Checks AIR constraints are valid
-pub const fn default_validate_constraints<S: Stark>(
+/// Errors that are returned when an execution trace does not satisfy the AIR
+#[derive(Debug, Snafu)]
+pub enum ConstraintError {
+    #[snafu(display(
+        "constraint {constraint} does not evaluate to a low degree polynomial. Divide by zero \
+         occurs at row {row}. Expression values: {}",
+        join(values)
+    ))]
+    UnsatisfiedConstraint {
+        constraint: usize,
+        row: usize,
+        values: Vec<LeafValue>,
+    },
+    #[snafu(display(
+        "constraint {constraint} references execution trace column {column} but there are only \
+         {num_columns} columns"
+    ))]
+    ColumnOutOfBounds {
+        constraint: usize,
+        column: usize,
+        num_columns: usize,
+    },
+    #[snafu(display("constraint {constraint} references challenge {index} which does not exist"))]
+    MissingChallenge { constraint: usize, index: usize },
+    #[snafu(display("constraint {constraint} references hint {index} which does not exist"))]
+    MissingHint { constraint: usize, index: usize },
+}
+
+/// A leaf of a constraint expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Leaf {
+    X,
+    Trace {
+        column: usize,
+        offset: isize,
+    },
+    Challenge(usize),
+    Hint(usize),
+    Periodic {
+        interval_size: usize,
+        num_coeffs: usize,
+    },
+}
+
+impl Display for Leaf {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::X => write!(f, "x"),
+            Self::Trace { column, offset } => {
+                write!(f, "Trace(col={column:0>3}, offset={offset:0>3})")
+            }
+            Self::Challenge(i) => write!(f, "Challenge({i})"),
+            Self::Hint(i) => write!(f, "Hint({i})"),
+            Self::Periodic {
+                interval_size,
+                num_coeffs,
+            } => write!(f, "Periodic(interval={interval_size}, coeffs={num_coeffs})"),
+        }
+    }
+}
+
+/// The value of a constraint leaf at the row a constraint failed
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LeafValue {
+    pub leaf: Leaf,
+    pub value: String,
+}
+
+impl Display for LeafValue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} = {}", self.leaf, self.value)
+    }
+}
+
+fn join(values: &[LeafValue]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Checks each AIR constraint holds over the trace domain.
+///
+/// Evaluates every constraint returned by [`AirConfig::constraints`] at each
+/// row of the execution trace. A constraint is unsatisfied if, at some row, its
+/// denominator is zero but its numerator is not.
+#[allow(clippy::too_many_lines, clippy::cast_possible_wrap)]
+pub fn default_validate_constraints<S: Stark>(
     _this: &S,
-    _challenges: &Challenges<S::Fq>,
-    _hints: &Hints<S::Fq>,
-    _base_trace: &Matrix<S::Fp>,
-    _extension_trace: Option<&Matrix<S::Fq>>,
-) {
-    // ```text
-    // TODO: move constraint checking from air.rs into here
-    // #[cfg(all(feature = "std", debug_assertions))]
-    // fn validate_constraints(
-    //     &self,
-    //     challenges: &Challenges<C::Fq>,
-    //     hints: &Hints<C::Fq>,
-    //     base_trace: &crate::Matrix<C::Fp>,
-    //     extension_trace: Option<&crate::Matrix<C::Fq>>,
-    // ) {
-    //     use AlgebraicItem::*;
-    //     use Expr::*;
-
-    //     let num_execution_trace_columns = C::NUM_BASE_COLUMNS +
-    // C::NUM_EXTENSION_COLUMNS;     let mut col_indicies = vec![false;
-    // num_execution_trace_columns];     let mut challenge_indicies =
-    // vec![false; challenges.len()];     let mut hint_indicies =
-    // vec![false; hints.len()];
-
-    //     for constraint in &self.constraints {
-    //         constraint.traverse(&mut |node| match node {
-    //             Leaf(Challenge(i)) => challenge_indicies[*i] = true,
-    //             Leaf(Trace(i, _)) => col_indicies[*i] = true,
-    //             Leaf(Hint(i)) => hint_indicies[*i] = true,
-    //             _ => {}
-    //         })
-    //     }
-
-    //     for (index, exists) in col_indicies.into_iter().enumerate() {
-    //         if !exists {
-    //             // TODO: make assertion
-    //             println!("WARN: no constraints for execution trace column
-    // {index}");         }
-    //     }
-
-    //     for (index, exists) in challenge_indicies.into_iter().enumerate()
-    // {         if !exists {
-    //             // TODO: make assertion
-    //             println!("WARN: challenge at index {index} never used");
-    //         }
-    //     }
-
-    //     for (index, exists) in hint_indicies.into_iter().enumerate() {
-    //         if !exists {
-    //             // TODO: make assertion
-    //             println!("WARN: hint at index {index} never used");
-    //         }
-    //     }
-
-    //     let trace_domain = self.trace_domain();
-    //     let base_column_range = Self::base_column_range();
-    //     let extension_column_range = Self::extension_column_range();
-
-    //     // helper function to get a value from the execution trace
-    //     let get_trace_value = |row: usize, col: usize, offset: isize| {
-    //         let pos = (row as isize +
-    // offset).rem_euclid(trace_domain.size() as isize) as usize;
-    // if base_column_range.contains(&col) {
-    // FieldVariant::Fp(base_trace.0[col][pos])         } else if
-    // extension_column_range.contains(&col) {             let col =
-    // col - C::NUM_BASE_COLUMNS;
-    // FieldVariant::Fq(extension_trace.unwrap().0[col][pos])
-    //         } else {
-    //             unreachable!("requested column {col} does not exist")
-    //         }
-    //     };
-
-    //     for (c_idx, constraint) in
-    // self.constraints().into_iter().enumerate() {         for
-    // (row, x) in trace_domain.elements().enumerate() {
-    // let is_valid = constraint                 .check(&mut |leaf|
-    // match leaf {                     X => FieldVariant::Fp(x),
-    //                     &Hint(i) => FieldVariant::Fq(hints[i]),
-    //                     &Challenge(i) => FieldVariant::Fq(challenges[i]),
-    //                     &Trace(col, offset) => get_trace_value(row, col,
-    // offset),                     &Constant(c) => c,
-    //                 })
-    //                 .is_some();
-
-    //             if !is_valid {
-    //                 let mut vals = vec![format!("x = {x}")];
-    //                 constraint.traverse(&mut |node| match *node {
-    //                     // get a description of each leaf node
-    //                     Leaf(Trace(col, offset)) => vals.push(format!(
-    //                         "Trace(col={col:0>3}, offset={offset:0>3}) =
-    // {}",                         get_trace_value(row, col,
-    // offset)                     )),
-    //                     Leaf(Challenge(i)) => {
-    //                         vals.push(format!("Challenge({i}) = {}",
-    // challenges[i]))                     }
-    //                     Leaf(Hint(i)) => vals.push(format!("Hint({i}) =
-    // {}", hints[i])),                     // skip tree nodes
-    //                     _ => (),
-    //                 });
-
-    //                 vals.sort();
-    //                 vals.dedup();
-
-    //                 // TODO: display constraint? eprintln!("Constraint
-    // is:\n{constraint}\n");                 #[cfg(feature = "std")]
-    //                 eprint!("Constraint {c_idx} does not evaluate to a
-    // low degree polynomial. ");                 #[cfg(feature =
-    // "std")]                 eprintln!("Divide by zero occurs at
-    // row {row}.\n");                 #[cfg(feature = "std")]
-    //                 eprintln!("Expression values:\n{}", vals.join("\n"));
-    //                 panic!();
-    //             }
-    //         }
-    //     }
-    // }
-    // ```
+    challenges: &Challenges<S::Fq>,
+    hints: &Hints<S::Fq>,
+    base_trace: &Matrix<S::Fp>,
+    extension_trace: Option<&Matrix<S::Fq>>,
+) -> Result<(), ConstraintError> {
+    use AlgebraicItem::*;
+
+    let trace_len = base_trace.num_rows();
+    let trace_domain = trace_domain::<S::AirConfig>(trace_len);
+    let num_base_columns = base_trace.num_cols();
+    let num_extension_columns = extension_trace.map_or(0, Matrix::num_cols);
+    let num_columns = num_base_columns + num_extension_columns;
+
+    // helper function to get a value from the execution trace
+    let get_trace_value = |row: usize, col: usize, offset: isize| {
+        let pos = (row as isize + offset).rem_euclid(trace_len as isize) as usize;
+        if col < num_base_columns {
+            FieldVariant::Fp(base_trace.0[col][pos])
+        } else {
+            FieldVariant::Fq(extension_trace.unwrap().0[col - num_base_columns][pos])
+        }
+    };
+
+    // helper function to get the value of a leaf at a given row
+    let get_leaf_value = |row: usize, x: S::Fp, leaf: &AlgebraicItem<_>| match leaf {
+        X => FieldVariant::Fp(x),
+        &Constant(c) => c,
+        &Hint(i) => FieldVariant::Fq(hints[i]),
+        &Challenge(i) => FieldVariant::Fq(challenges[i]),
+        &Trace(col, offset) => get_trace_value(row, col, offset),
+        Periodic(col) => {
+            let point = FieldVariant::Fp(x.pow([(trace_len / col.interval_size()) as u64]));
+            col.coeffs()
+                .iter()
+                .rfold(FieldVariant::zero(), |acc, &coeff| acc * point + coeff)
+        }
+    };
+
+    let constraints = S::AirConfig::constraints(trace_len);
+
+    // check all the leaves of the constraints can be resolved
+    for (c_idx, constraint) in constraints.iter().enumerate() {
+        let mut res = Ok(());
+        constraint.traverse(&mut |node| match *node {
+            Expr::Leaf(Trace(column, _)) if res.is_ok() && column >= num_columns => {
+                res = ColumnOutOfBoundsSnafu {
+                    constraint: c_idx,
+                    column,
+                    num_columns,
+                }
+                .fail();
+            }
+            Expr::Leaf(Challenge(index)) if res.is_ok() && index >= challenges.len() => {
+                res = MissingChallengeSnafu {
+                    constraint: c_idx,
+                    index,
+                }
+                .fail();
+            }
+            Expr::Leaf(Hint(index)) if res.is_ok() && index >= hints.len() => {
+                res = MissingHintSnafu {
+                    constraint: c_idx,
+                    index,
+                }
+                .fail();
+            }
+            _ => {}
+        });
+        res?;
+    }
+
+    for (c_idx, constraint) in constraints.iter().enumerate() {
+        let is_invalid_row = |&row: &usize| {
+            let x = trace_domain.element(row);
+            constraint
+                .check(&mut |leaf| get_leaf_value(row, x, leaf))
+                .is_none()
+        };
+
+        #[cfg(not(feature = "parallel"))]
+        let invalid_row = (0..trace_len).find(is_invalid_row);
+        #[cfg(feature = "parallel")]
+        let invalid_row = (0..trace_len).into_par_iter().find_first(is_invalid_row);
+
+        if let Some(row) = invalid_row {
+            // get a description of each leaf node
+            let x = trace_domain.element(row);
+            let mut values = vec![LeafValue {
+                leaf: Leaf::X,
+                value: x.to_string(),
+            }];
+            constraint.traverse(&mut |node| {
+                let leaf = match node {
+                    Expr::Leaf(Trace(column, offset)) => Leaf::Trace {
+                        column: *column,
+                        offset: *offset,
+                    },
+                    Expr::Leaf(Challenge(i)) => Leaf::Challenge(*i),
+                    Expr::Leaf(Hint(i)) => Leaf::Hint(*i),
+                    Expr::Leaf(Periodic(col)) => Leaf::Periodic {
+                        interval_size: col.interval_size(),
+                        num_coeffs: col.coeffs().len(),
+                    },
+                    // skip tree nodes, constants and x
+                    _ => return,
+                };
+                if let Expr::Leaf(item) = node {
+                    let value = get_leaf_value(row, x, item).to_string();
+                    values.push(LeafValue { leaf, value });
+                }
+            });
+
+            values.sort();
+            values.dedup();
+
+            return UnsatisfiedConstraintSnafu {
+                constraint: c_idx,
+                row,
+                values,
+            }
+            .fail();
+        }
+    }
+
+    Ok(())
 }
@@ -1,5 +1,5 @@
-use crate::hash::Digest;
 use crate::merkle;
+use crate::merkle::Commitment;
 use crate::merkle::MatrixMerkleTree;
 use crate::merkle::MerkleTree;
 use crate::random::PublicCoin;
@@ -17,19 +17,191 @@ use ark_poly::Polynomial;
 use ark_poly::Radix2EvaluationDomain;
 use ark_serialize::CanonicalDeserialize;
 use ark_serialize::CanonicalSerialize;
+use ark_serialize::Compress;
+use ark_serialize::Read;
+use ark_serialize::SerializationError;
+use ark_serialize::Valid;
+use ark_serialize::Validate;
+use ark_serialize::Write;
 use ministark_gpu::prelude::*;
 use ministark_gpu::utils::bit_reverse;
 use ministark_gpu::utils::bit_reverse_index;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use snafu::Snafu;
+use std::iter::successors;
 use std::iter::zip;
 use std::marker::PhantomData;
 
+/// Returns true if FRI layers can be folded by `folding_factor`
+pub const fn is_supported_folding_factor(folding_factor: usize) -> bool {
+    matches!(folding_factor, 2 | 4 | 8 | 16)
+}
+
+/// Folding factors of the first FRI layers e.g. `[8, 8, 4, 2]`. Layers after
+/// the schedule fold by the default folding factor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FoldingSchedule {
+    len: u8,
+    folding_factors: [u8; Self::MAX_LEN],
+}
+
+impl FoldingSchedule {
+    pub const MAX_LEN: usize = 32;
+
+    /// # Panics
+    ///
+    /// Panics if the schedule has more than [`Self::MAX_LEN`] layers or a
+    /// folding factor isn't supported
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn new(folding_factors: &[u8]) -> Self {
+        assert!(folding_factors.len() <= Self::MAX_LEN);
+        let mut schedule = [0; Self::MAX_LEN];
+        let mut i = 0;
+        while i < folding_factors.len() {
+            assert!(is_supported_folding_factor(folding_factors[i] as usize));
+            schedule[i] = folding_factors[i];
+            i += 1;
+        }
+        Self {
+            len: folding_factors.len() as u8,
+            folding_factors: schedule,
+        }
+    }
+
+    /// Returns the folding factor of `layer` if it's in the schedule
+    pub const fn get(&self, layer: usize) -> Option<usize> {
+        if layer < self.len as usize {
+            Some(self.folding_factors[layer] as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.folding_factors[..self.len as usize]
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl CanonicalSerialize for FoldingSchedule {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.as_slice().serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.as_slice().serialized_size(compress)
+    }
+}
+
+impl Valid for FoldingSchedule {
+    fn check(&self) -> Result<(), SerializationError> {
+        if self
+            .as_slice()
+            .iter()
+            .all(|&folding_factor| is_supported_folding_factor(folding_factor.into()))
+        {
+            Ok(())
+        } else {
+            Err(SerializationError::InvalidData)
+        }
+    }
+}
+
+impl CanonicalDeserialize for FoldingSchedule {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let len = u64::deserialize_with_mode(&mut reader, compress, validate)?;
+        let len = u8::try_from(len)
+            .ok()
+            .filter(|&len| usize::from(len) <= Self::MAX_LEN)
+            .ok_or(SerializationError::InvalidData)?;
+        let mut folding_factors = [0; Self::MAX_LEN];
+        for folding_factor in &mut folding_factors[..len.into()] {
+            *folding_factor = u8::deserialize_with_mode(&mut reader, compress, validate)?;
+        }
+        let schedule = Self {
+            len,
+            folding_factors,
+        };
+        if validate == Validate::Yes {
+            schedule.check()?;
+        }
+        Ok(schedule)
+    }
+}
+
+/// How the prover commits to the FRI remainder
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RemainderCommitment {
+    /// The coefficients of the remainder polynomial are sent to the verifier
+    /// once the last layer is committed to
+    #[default]
+    Coefficients,
+    /// The remainder codeword is committed to in a Merkle tree and the
+    /// coefficients of the remainder polynomial are only sent at the end of
+    /// the proof. The verifier checks the coefficients against the commitment.
+    Codeword,
+}
+
+impl CanonicalSerialize for RemainderCommitment {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        let tag: u8 = match self {
+            Self::Coefficients => 0,
+            Self::Codeword => 1,
+        };
+        tag.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        0u8.serialized_size(compress)
+    }
+}
+
+impl Valid for RemainderCommitment {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for RemainderCommitment {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        match u8::deserialize_with_mode(reader, compress, validate)? {
+            0 => Ok(Self::Coefficients),
+            1 => Ok(Self::Codeword),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct FriOptions {
     folding_factor: usize,
+    folding_schedule: FoldingSchedule,
     max_remainder_coeffs: usize,
+    remainder_commitment: RemainderCommitment,
     blowup_factor: usize,
 }
 
@@ -41,53 +213,214 @@ impl FriOptions {
     ) -> Self {
         Self {
             folding_factor,
+            folding_schedule: FoldingSchedule::new(&[]),
             max_remainder_coeffs,
+            remainder_commitment: RemainderCommitment::Coefficients,
             blowup_factor,
         }
     }
 
+    /// Sets how the prover commits to the remainder
+    pub const fn with_remainder_commitment(
+        mut self,
+        remainder_commitment: RemainderCommitment,
+    ) -> Self {
+        self.remainder_commitment = remainder_commitment;
+        self
+    }
+
+    /// Sets the folding factors of the first layers
+    pub const fn with_folding_schedule(mut self, folding_schedule: FoldingSchedule) -> Self {
+        self.folding_schedule = folding_schedule;
+        self
+    }
+
+    /// Returns the folding factor of layers after the folding schedule
+    pub const fn folding_factor(&self) -> usize {
+        self.folding_factor
+    }
+
+    pub const fn folding_schedule(&self) -> FoldingSchedule {
+        self.folding_schedule
+    }
+
+    /// Returns the folding factor of the layer at index `layer`
+    pub const fn layer_folding_factor(&self, layer: usize) -> usize {
+        match self.folding_schedule.get(layer) {
+            Some(folding_factor) => folding_factor,
+            None => self.folding_factor,
+        }
+    }
+
+    pub const fn max_remainder_coeffs(&self) -> usize {
+        self.max_remainder_coeffs
+    }
+
+    pub const fn remainder_commitment(&self) -> RemainderCommitment {
+        self.remainder_commitment
+    }
+
+    pub const fn blowup_factor(&self) -> usize {
+        self.blowup_factor
+    }
+
     pub const fn num_layers(&self, mut domain_size: usize) -> usize {
         let mut num_layers = 0;
         while domain_size > self.max_remainder_coeffs * self.blowup_factor {
-            domain_size /= self.folding_factor;
+            domain_size /= self.layer_folding_factor(num_layers);
             num_layers += 1;
         }
         num_layers
     }
 
-    pub const fn remainder_size(&self, mut domain_size: usize) -> usize {
-        while domain_size > self.max_remainder_coeffs * self.blowup_factor {
-            domain_size /= self.folding_factor;
+    pub const fn remainder_size(&self, domain_size: usize) -> usize {
+        self.layer_domain_size(domain_size, self.num_layers(domain_size))
+    }
+
+    /// Returns the size of the domain a batch of polynomials is folded over
+    pub const fn batch_domain_size(&self, max_degree_bound: usize) -> usize {
+        max_degree_bound.next_power_of_two() * self.blowup_factor
+    }
+
+    /// Returns the size of the domain of the layer at index `layer`. The layer
+    /// after the last layer is the remainder.
+    pub const fn layer_domain_size(&self, mut domain_size: usize, layer: usize) -> usize {
+        let mut i = 0;
+        while i < layer {
+            domain_size /= self.layer_folding_factor(i);
+            i += 1;
         }
         domain_size
     }
 
-    pub const fn domain_offset<F: GpuField>(&self) -> F::FftField
-    where
-        F::FftField: FftField,
-    {
-        // TODO: Can specify a domain offset in the air which might cause issues with
-        // having this a constant
-        F::FftField::GENERATOR
+    /// Returns the layer a polynomial of degree less than `degree_bound` is
+    /// injected into when batched over a domain of `domain_size`. This is the
+    /// last layer whose degree bound is at least `degree_bound`.
+    pub const fn batch_layer(&self, domain_size: usize, degree_bound: usize) -> usize {
+        let num_layers = self.num_layers(domain_size);
+        let mut layer = 0;
+        let mut layer_degree_bound = domain_size / self.blowup_factor;
+        while layer < num_layers
+            && layer_degree_bound / self.layer_folding_factor(layer) >= degree_bound
+        {
+            layer_degree_bound /= self.layer_folding_factor(layer);
+            layer += 1;
+        }
+        layer
     }
 }
 
+/// Returns the coset offset of the domain with `layer_domain_size` elements
+/// obtained by folding a coset of `domain_size` elements offset by
+/// `domain_offset`.
+fn layer_domain_offset<F: FftField>(
+    domain_offset: F,
+    domain_size: usize,
+    layer_domain_size: usize,
+) -> F {
+    domain_offset.pow([(domain_size / layer_domain_size) as u64])
+}
+
+/// Proof of a FRI low degree test. Batched proofs share this representation
+/// since the evaluations of batched codewords at each layer's query positions
+/// are provided to the verifier by the caller.
 #[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
-pub struct FriProof<F: Field, D: Digest, M: MatrixMerkleTree<F>> {
+pub struct FriProof<F: Field, D: Commitment, M: MatrixMerkleTree<F>> {
     pub layers: Vec<LayerProof<F, D, M>>,
+    /// Root of the remainder codeword. Only present if the remainder is
+    /// committed to with [`RemainderCommitment::Codeword`]
+    pub remainder_commitment: Option<D>,
     pub remainder_coeffs: Vec<F>,
 }
 
-impl<F: GpuField + Field, D: Digest, M: MatrixMerkleTree<F, Root = D>> FriProof<F, D, M>
+impl<F: GpuField + Field, D: Commitment, M: MatrixMerkleTree<F, Root = D>> FriProof<F, D, M>
 where
     F::FftField: FftField,
 {
     pub fn new(layers: Vec<LayerProof<F, D, M>>, remainder_coeffs: Vec<F>) -> Self {
         Self {
             layers,
+            remainder_commitment: None,
             remainder_coeffs,
         }
     }
+
+    /// Sets the root of the remainder codeword
+    #[must_use]
+    pub fn with_remainder_commitment(mut self, remainder_commitment: D) -> Self {
+        self.remainder_commitment = Some(remainder_commitment);
+        self
+    }
+}
+
+/// Codeword of a polynomial that is batched with others into FRI
+pub struct BatchedCodeword<F: GpuField> {
+    /// Bit-reversed evaluations over the domain of the layer the polynomial is
+    /// injected into. See [`FriOptions::batch_layer`]. The layer's domain is
+    /// the coset offset by `domain_offset^(domain_size / layer_domain_size)`.
+    pub evaluations: GpuVec<F>,
+    pub degree_bound: usize,
+}
+
+impl<F: GpuField> BatchedCodeword<F> {
+    pub const fn new(evaluations: GpuVec<F>, degree_bound: usize) -> Self {
+        Self {
+            evaluations,
+            degree_bound,
+        }
+    }
+}
+
+/// Batches a polynomial into a FRI layer. The polynomial `f(x)` is injected as
+/// `(alpha + beta * x^degree_shift) * f(x)` which raises its degree bound to
+/// the degree bound of the layer.
+#[derive(Clone, Copy)]
+struct BatchedPoly<F> {
+    layer: usize,
+    degree_shift: usize,
+    alpha: F,
+    beta: F,
+}
+
+impl<F: GpuField + Field + DomainCoeff<F::FftField>> BatchedPoly<F>
+where
+    F::FftField: FftField,
+{
+    const fn new(
+        options: &FriOptions,
+        domain_size: usize,
+        degree_bound: usize,
+        alpha: F,
+        beta: F,
+    ) -> Self {
+        let layer = options.batch_layer(domain_size, degree_bound);
+        let layer_degree_bound =
+            options.layer_domain_size(domain_size, layer) / options.blowup_factor;
+        Self {
+            layer,
+            degree_shift: layer_degree_bound - degree_bound,
+            alpha,
+            beta,
+        }
+    }
+
+    /// Returns the generator of the layer's domain raised to `degree_shift`
+    fn shifted_generator(&self, layer_domain_size: usize) -> F::FftField {
+        let domain = Radix2EvaluationDomain::<F::FftField>::new(layer_domain_size).unwrap();
+        domain.group_gen().pow([self.degree_shift as u64])
+    }
+
+    /// Returns the coset offset of the layer's domain raised to `degree_shift`
+    fn shifted_offset(&self, layer_domain_offset: F::FftField) -> F::FftField {
+        layer_domain_offset.pow([self.degree_shift as u64])
+    }
+
+    /// Returns the coefficient of the evaluation at `x` given `x^degree_shift`
+    fn coeff(&self, shifted_x: F::FftField) -> F {
+        let mut coeff = self.beta;
+        coeff *= shifted_x;
+        coeff + self.alpha
+    }
 }
 
 struct FriLayer<F: GpuField, M: MerkleTree> {
@@ -96,13 +429,13 @@ struct FriLayer<F: GpuField, M: MerkleTree> {
 }
 
 #[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
-pub struct LayerProof<F: Field, D: Digest, M: MatrixMerkleTree<F>> {
+pub struct LayerProof<F: Field, D: Commitment, M: MatrixMerkleTree<F>> {
     pub flattenend_rows: Vec<F>,
     pub merkle_proof: M::Proof,
     pub commitment: D,
 }
 
-impl<F: GpuField + Field, D: Digest, M: MatrixMerkleTree<F, Root = D>> LayerProof<F, D, M>
+impl<F: GpuField + Field, D: Commitment, M: MatrixMerkleTree<F, Root = D>> LayerProof<F, D, M>
 where
     F::FftField: FftField,
 {
@@ -121,44 +454,51 @@ where
     }
 }
 
-pub struct FriProver<F: GpuField, D: Digest, M: MerkleTree> {
+pub struct FriProver<F: GpuField, D: Commitment, M: MerkleTree> {
     options: FriOptions,
+    domain_offset: F::FftField,
     layers: Vec<FriLayer<F, M>>,
+    remainder_commitment: Option<D>,
     remainder_coeffs: Vec<F>,
     _phantom: PhantomData<D>,
 }
 
 impl<
         F: GpuField + Field + DomainCoeff<F::FftField>,
-        D: Digest,
+        D: Commitment,
         M: MatrixMerkleTree<F, Root = D>,
     > FriProver<F, D, M>
 where
     F::FftField: FftField,
 {
-    pub const fn new(options: FriOptions) -> Self {
+    /// Creates a prover for codewords over the coset of the evaluation domain
+    /// offset by `domain_offset`.
+    pub const fn new(options: FriOptions, domain_offset: F::FftField) -> Self {
         Self {
             options,
+            domain_offset,
             layers: Vec::new(),
+            remainder_commitment: None,
             remainder_coeffs: Vec::new(),
             _phantom: PhantomData,
         }
     }
 
-    pub fn into_proof(self, positions: &[usize]) -> FriProof<F, D, M> {
-        let folding_factor = self.options.folding_factor;
+    pub fn into_proof(self, positions: &[usize]) -> Result<FriProof<F, D, M>, ProvingError> {
         // let (last_layer, initial_layers) = self.layers.split_last().unwrap();
         let mut proof_layers = Vec::new();
         let mut positions = positions.to_vec();
-        for layer in &self.layers {
+        for (i, layer) in self.layers.iter().enumerate() {
+            let folding_factor = self.options.layer_folding_factor(i);
             positions = fold_positions(&positions, folding_factor);
             proof_layers.push(match folding_factor {
-                2 => query_layer::<F, D, M, 2>(layer, &positions),
-                4 => query_layer::<F, D, M, 4>(layer, &positions),
-                6 => query_layer::<F, D, M, 6>(layer, &positions),
-                8 => query_layer::<F, D, M, 8>(layer, &positions),
-                16 => query_layer::<F, D, M, 16>(layer, &positions),
-                _ => unimplemented!("folding factor {folding_factor} is not supported"),
+                2 => query_layer::<F, D, M, 2>(layer, &positions)?,
+                4 => query_layer::<F, D, M, 4>(layer, &positions)?,
+                8 => query_layer::<F, D, M, 8>(layer, &positions)?,
+                16 => query_layer::<F, D, M, 16>(layer, &positions)?,
+                folding_factor => {
+                    return Err(ProvingError::UnsupportedFoldingFactor { folding_factor })
+                }
             });
         }
 
@@ -173,33 +513,126 @@ where
         // j];     }
         // }
 
-        FriProof::new(proof_layers, self.remainder_coeffs)
+        Ok(FriProof {
+            layers: proof_layers,
+            remainder_commitment: self.remainder_commitment,
+            remainder_coeffs: self.remainder_coeffs,
+        })
     }
 
     pub fn build_layers(
         &mut self,
-        channel: &mut impl ProverChannel<Field = F, Digest = D>,
+        channel: &mut impl ProverChannel<Field = F, Commitment = D>,
+        evaluations: GpuVec<F>,
+    ) -> Result<(), ProvingError> {
+        self.fold_layers(channel, evaluations, Vec::new())
+    }
+
+    /// Builds the layers for a batch of polynomials with different degree
+    /// bounds. Each polynomial is degree corrected with random coefficients
+    /// and injected into the layer that matches its degree bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the batch is empty, a degree bound is zero or a codeword
+    /// isn't over the domain of its layer.
+    pub fn build_batched_layers(
+        &mut self,
+        channel: &mut impl ProverChannel<Field = F, Commitment = D>,
+        codewords: Vec<BatchedCodeword<F>>,
+    ) -> Result<(), ProvingError> {
+        let max_degree_bound = codewords.iter().map(|c| c.degree_bound).max();
+        let domain_size = self
+            .options
+            .batch_domain_size(max_degree_bound.expect("batch is empty"));
+        let num_layers = self.options.num_layers(domain_size);
+        let mut injections = (0..=num_layers).map(|_| None).collect::<Vec<_>>();
+        for codeword in codewords {
+            assert_ne!(codeword.degree_bound, 0, "degree bound must be positive");
+            let alpha = channel.draw_batching_coeff();
+            let beta = channel.draw_batching_coeff();
+            let poly = BatchedPoly::new(
+                &self.options,
+                domain_size,
+                codeword.degree_bound,
+                alpha,
+                beta,
+            );
+            let layer_domain_size = self.options.layer_domain_size(domain_size, poly.layer);
+            assert_eq!(
+                codeword.evaluations.len(),
+                layer_domain_size,
+                "codeword is not over the domain of layer {}",
+                poly.layer
+            );
+
+            let layer_offset =
+                layer_domain_offset(self.domain_offset, domain_size, layer_domain_size);
+            let offset = poly.shifted_offset(layer_offset);
+            let generator = poly.shifted_generator(layer_domain_size);
+            let mut shifted_xs = successors(Some(offset), |x| Some(*x * generator))
+                .take(layer_domain_size)
+                .collect::<Vec<F::FftField>>();
+            bit_reverse(&mut shifted_xs);
+
+            let injected: &mut GpuVec<F> = injections[poly.layer].get_or_insert_with(|| {
+                let mut zeros = Vec::new_in(GpuAllocator);
+                zeros.resize(layer_domain_size, F::zero());
+                zeros
+            });
+            for ((acc, v), x) in zip(zip(injected, &codeword.evaluations), shifted_xs) {
+                *acc += *v * poly.coeff(x);
+            }
+        }
+        // the polynomial with the largest degree bound is always in the first layer
+        let evaluations = injections[0].take().unwrap();
+        self.fold_layers(channel, evaluations, injections)
+    }
+
+    /// Folds the evaluations until the remainder is reached. Injected
+    /// evaluations are added to the layer at their index.
+    fn fold_layers(
+        &mut self,
+        channel: &mut impl ProverChannel<Field = F, Commitment = D>,
         mut evaluations: GpuVec<F>,
-    ) {
+        mut injections: Vec<Option<GpuVec<F>>>,
+    ) -> Result<(), ProvingError> {
         assert!(self.layers.is_empty());
-        for _ in 0..self.options.num_layers(evaluations.len()) {
-            evaluations = match self.options.folding_factor {
-                2 => self.build_layer::<2>(channel, evaluations),
-                4 => self.build_layer::<4>(channel, evaluations),
-                8 => self.build_layer::<8>(channel, evaluations),
-                16 => self.build_layer::<16>(channel, evaluations),
-                folding_factor => unreachable!("folding factor {folding_factor} not supported"),
+        let mut domain_offset = self.domain_offset;
+        // inverse twiddles of every layer are a prefix of the first layer's
+        let inv_twiddles = inverse_twiddles(evaluations.len());
+        for layer in 0..self.options.num_layers(evaluations.len()) {
+            let folding_factor = self.options.layer_folding_factor(layer);
+            let evals = &evaluations;
+            let twiddles = &inv_twiddles;
+            evaluations = match folding_factor {
+                2 => self.build_layer::<2>(channel, evals, twiddles, domain_offset),
+                4 => self.build_layer::<4>(channel, evals, twiddles, domain_offset),
+                8 => self.build_layer::<8>(channel, evals, twiddles, domain_offset),
+                16 => self.build_layer::<16>(channel, evals, twiddles, domain_offset),
+                folding_factor => {
+                    return Err(ProvingError::UnsupportedFoldingFactor { folding_factor })
+                }
+            };
+            domain_offset = domain_offset.pow([folding_factor as u64]);
+            if let Some(injected) = injections.get_mut(layer + 1).and_then(Option::take) {
+                for (acc, v) in zip(&mut evaluations, injected) {
+                    *acc += v;
+                }
             }
         }
-        self.set_remainder(channel, evaluations);
+        self.set_remainder(channel, evaluations, domain_offset);
+        Ok(())
     }
 
-    /// Builds a single layer of the FRI protocol
-    /// Returns the evaluations for the next layer.
+    /// Builds a single layer of the FRI protocol over the coset offset by
+    /// `domain_offset`. Returns the evaluations for the next layer.
     fn build_layer<const N: usize>(
         &mut self,
-        channel: &mut impl ProverChannel<Field = F, Digest = D>,
-        evaluations: GpuVec<F>,
+        channel: &mut impl ProverChannel<Field = F, Commitment = D>,
+        evaluations: &[F],
+        inv_twiddles: &[F::FftField],
+        domain_offset: F::FftField,
     ) -> GpuVec<F> {
         // Each layer requires decommitting to `folding_factor` many evaluations e.g.
         // `folding_factor = 2` decommits to an evaluation for LHS_i and RHS_i
@@ -222,34 +655,50 @@ where
         });
 
         // return the next evaluations
-        apply_drp(
-            evaluations,
-            F::FftField::ONE,
-            channel.draw_fri_alpha(),
-            self.options.folding_factor,
-        )
+        let alpha = channel.draw_fri_alpha();
+        fold_cosets::<F, N>(evaluations, inv_twiddles, domain_offset, alpha)
     }
 
     fn set_remainder(
         &mut self,
-        channel: &mut impl ProverChannel<Field = F, Digest = D>,
+        channel: &mut impl ProverChannel<Field = F, Commitment = D>,
         mut evaluations: GpuVec<F>,
+        domain_offset: F::FftField,
     ) {
         let domain_size = evaluations.len();
         assert!(domain_size.is_power_of_two());
         assert!(domain_size <= self.options.max_remainder_coeffs * self.options.blowup_factor);
-        let domain = Radix2EvaluationDomain::new(domain_size).unwrap();
+        let domain = Radix2EvaluationDomain::new_coset(domain_size, domain_offset).unwrap();
+        if self.options.remainder_commitment == RemainderCommitment::Codeword {
+            let merkle_tree =
+                M::from_matrix(&Matrix::new(vec![evaluations.to_vec_in(GpuAllocator)]));
+            channel.commit_remainder_codeword(merkle_tree.root());
+            self.remainder_commitment = Some(merkle_tree.root());
+        }
         bit_reverse(&mut evaluations);
         let coeffs = domain.ifft(&evaluations);
         let max_degree = domain_size / self.options.blowup_factor - 1;
         let (remainder_coeffs, zero_coeffs) = coeffs.split_at(max_degree + 1);
         assert!(zero_coeffs.iter().all(F::is_zero));
-        channel.commit_remainder(remainder_coeffs);
+        if self.options.remainder_commitment == RemainderCommitment::Coefficients {
+            channel.commit_remainder(remainder_coeffs);
+        }
         self.remainder_coeffs = remainder_coeffs.to_vec();
     }
 }
 
+/// Errors that are returned when generating a FRI proof
 #[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum ProvingError {
+    #[snafu(display("folding factor {folding_factor} is not supported"))]
+    UnsupportedFoldingFactor { folding_factor: usize },
+    #[snafu(context(false))]
+    #[snafu(display("failed to open the commitment: {source}"))]
+    Merkle { source: merkle::Error },
+}
+
+#[derive(Debug, PartialEq, Eq, Snafu)]
 pub enum VerificationError {
     #[snafu(display("queries do not resolve to their commitment in layer {layer}"))]
     LayerCommitmentInvalid { layer: usize },
@@ -259,6 +708,8 @@ pub enum VerificationError {
     NumPositionEvaluationMismatch,
     #[snafu(display("remainder is invalid"))]
     RemainderCommitmentInvalid,
+    #[snafu(display("remainder commitment is missing or unexpected"))]
+    RemainderCommitmentMismatch,
     #[snafu(display("remainder is not a degree {degree} polynomial"))]
     RemainderDegreeMismatch { degree: usize },
     #[snafu(display("{size} can't be divided by {folding_factor} (layer {layer})"))]
@@ -267,11 +718,27 @@ pub enum VerificationError {
         folding_factor: usize,
         layer: usize,
     },
+    #[snafu(display("proof has {actual} layers but {expected} were expected"))]
+    LayerCountMismatch { expected: usize, actual: usize },
+    #[snafu(display("layer {layer} has {actual} values but {expected} were expected"))]
+    LayerValueCountMismatch {
+        layer: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[snafu(display("folding factor {folding_factor} is not supported"))]
+    UnsupportedFoldingFactor { folding_factor: usize },
+    #[snafu(display("batch must contain at least one polynomial"))]
+    EmptyBatch,
+    #[snafu(display("degree bounds of batched polynomials must be positive"))]
+    InvalidBatchDegreeBound,
+    #[snafu(display("batch has {expected} polynomials but {actual} were evaluated"))]
+    BatchSizeMismatch { expected: usize, actual: usize },
 }
 
 /// Fri verifier adapted from Winterfell to match Starkware's verifier
 /// <https://github.com/facebook/winterfell/blob/main/fri/src/verifier/mod.rs#L58>
-pub struct FriVerifier<F: GpuField + Field, D: Digest, M: MatrixMerkleTree<F, Root = D>>
+pub struct FriVerifier<F: GpuField + Field, D: Commitment, M: MatrixMerkleTree<F, Root = D>>
 where
     F::FftField: FftField,
 {
@@ -280,26 +747,79 @@ where
     pub layer_alphas: Vec<F>,
     proof: FriProof<F, D, M>,
     domain: Radix2EvaluationDomain<F::FftField>,
+    batch: Vec<BatchedPoly<F>>,
 }
 
 impl<
         F: GpuField + Field + DomainCoeff<F::FftField>,
-        D: Digest,
+        D: Commitment,
         M: MatrixMerkleTree<F, Root = D>,
     > FriVerifier<F, D, M>
 where
     F::FftField: FftField,
 {
+    /// Creates a verifier for a polynomial of degree at most `max_poly_degree`
+    /// evaluated over the coset offset by `domain_offset`.
     pub fn new(
-        public_coin: &mut impl PublicCoin<Field = F, Digest = D>,
+        public_coin: &mut impl PublicCoin<Field = F, Digest = D::Digest>,
         options: FriOptions,
         proof: FriProof<F, D, M>,
         max_poly_degree: usize,
+        domain_offset: F::FftField,
     ) -> Result<Self, VerificationError> {
-        let folding_factor = options.folding_factor;
-        let domain_offset = options.domain_offset::<F>();
         let domain_size = max_poly_degree.next_power_of_two() * options.blowup_factor;
         let domain = Radix2EvaluationDomain::new_coset(domain_size, domain_offset).unwrap();
+        Self::new_with_batch(public_coin, options, proof, domain, Vec::new())
+    }
+
+    /// Creates a verifier for a batch of polynomials of degree less than
+    /// `degree_bounds` evaluated over cosets offset by powers of
+    /// `domain_offset`. Batching coefficients are drawn in the same order as
+    /// the codewords given to [`FriProver::build_batched_layers`].
+    pub fn new_batched(
+        public_coin: &mut impl PublicCoin<Field = F, Digest = D::Digest>,
+        options: FriOptions,
+        proof: FriProof<F, D, M>,
+        degree_bounds: &[usize],
+        domain_offset: F::FftField,
+    ) -> Result<Self, VerificationError> {
+        let max_degree_bound = degree_bounds.iter().copied().max();
+        let max_degree_bound = max_degree_bound.ok_or(VerificationError::EmptyBatch)?;
+        let domain_size = options.batch_domain_size(max_degree_bound);
+        let mut batch = Vec::new();
+        for &degree_bound in degree_bounds {
+            if degree_bound == 0 {
+                return Err(VerificationError::InvalidBatchDegreeBound);
+            }
+            let alpha = public_coin.draw();
+            let beta = public_coin.draw();
+            batch.push(BatchedPoly::new(
+                &options,
+                domain_size,
+                degree_bound,
+                alpha,
+                beta,
+            ));
+        }
+        let domain = Radix2EvaluationDomain::new_coset(domain_size, domain_offset).unwrap();
+        Self::new_with_batch(public_coin, options, proof, domain, batch)
+    }
+
+    fn new_with_batch(
+        public_coin: &mut impl PublicCoin<Field = F, Digest = D::Digest>,
+        options: FriOptions,
+        proof: FriProof<F, D, M>,
+        domain: Radix2EvaluationDomain<F::FftField>,
+        batch: Vec<BatchedPoly<F>>,
+    ) -> Result<Self, VerificationError> {
+        let domain_size = domain.size();
+        let num_layers = options.num_layers(domain_size);
+        if proof.layers.len() != num_layers {
+            return Err(VerificationError::LayerCountMismatch {
+                expected: num_layers,
+                actual: proof.layers.len(),
+            });
+        }
 
         let mut layer_alphas = Vec::new();
         let mut layer_commitments = Vec::new();
@@ -307,11 +827,12 @@ where
         for (i, layer) in proof.layers.iter().enumerate() {
             // TODO: batch merkle tree proofs
             // get the merkle root from the first merkle path
-            public_coin.reseed_with_digest(&layer.commitment);
+            public_coin.reseed_with_commitment(&layer.commitment);
             let alpha = public_coin.draw();
             layer_alphas.push(alpha);
             layer_commitments.push(layer.commitment.clone());
 
+            let folding_factor = options.layer_folding_factor(i);
             if i != proof.layers.len() - 1 && layer_codeword_len % folding_factor != 0 {
                 return Err(VerificationError::CodewordTruncation {
                     size: layer_codeword_len,
@@ -323,17 +844,15 @@ where
             layer_codeword_len /= folding_factor;
         }
 
-        public_coin.reseed_with_field_element_vector(&proof.remainder_coeffs);
-
-        // TODO: add back in
-        // let remainder_root =
-        // Output::<D>::from_slice(&proof.remainder_commitment).clone();
-        // let remainder_root =
-        // Output::<D>::from_slice(&proof.remainder_commitment).clone();
-        // public_coin.reseed_with_hash(&remainder_root);
-        // let remainder_alpha = public_coin.draw();
-        // layer_alphas.push(remainder_alpha);
-        // layer_commitments.push(remainder_root);
+        match (options.remainder_commitment, &proof.remainder_commitment) {
+            (RemainderCommitment::Coefficients, None) => {
+                public_coin.reseed_with_field_element_vector(&proof.remainder_coeffs);
+            }
+            (RemainderCommitment::Codeword, Some(remainder_root)) => {
+                public_coin.reseed_with_commitment(remainder_root);
+            }
+            _ => return Err(VerificationError::RemainderCommitmentMismatch),
+        }
 
         Ok(Self {
             options,
@@ -341,84 +860,81 @@ where
             layer_alphas,
             proof,
             domain,
+            batch,
         })
     }
 
-    pub fn verify_generic<const N: usize>(
+    /// Verifies all layers. Injected evaluations are added to the evaluations
+    /// of the layer at their index.
+    fn verify_layers(
         self,
         positions: &[usize],
-        evaluations: &[F],
+        mut evaluations: Vec<F>,
+        mut injections: Vec<Option<Vec<F>>>,
     ) -> Result<(), VerificationError> {
-        let folding_domain = Radix2EvaluationDomain::new(N).unwrap();
-        let mut layers = self.proof.layers.into_iter();
-        let mut layer_alphas = self.layer_alphas.into_iter();
-        let mut layer_commitments = self.layer_commitments.into_iter();
+        let mut inject = |layer: usize, evaluations: &mut Vec<F>| {
+            if let Some(injected) = injections.get_mut(layer).and_then(Option::take) {
+                for (acc, v) in zip(evaluations, injected) {
+                    *acc += v;
+                }
+            }
+        };
         let mut positions = positions.to_vec();
-        let mut evaluations = evaluations.to_vec();
         let mut domain_size = self.domain.size();
         let mut domain_generator = self.domain.group_gen();
+        let mut domain_offset = self.domain.coset_offset();
 
-        // verify all layers except remainder
-        for i in 0..self.options.num_layers(domain_size) {
-            let folded_positions = fold_positions(&positions, N);
-            let layer_alpha = layer_alphas.next().unwrap();
-            let layer_commitment = layer_commitments.next().unwrap();
-
-            // TODO: change assert to error. Check remainder
-            let layer = layers.next().unwrap();
-            let (rows, _) = &layer.flattenend_rows.as_chunks::<N>();
-            assert_eq!(rows.len(), folded_positions.len());
-
-            // verify the layer values against the layer's commitment
-            M::verify_rows(
-                &layer_commitment,
-                &folded_positions,
-                rows,
-                layer.merkle_proof,
-            )
-            .map_err(|_| VerificationError::LayerCommitmentInvalid { layer: i })?;
-
-            let query_values = get_query_values(rows, &positions, &folded_positions);
-            // println!("evaluatinos: {:?}", evaluations);
-            // println!("query vals: {:?}", query_values);
-            assert_eq!(evaluations.len(), query_values.len());
-            for (i, (eval, query_val)) in zip(&evaluations, &query_values).enumerate() {
-                assert_eq!(eval, query_val, "mismatch at {i}");
-            }
-
-            if evaluations != query_values {
-                return Err(VerificationError::InvalidDegreeRespectingProjection { layer: i });
-            }
-
-            let polys = rows
-                .iter()
-                .zip(&folded_positions)
-                .map(move |(chunk, position)| {
-                    let bit_rev_position = bit_reverse_index(domain_size / N, *position);
-                    // let offset = domain_offset.pow([N.pow(i as u32) as u64])
-                    //     * domain_generator.pow([bit_rev_position as u64]);
-                    let offset = domain_generator.pow([bit_rev_position as u64]);
-                    let domain = folding_domain.get_coset(offset).unwrap();
-                    let mut chunk = *chunk;
-                    bit_reverse(&mut chunk);
-                    let mut coeffs = domain.ifft(&chunk);
-                    for coeff in &mut coeffs {
-                        *coeff *= F::from(N as u64);
-                    }
-                    DensePolynomial::from_coefficients_vec(coeffs)
-                });
+        // verify all layers except remainder. the number of layers is checked
+        // when the verifier is created
+        let num_layers = self.proof.layers.len();
+        let layers = zip(
+            self.proof.layers,
+            zip(self.layer_alphas, self.layer_commitments),
+        );
+        for (i, (layer, (layer_alpha, layer_commitment))) in layers.enumerate() {
+            inject(i, &mut evaluations);
+            let folding_factor = self.options.layer_folding_factor(i);
+            let query = LayerQuery {
+                layer: i,
+                positions: &positions,
+                evaluations: &evaluations,
+                domain_generator,
+                domain_offset,
+                domain_size,
+            };
+            let commitment = &layer_commitment;
+            let (folded_positions, folded_evaluations) = match folding_factor {
+                2 => query.verify::<D, M, 2>(layer, commitment, layer_alpha)?,
+                4 => query.verify::<D, M, 4>(layer, commitment, layer_alpha)?,
+                8 => query.verify::<D, M, 8>(layer, commitment, layer_alpha)?,
+                16 => query.verify::<D, M, 16>(layer, commitment, layer_alpha)?,
+                folding_factor => {
+                    return Err(VerificationError::UnsupportedFoldingFactor { folding_factor })
+                }
+            };
 
             // prepare for next layer
-            evaluations = polys.map(|poly| poly.evaluate(&layer_alpha)).collect();
+            evaluations = folded_evaluations;
             positions = folded_positions;
-            domain_generator = domain_generator.pow([N as u64]);
-            domain_size /= N;
+            domain_generator = domain_generator.pow([folding_factor as u64]);
+            domain_offset = domain_offset.pow([folding_factor as u64]);
+            domain_size /= folding_factor;
+        }
+        inject(num_layers, &mut evaluations);
+        if let Some(remainder_root) = &self.proof.remainder_commitment {
+            let domain = Radix2EvaluationDomain::new_coset(domain_size, domain_offset).unwrap();
+            verify_remainder_codeword::<F, D, M>(
+                remainder_root,
+                &self.proof.remainder_coeffs,
+                domain,
+            )?;
         }
         verify_remainder::<F>(
             self.proof.remainder_coeffs,
             &positions,
             &evaluations,
             domain_generator,
+            domain_offset,
             domain_size,
             self.options.blowup_factor,
         )
@@ -429,14 +945,121 @@ where
             return Err(VerificationError::NumPositionEvaluationMismatch);
         }
 
-        match self.options.folding_factor {
-            2 => self.verify_generic::<2>(positions, evaluations),
-            4 => self.verify_generic::<4>(positions, evaluations),
-            8 => self.verify_generic::<8>(positions, evaluations),
-            16 => self.verify_generic::<16>(positions, evaluations),
-            // TODO: move this to options
-            folding_factor => unreachable!("folding factor {folding_factor} not supported"),
+        self.verify_layers(positions, evaluations.to_vec(), Vec::new())
+    }
+
+    /// Verifies a batch created with [`Self::new_batched`]. `evaluations`
+    /// holds the evaluations of each codeword in the batch at the query
+    /// positions of its layer i.e. `positions` folded by each preceding layer
+    /// with [`fold_positions`].
+    pub fn verify_batched(
+        self,
+        positions: &[usize],
+        evaluations: &[Vec<F>],
+    ) -> Result<(), VerificationError> {
+        if evaluations.len() != self.batch.len() {
+            return Err(VerificationError::BatchSizeMismatch {
+                expected: self.batch.len(),
+                actual: evaluations.len(),
+            });
+        }
+
+        // query positions of every layer including the remainder
+        let num_layers = self.proof.layers.len();
+        let mut layer_positions = vec![positions.to_vec()];
+        for layer in 0..num_layers {
+            let folding_factor = self.options.layer_folding_factor(layer);
+            let folded_positions = fold_positions(&layer_positions[layer], folding_factor);
+            layer_positions.push(folded_positions);
+        }
+
+        let domain_size = self.domain.size();
+        let mut injections = vec![None; num_layers + 1];
+        for (poly, poly_evaluations) in zip(&self.batch, evaluations) {
+            let positions = &layer_positions[poly.layer];
+            if positions.len() != poly_evaluations.len() {
+                return Err(VerificationError::NumPositionEvaluationMismatch);
+            }
+            let layer_domain_size = self.options.layer_domain_size(domain_size, poly.layer);
+            let layer_offset =
+                layer_domain_offset(self.domain.coset_offset(), domain_size, layer_domain_size);
+            let offset = poly.shifted_offset(layer_offset);
+            let generator = poly.shifted_generator(layer_domain_size);
+            let injected =
+                injections[poly.layer].get_or_insert_with(|| vec![F::zero(); positions.len()]);
+            for ((acc, &position), v) in zip(zip(injected, positions), poly_evaluations) {
+                let shifted_x =
+                    offset * generator.pow([bit_reverse_index(layer_domain_size, position) as u64]);
+                *acc += *v * poly.coeff(shifted_x);
+            }
         }
+        // the polynomial with the largest degree bound is always in the first layer
+        let evaluations = injections[0].take().unwrap();
+        self.verify_layers(positions, evaluations, injections)
+    }
+}
+
+/// Query positions and evaluations of a layer
+struct LayerQuery<'a, F: GpuField> {
+    layer: usize,
+    positions: &'a [usize],
+    evaluations: &'a [F],
+    domain_generator: F::FftField,
+    domain_offset: F::FftField,
+    domain_size: usize,
+}
+
+impl<F: GpuField + Field + DomainCoeff<F::FftField>> LayerQuery<'_, F>
+where
+    F::FftField: FftField,
+{
+    /// Verifies the layer's values against the query and returns the folded
+    /// positions and evaluations for the next layer
+    fn verify<D: Commitment, M: MatrixMerkleTree<F, Root = D>, const N: usize>(
+        &self,
+        layer_proof: LayerProof<F, D, M>,
+        commitment: &D,
+        alpha: F,
+    ) -> Result<(Vec<usize>, Vec<F>), VerificationError> {
+        let layer = self.layer;
+        let folded_positions = fold_positions(self.positions, N);
+
+        let (rows, remainder) = &layer_proof.flattenend_rows.as_chunks::<N>();
+        if rows.len() != folded_positions.len() || !remainder.is_empty() {
+            return Err(VerificationError::LayerValueCountMismatch {
+                layer,
+                expected: folded_positions.len() * N,
+                actual: layer_proof.flattenend_rows.len(),
+            });
+        }
+
+        // verify the layer values against the layer's commitment
+        M::verify_rows(
+            commitment,
+            &folded_positions,
+            rows,
+            layer_proof.merkle_proof,
+        )
+        .map_err(|_| VerificationError::LayerCommitmentInvalid { layer })?;
+
+        let query_values = get_query_values(rows, self.positions, &folded_positions);
+        if self.evaluations != query_values {
+            return Err(VerificationError::InvalidDegreeRespectingProjection { layer });
+        }
+
+        let inv_twiddles = inverse_twiddles(N);
+        let evaluations = rows
+            .iter()
+            .zip(&folded_positions)
+            .map(|(chunk, position)| {
+                let bit_rev_position = bit_reverse_index(self.domain_size / N, *position);
+                let offset =
+                    self.domain_offset * self.domain_generator.pow([bit_rev_position as u64]);
+                let inv_offset = offset.inverse().unwrap();
+                fold_coset(*chunk, 0, &inv_twiddles, inv_offset, alpha)
+            })
+            .collect();
+        Ok((folded_positions, evaluations))
     }
 }
 
@@ -445,6 +1068,7 @@ fn verify_remainder<F: GpuField + Field + DomainCoeff<F::FftField>>(
     positions: &[usize],
     expected_evaluations: &[F],
     domain_generator: F::FftField,
+    domain_offset: F::FftField,
     domain_size: usize,
     blowup_factor: usize,
 ) -> Result<(), VerificationError>
@@ -460,7 +1084,7 @@ where
     }
     let xs = positions
         .iter()
-        .map(|p| domain_generator.pow([bit_reverse_index(domain_size, *p) as u64]));
+        .map(|p| domain_offset * domain_generator.pow([bit_reverse_index(domain_size, *p) as u64]));
     for (i, x) in xs.enumerate() {
         // TODO: fix types and just call horner_evaluate
         let y = remainder_poly
@@ -476,138 +1100,201 @@ where
     Ok(())
 }
 
+/// Checks the codeword of the remainder polynomial over `domain` resolves to
+/// the committed remainder root
+fn verify_remainder_codeword<
+    F: GpuField + Field + DomainCoeff<F::FftField>,
+    D: Commitment,
+    M: MatrixMerkleTree<F, Root = D>,
+>(
+    remainder_root: &D,
+    remainder_coeffs: &[F],
+    domain: Radix2EvaluationDomain<F::FftField>,
+) -> Result<(), VerificationError>
+where
+    F::FftField: FftField,
+{
+    if remainder_coeffs.len() > domain.size() {
+        return Err(VerificationError::RemainderCommitmentInvalid);
+    }
+    let mut codeword = domain.fft(remainder_coeffs);
+    bit_reverse(&mut codeword);
+    let merkle_tree = M::from_matrix(&Matrix::new(vec![codeword.to_vec_in(GpuAllocator)]));
+    if merkle_tree.root() == *remainder_root {
+        Ok(())
+    } else {
+        Err(VerificationError::RemainderCommitmentInvalid)
+    }
+}
+
 pub trait ProverChannel {
-    type Digest: Digest;
+    type Commitment: Commitment;
     type Field: GpuField;
 
-    fn commit_fri_layer(&mut self, layer_root: Self::Digest);
+    fn commit_fri_layer(&mut self, layer_root: Self::Commitment);
 
     fn commit_remainder(&mut self, remainder_coeffs: &[Self::Field]);
 
+    /// Commits to the remainder codeword. Only used with
+    /// [`RemainderCommitment::Codeword`]
+    fn commit_remainder_codeword(&mut self, remainder_root: Self::Commitment) {
+        self.commit_fri_layer(remainder_root);
+    }
+
     fn draw_fri_alpha(&mut self) -> Self::Field;
+
+    /// Draws a random coefficient used to batch polynomials together
+    fn draw_batching_coeff(&mut self) -> Self::Field {
+        self.draw_fri_alpha()
+    }
 }
 
 /// Performs a degree respecting projection (drp) on polynomial evaluations.
-// Example for `folding_factor = 2`:
+///
+/// `evals` are the bit-reversed evaluations of `f(x)` over the coset of the
+/// evaluation domain offset by `domain_offset`. Returns the bit-reversed
+/// evaluations of `folding_factor * f'(x)` over the coset offset by
+/// `domain_offset^folding_factor` where `f'(x)` is the random linear
+/// combination of the `folding_factor` components of `f(x)` with powers of
+/// `alpha`. The result isn't normalised: divide it by `folding_factor` to get
+/// the evaluations of `f'(x)`. FRI layers are folded the same way.
+///
+/// # Errors
+///
+/// Returns an error if `folding_factor` isn't supported. See
+/// [`is_supported_folding_factor`].
+// The projection is applied in evaluation form. For `folding_factor = 2` and
+// `f(x) = f_e(x^2) + x * f_o(x^2)`:
+// ```text
+//    f(x)  + f(-x) = 2 * f_e(x^2)
+//    f(x)  - f(-x) = 2 * x * f_o(x^2)
+//    2 * f'(x^2)   = (f(x) + f(-x)) + α / x * (f(x) - f(-x))
+// ```
+// Bit-reversed evaluations of `x` and `-x` are next to each other e.g. for a
+// domain of size 8 where `Ω^4 = -1`:
 // ```text
-// 1. interpolate evals over the evaluation domain to obtain f(x):
-//    ┌─────────┬───┬───┬───┬───┬───┬───┬───┬───┐
-//    │ i       │ 0 │ 1 │ 2 │ 3 │ 4 │ 5 │ 6 │ 7 │
-//    ├─────────┼───┼───┼───┼───┼───┼───┼───┼───┤
-//    │ eval[i] │ 9 │ 2 │ 3 │ 5 │ 9 │ 2 │ 3 │ 5 │
-//    └─────────┴───┴───┴───┴───┴───┴───┴───┴───┘
-//    ┌──────┬───────┬───────┬───────┬───────┬───────┬───────┬───────┬───────┐
-//    │ x    │ o*Ω^0 │ o*Ω^1 │ o*Ω^2 │ o*Ω^3 │ o*Ω^4 │ o*Ω^5 │ o*Ω^6 │ o*Ω^7 │
-//    ├──────┼───────┼───────┼───────┼───────┼───────┼───────┼───────┼───────┤
-//    │ f(x) │ 9     │ 2     │ 3     │ 5     │ 9     │ 2     │ 3     │ 5     │
-//    └──────┴───────┴───────┴───────┴───────┴───────┴───────┴───────┴───────┘
-//      f(x) = c0 * x^0 + c1 * x^1 + c2 * x^2 + c3 * x^3 +
-//             c4 * x^4 + c5 * x^5 + c6 * x^6 + c7 * x^7
-//
-// 2. perform a random linear combination of odd and even coefficients of f(x):
-//    f_e(x) = c0 + c2 * x + c4 * x^2 + c6 * x^3
-//    f_o(x) = c1 + c3 * x + c5 * x^2 + c7 * x^3
-//    f(x)   = f_e(x) + x * f_o(x)
-//    f'(x)  = f_e(x) + α * f_o(x)
-//    α      = <random field element sent from verifier>
-//
-// 4. obtain the DRP by evaluating f'(x) over a new domain of half the size:
-//    ┌───────┬───────────┬───────────┬───────────┬───────────┐
-//    │ x     │ (o*Ω^0)^2 │ (o*Ω^1)^2 │ (o*Ω^2)^2 │ (o*Ω^3)^2 │
-//    ├───────┼───────────┼───────────┼───────────┼───────────┤
-//    │ f'(x) │ 82        │ 12        │ 57        │ 34        │
-//    └───────┴───────────┴───────────┴───────────┴───────────┘
-//    ┌────────┬────┬────┬────┬────┐
-//    │ i      │ 0  │ 1  │ 2  │ 3  │
-//    ├────────┼────┼────┼────┼────┤
-//    │ drp[i] │ 82 │ 12 │ 57 │ 34 │
-//    └────────┴────┴────┴────┴────┘
+//    ┌───┬───────┬───────┬───────┬───────┬───────┬───────┬───────┬───────┐
+//    │ i │ 0     │ 1     │ 2     │ 3     │ 4     │ 5     │ 6     │ 7     │
+//    ├───┼───────┼───────┼───────┼───────┼───────┼───────┼───────┼───────┤
+//    │ x │ o*Ω^0 │ o*Ω^4 │ o*Ω^2 │ o*Ω^6 │ o*Ω^1 │ o*Ω^5 │ o*Ω^3 │ o*Ω^7 │
+//    └───┴───────┴───────┴───────┴───────┴───────┴───────┴───────┴───────┘
 // ```
+// Each pair folds to a single evaluation and the folded evaluations are the
+// bit-reversed evaluations over `(o*Ω^0)^2, (o*Ω^2)^2, (o*Ω^1)^2, (o*Ω^3)^2`.
+// Larger folding factors are applied as repeated folds by two with `α`
+// squared after each fold.
 pub fn apply_drp<F: GpuField + Field + DomainCoeff<F::FftField>>(
-    mut evals: GpuVec<F>,
+    evals: &[F],
     domain_offset: F::FftField,
     alpha: F,
     folding_factor: usize,
-) -> GpuVec<F>
+) -> Result<GpuVec<F>, ProvingError>
 where
     F::FftField: FftField,
 {
-    let n = evals.len();
-    let domain = Radix2EvaluationDomain::new_coset(n, domain_offset).unwrap();
-    // TODO: integrate bit reverse into fft
-    bit_reverse(&mut evals);
-    let mut coeffs = ifft(evals, domain);
-    let fold_fact = F::from(folding_factor as u64);
-    for coeff in &mut coeffs {
-        *coeff *= fold_fact;
-    }
-
-    let alpha_powers = (0..folding_factor)
-        .map(|i| alpha.pow([i as u64]))
-        .collect::<Vec<F>>();
-
-    let drp_coeffs = ark_std::cfg_chunks!(coeffs, folding_factor)
-        .map(|chunk| {
-            chunk
-                .iter()
-                .zip(&alpha_powers)
-                .map(|(v, alpha)| *v * alpha)
-                .sum()
-        })
-        .collect::<Vec<F>>()
-        .to_vec_in(GpuAllocator);
+    let inv_twiddles = inverse_twiddles(evals.len());
+    fold_evaluations(evals, &inv_twiddles, domain_offset, alpha, folding_factor)
+}
+
+/// Returns the bit-reversed inverse powers of the generator of a domain with
+/// `n` elements. The first half of the returned inverse twiddles are the
+/// inverse twiddles of the domain with `n / 2` elements.
+fn inverse_twiddles<F: FftField>(n: usize) -> Vec<F> {
+    let domain = Radix2EvaluationDomain::<F>::new(n).unwrap();
+    let mut twiddles = vec![F::zero(); n / 2];
 
-    let drp_offset = domain_offset.pow([folding_factor as u64]);
-    let drp_domain = Radix2EvaluationDomain::new_coset(n / folding_factor, drp_offset).unwrap();
+    #[cfg(feature = "parallel")]
+    let chunk_size = core::cmp::max(n / 2 / rayon::current_num_threads(), 1024);
+    #[cfg(not(feature = "parallel"))]
+    let chunk_size = core::cmp::max(n / 2, 1);
 
-    // return the drp evals
-    let mut evals = fft(drp_coeffs, drp_domain);
-    bit_reverse(&mut evals);
-    evals
+    let generator_inv = domain.group_gen_inv();
+    ark_std::cfg_chunks_mut!(twiddles, chunk_size)
+        .enumerate()
+        .for_each(|(i, chunk)| {
+            let mut acc = generator_inv.pow([(i * chunk_size) as u64]);
+            for twiddle in chunk {
+                *twiddle = acc;
+                acc *= &generator_inv;
+            }
+        });
+    if twiddles.len() > 1 {
+        bit_reverse(&mut twiddles);
+    }
+    twiddles
 }
 
-// requires ownership when the gpu feature is enabled
-#[allow(clippy::needless_pass_by_value)]
-fn ifft<F: GpuField + Field + DomainCoeff<F::FftField>>(
-    evals: GpuVec<F>,
-    domain: Radix2EvaluationDomain<F::FftField>,
-) -> GpuVec<F>
+/// Applies a degree respecting projection to bit-reversed evaluations over
+/// the coset offset by `domain_offset`. `inv_twiddles` are the inverse
+/// twiddles of the domain or of any larger domain (see [`inverse_twiddles`]).
+fn fold_evaluations<F: GpuField + Field + DomainCoeff<F::FftField>>(
+    evals: &[F],
+    inv_twiddles: &[F::FftField],
+    domain_offset: F::FftField,
+    alpha: F,
+    folding_factor: usize,
+) -> Result<GpuVec<F>, ProvingError>
 where
     F::FftField: FftField,
 {
-    #[cfg(feature = "gpu")]
-    if domain.size() >= GpuFft::<F>::MIN_SIZE {
-        let mut coeffs = evals;
-        let mut ifft = GpuIfft::from(domain);
-        ifft.encode(&mut coeffs);
-        ifft.execute();
-        return coeffs;
-    }
-
-    let coeffs = domain.ifft(&evals);
-    coeffs.to_vec_in(GpuAllocator)
+    Ok(match folding_factor {
+        2 => fold_cosets::<F, 2>(evals, inv_twiddles, domain_offset, alpha),
+        4 => fold_cosets::<F, 4>(evals, inv_twiddles, domain_offset, alpha),
+        8 => fold_cosets::<F, 8>(evals, inv_twiddles, domain_offset, alpha),
+        16 => fold_cosets::<F, 16>(evals, inv_twiddles, domain_offset, alpha),
+        folding_factor => return Err(ProvingError::UnsupportedFoldingFactor { folding_factor }),
+    })
 }
 
-// requires ownership when the gpu feature is enabled
-#[allow(clippy::needless_pass_by_value)]
-fn fft<F: GpuField + Field + DomainCoeff<F::FftField>>(
-    coeffs: GpuVec<F>,
-    domain: Radix2EvaluationDomain<F::FftField>,
+fn fold_cosets<F: GpuField + Field + DomainCoeff<F::FftField>, const N: usize>(
+    evals: &[F],
+    inv_twiddles: &[F::FftField],
+    domain_offset: F::FftField,
+    alpha: F,
 ) -> GpuVec<F>
 where
     F::FftField: FftField,
 {
-    #[cfg(feature = "gpu")]
-    if domain.size() >= GpuFft::<F>::MIN_SIZE {
-        let mut evals = coeffs;
-        let mut fft = GpuFft::from(domain);
-        fft.encode(&mut evals);
-        fft.execute();
-        return evals;
-    }
+    let (cosets, remainder) = evals.as_chunks::<N>();
+    assert!(remainder.is_empty());
+    assert!(inv_twiddles.len() >= evals.len() / 2);
+    let inv_offset = domain_offset.inverse().unwrap();
+    let mut folded = Vec::with_capacity_in(cosets.len(), GpuAllocator);
+    folded.resize(cosets.len(), F::zero());
+    ark_std::cfg_iter_mut!(folded)
+        .zip(ark_std::cfg_iter!(cosets))
+        .enumerate()
+        .for_each(|(i, (folded, coset))| {
+            *folded = fold_coset(*coset, i, inv_twiddles, inv_offset, alpha);
+        });
+    folded
+}
 
-    let evals = domain.fft(&coeffs);
-    evals.to_vec_in(GpuAllocator)
+/// Folds the bit-reversed evaluations of the `index`-th coset of `N` elements
+/// in a domain offset by `1 / inv_offset` to a single evaluation.
+fn fold_coset<F: GpuField + Field + DomainCoeff<F::FftField>, const N: usize>(
+    mut values: [F; N],
+    index: usize,
+    inv_twiddles: &[F::FftField],
+    mut inv_offset: F::FftField,
+    mut alpha: F,
+) -> F
+where
+    F::FftField: FftField,
+{
+    let mut len = N;
+    while len > 1 {
+        len /= 2;
+        for i in 0..len {
+            let (lhs, rhs) = (values[2 * i], values[2 * i + 1]);
+            let mut odd = (lhs - rhs) * alpha;
+            odd *= inv_twiddles[index * len + i] * inv_offset;
+            values[i] = lhs + rhs + odd;
+        }
+        alpha.square_in_place();
+        inv_offset.square_in_place();
+    }
+    values[0]
 }
 
 /// # Panics
@@ -647,18 +1334,27 @@ pub fn get_query_values<F: Field, const N: usize>(
         .collect()
 }
 
-fn query_layer<F: GpuField + Field, D: Digest, M: MatrixMerkleTree<F, Root = D>, const N: usize>(
+fn query_layer<
+    F: GpuField + Field,
+    D: Commitment,
+    M: MatrixMerkleTree<F, Root = D>,
+    const N: usize,
+>(
     layer: &FriLayer<F, M>,
     positions: &[usize],
-) -> LayerProof<F, D, M>
+) -> Result<LayerProof<F, D, M>, merkle::Error>
 where
     F::FftField: FftField,
 {
-    let merkle_proof = layer.merkle_tree.prove_rows(positions).unwrap();
+    let merkle_proof = layer.merkle_tree.prove_rows(positions)?;
     let mut rows: Vec<[F; N]> = Vec::new();
     for &position in positions {
         let row = layer.evaluations.get_row(position).unwrap();
         rows.push(row.try_into().unwrap());
     }
-    LayerProof::new(rows, merkle_proof, layer.merkle_tree.root())
+    Ok(LayerProof::new(
+        rows,
+        merkle_proof,
+        layer.merkle_tree.root(),
+    ))
 }
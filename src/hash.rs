@@ -1,10 +1,26 @@
+use crate::utils::deserialize_array;
 use crate::utils::SerdeOutput;
+use ark_ff::BigInt;
 use ark_ff::Field;
+use ark_ff::PrimeField;
 use ark_serialize::CanonicalDeserialize;
 use ark_serialize::CanonicalSerialize;
+use ark_serialize::Compress;
+use ark_serialize::Read;
+use ark_serialize::SerializationError;
+use ark_serialize::Valid;
+use ark_serialize::Validate;
+use blake2::Blake2s256;
 use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::ops::Range;
 use digest::Digest as _;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp as GoldilocksFp;
 use sha2::Sha256;
+use sha3::Keccak256;
+
+pub mod poseidon2;
+pub mod rpo;
 
 /// Trait inspired by Winterfell: <https://github.com/facebook/winterfell/blob/main/crypto/src/hash/mod.rs#L33>
 pub trait HashFn: Send + Sync + 'static {
@@ -14,6 +30,10 @@ pub trait HashFn: Send + Sync + 'static {
     /// Collision resistance of the hash function measured in bits.
     const COLLISION_RESISTANCE: u32;
 
+    /// Returns a name that identifies the hash function e.g. in serialized
+    /// proofs.
+    fn name() -> String;
+
     /// Returns a hash of the provided sequence of bytes.
     fn hash(bytes: impl IntoIterator<Item = u8>) -> Self::Digest;
 
@@ -40,6 +60,29 @@ pub trait ElementHashFn<F: Field>: HashFn {
     fn hash_elements(elements: impl IntoIterator<Item = F>) -> Self::Digest;
 }
 
+/// Defines a hash function built from a permutation over a prime field.
+///
+/// Exposes the sponge construction of algebraic hash functions so that field
+/// elements can be absorbed and squeezed without going through bytes. See
+/// [`FieldPublicCoin`](crate::random::FieldPublicCoin).
+pub trait AlgebraicHashFn: HashFn {
+    /// Prime field the permutation is defined over
+    type Field: PrimeField;
+
+    /// Number of field elements in the permutation state
+    const STATE_WIDTH: usize;
+
+    /// Elements of the state that input is absorbed into and output is
+    /// squeezed from
+    const RATE_RANGE: Range<usize>;
+
+    /// Applies the permutation to a state of size [`Self::STATE_WIDTH`]
+    fn permute(state: &mut [Self::Field]);
+
+    /// Returns the field elements that make up a digest
+    fn digest_elements(digest: &Self::Digest) -> &[Self::Field];
+}
+
 /// Defines output type for a cryptographic hash function.
 /// Digest trait from Winterfell: <https://github.com/facebook/winterfell/blob/main/crypto/src/hash/mod.rs#L69>
 pub trait Digest:
@@ -53,6 +96,10 @@ pub trait Digest:
     /// on the possible digest size. For digests which are smaller than 32
     /// bytes, the unused bytes should be set to 0.
     fn as_bytes(&self) -> [u8; 32];
+
+    /// Number of leading bytes returned by [`Digest::as_bytes`] that hold the
+    /// digest. The remaining bytes are zero padding.
+    const NUM_BYTES: usize = 32;
 }
 
 pub struct Sha256HashFn;
@@ -62,6 +109,10 @@ impl HashFn for Sha256HashFn {
 
     const COLLISION_RESISTANCE: u32 = 128;
 
+    fn name() -> String {
+        "sha256".to_string()
+    }
+
     fn hash(bytes: impl IntoIterator<Item = u8>) -> SerdeOutput<Sha256> {
         let mut hasher = Sha256::new();
         bytes.into_iter().for_each(|b| hasher.update([b]));
@@ -98,3 +149,262 @@ impl<F: Field> ElementHashFn<F> for Sha256HashFn {
         Self::hash_chunks([&*byte_buffer])
     }
 }
+
+/// Incremental hash function over bytes with a 256 bit output
+pub trait ByteHasher: Default + Send + Sync + 'static {
+    const NAME: &'static str;
+
+    fn update(&mut self, bytes: &[u8]);
+
+    fn finalize(self) -> [u8; 32];
+}
+
+impl ByteHasher for Keccak256 {
+    const NAME: &'static str = "keccak256";
+
+    fn update(&mut self, bytes: &[u8]) {
+        digest::Digest::update(self, bytes);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        digest::Digest::finalize(self).into()
+    }
+}
+
+impl ByteHasher for Blake2s256 {
+    const NAME: &'static str = "blake2s256";
+
+    fn update(&mut self, bytes: &[u8]) {
+        digest::Digest::update(self, bytes);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        digest::Digest::finalize(self).into()
+    }
+}
+
+impl ByteHasher for blake3::Hasher {
+    const NAME: &'static str = "blake3";
+
+    fn update(&mut self, bytes: &[u8]) {
+        Self::update(self, bytes);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        Self::finalize(&self).into()
+    }
+}
+
+/// Digest of `N` bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, CanonicalSerialize)]
+pub struct ByteDigest<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> Valid for ByteDigest<N> {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl<const N: usize> CanonicalDeserialize for ByteDigest<N> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(Self(deserialize_array(reader, compress, validate)?))
+    }
+}
+
+impl<const N: usize> Default for ByteDigest<N> {
+    fn default() -> Self {
+        Self([0; N])
+    }
+}
+
+impl<const N: usize> Digest for ByteDigest<N> {
+    const NUM_BYTES: usize = N;
+
+    fn as_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        bytes[..N].copy_from_slice(&self.0);
+        bytes
+    }
+}
+
+/// Hash function that outputs the first `N` bytes of a 256 bit byte hasher.
+///
+/// Truncating to 20 bytes (160 bits) matches the masked digests used for
+/// Merkle commitments by Starkware's Solidity verifier. Digests are zero padded
+/// to 32 bytes before being merged so nodes can be recomputed on-chain from
+/// 32 byte words. Note that truncation reduces collision resistance to `4N`
+/// bits.
+pub struct ByteHashFn<H: ByteHasher, const N: usize>(PhantomData<H>);
+
+impl<H: ByteHasher, const N: usize> ByteHashFn<H, N> {
+    fn truncate(hash: [u8; 32]) -> ByteDigest<N> {
+        ByteDigest(hash[..N].try_into().unwrap())
+    }
+}
+
+impl<H: ByteHasher, const N: usize> HashFn for ByteHashFn<H, N> {
+    type Digest = ByteDigest<N>;
+
+    #[allow(clippy::cast_possible_truncation)]
+    const COLLISION_RESISTANCE: u32 = N as u32 * 8 / 2;
+
+    fn name() -> String {
+        if N == 32 {
+            H::NAME.to_string()
+        } else {
+            format!("{}_masked{}", H::NAME, N * 8)
+        }
+    }
+
+    fn hash(bytes: impl IntoIterator<Item = u8>) -> ByteDigest<N> {
+        let bytes = bytes.into_iter().collect::<Vec<u8>>();
+        Self::hash_chunks([&*bytes])
+    }
+
+    fn hash_chunks<'a>(chunks: impl IntoIterator<Item = &'a [u8]>) -> ByteDigest<N> {
+        let mut hasher = H::default();
+        chunks.into_iter().for_each(|chunk| hasher.update(chunk));
+        Self::truncate(hasher.finalize())
+    }
+
+    fn merge(v0: &ByteDigest<N>, v1: &ByteDigest<N>) -> ByteDigest<N> {
+        let mut hasher = H::default();
+        hasher.update(&v0.as_bytes());
+        hasher.update(&v1.as_bytes());
+        Self::truncate(hasher.finalize())
+    }
+
+    fn merge_with_int(seed: &ByteDigest<N>, value: u64) -> ByteDigest<N> {
+        let mut hasher = H::default();
+        hasher.update(&seed.as_bytes());
+        hasher.update(&value.to_be_bytes());
+        Self::truncate(hasher.finalize())
+    }
+}
+
+impl<F: Field, H: ByteHasher, const N: usize> ElementHashFn<F> for ByteHashFn<H, N> {
+    fn hash_elements(elements: impl IntoIterator<Item = F>) -> ByteDigest<N> {
+        let mut byte_buffer = Vec::new();
+        for element in elements {
+            element.serialize_uncompressed(&mut byte_buffer).unwrap();
+        }
+        Self::hash_chunks([&*byte_buffer])
+    }
+}
+
+pub type Keccak256HashFn = ByteHashFn<Keccak256, 32>;
+
+/// Keccak-256 truncated to 160 bits
+pub type MaskedKeccak256HashFn = ByteHashFn<Keccak256, 20>;
+
+pub type Blake2s256HashFn = ByteHashFn<Blake2s256, 32>;
+
+/// BLAKE2s-256 truncated to 160 bits
+pub type MaskedBlake2s256HashFn = ByteHashFn<Blake2s256, 20>;
+
+pub type Blake3HashFn = ByteHashFn<blake3::Hasher, 32>;
+
+/// Encodes bytes as a lowercase hex string
+#[cfg(test)]
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    use core::fmt::Write;
+    let mut res = String::new();
+    for byte in bytes {
+        write!(res, "{byte:02x}").unwrap();
+    }
+    res
+}
+
+/// Converts a canonical value into a Goldilocks field element at compile time.
+/// Goldilocks elements are stored in Montgomery form with `R = 2^64 mod p`.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) const fn goldilocks_fp(value: u64) -> GoldilocksFp {
+    const MODULUS: u128 = 0xFFFF_FFFF_0000_0001;
+    const MONTGOMERY_R: u128 = 0xFFFF_FFFF;
+    let mont = (value as u128 * MONTGOMERY_R) % MODULUS;
+    ark_ff::Fp(BigInt([mont as u64]), PhantomData)
+}
+
+/// Converts a `u64` into field elements. Values are split into two 32 bit limbs
+/// if they may not fit into a single field element.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn int_to_elements<F: PrimeField>(value: u64) -> Vec<F> {
+    if F::MODULUS_BIT_SIZE > 64 {
+        vec![F::from(value)]
+    } else {
+        vec![F::from(value as u32), F::from(value >> 32)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keccak_matches_reference_vector() {
+        let digest = Keccak256HashFn::hash([]);
+
+        assert_eq!(
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            hex(&digest.0)
+        );
+    }
+
+    #[test]
+    fn blake2s_matches_reference_vectors() {
+        assert_eq!(
+            "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9",
+            hex(&Blake2s256HashFn::hash([]).0)
+        );
+        assert_eq!(
+            "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+            hex(&Blake2s256HashFn::hash(*b"abc").0)
+        );
+    }
+
+    #[test]
+    fn blake3_matches_reference_vectors() {
+        assert_eq!(
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            hex(&Blake3HashFn::hash([]).0)
+        );
+        assert_eq!(
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+            hex(&Blake3HashFn::hash(*b"abc").0)
+        );
+    }
+
+    #[test]
+    fn masked_digests_keep_most_significant_bytes() {
+        let bytes = *b"ministark";
+
+        let full = Keccak256HashFn::hash(bytes);
+        let masked = MaskedKeccak256HashFn::hash(bytes);
+
+        assert_eq!(full.0[..20], masked.0);
+        assert_eq!([0; 12], masked.as_bytes()[20..]);
+    }
+
+    #[test]
+    fn masked_merge_hashes_padded_words() {
+        let v0 = MaskedBlake2s256HashFn::hash(*b"left");
+        let v1 = MaskedBlake2s256HashFn::hash(*b"right");
+
+        let merged = MaskedBlake2s256HashFn::merge(&v0, &v1);
+
+        let words = [v0.as_bytes(), v1.as_bytes()].concat();
+        assert_eq!(MaskedBlake2s256HashFn::hash(words), merged);
+    }
+
+    #[test]
+    fn truncation_reduces_collision_resistance() {
+        assert_eq!(128, Keccak256HashFn::COLLISION_RESISTANCE);
+        assert_eq!(128, Blake3HashFn::COLLISION_RESISTANCE);
+        assert_eq!(80, MaskedKeccak256HashFn::COLLISION_RESISTANCE);
+        assert_eq!(80, MaskedBlake2s256HashFn::COLLISION_RESISTANCE);
+    }
+}
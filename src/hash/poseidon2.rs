@@ -0,0 +1,430 @@
+//! Poseidon2 hash function
+//!
+//! Implementation of the Poseidon2 permutation from
+//! <https://eprint.iacr.org/2023/323.pdf>. The linear layers, round structure
+//! and round constants match the reference implementation by Horizen Labs:
+//! <https://github.com/HorizenLabs/poseidon2>
+use super::int_to_elements;
+use super::AlgebraicHashFn;
+use super::Digest;
+use super::ElementHashFn;
+use super::HashFn;
+use crate::utils::deserialize_array;
+use alloc::vec::Vec;
+use ark_ff::BigInteger;
+use ark_ff::Field;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalDeserialize;
+use ark_serialize::CanonicalSerialize;
+use ark_serialize::Compress;
+use ark_serialize::Read;
+use ark_serialize::SerializationError;
+use ark_serialize::Valid;
+use ark_serialize::Validate;
+use core::marker::PhantomData;
+use core::ops::Range;
+
+pub mod goldilocks;
+pub mod stark252;
+
+/// Parameters of a Poseidon2 instance
+pub trait Poseidon2Config: Send + Sync + 'static {
+    type Field: PrimeField;
+
+    /// Identifies the instance
+    const NAME: &'static str;
+
+    /// Number of field elements in the permutation state
+    const WIDTH: usize;
+
+    /// Number of state elements absorbed into per permutation. The remaining
+    /// elements are the capacity.
+    const RATE: usize;
+
+    /// Exponent of the S-box. Must be coprime to `p - 1`.
+    const ALPHA: u64;
+
+    /// Total number of full rounds. Half are applied before the partial rounds
+    /// and half after.
+    const NUM_FULL_ROUNDS: usize;
+
+    const NUM_PARTIAL_ROUNDS: usize;
+
+    /// Round constants of the full rounds. `NUM_FULL_ROUNDS * WIDTH` elements.
+    const EXTERNAL_ROUND_CONSTANTS: &'static [Self::Field];
+
+    /// Round constants of the partial rounds. `NUM_PARTIAL_ROUNDS` elements.
+    const INTERNAL_ROUND_CONSTANTS: &'static [Self::Field];
+
+    /// Diagonal of the internal matrix minus one. Unused for states of width 2
+    /// and 3 which have fixed internal matrices.
+    const INTERNAL_DIAG_M_1: &'static [Self::Field];
+
+    /// Evaluated by [`permute`] so instances with an unsupported state width
+    /// or mismatched parameters fail to compile
+    const VALID: () = {
+        assert!(
+            matches!(Self::WIDTH, 2 | 3 | 4 | 8 | 12 | 16 | 20 | 24),
+            "unsupported Poseidon2 state width"
+        );
+        assert!(
+            Self::RATE < Self::WIDTH,
+            "rate must leave room for capacity"
+        );
+        assert!(Self::NUM_FULL_ROUNDS % 2 == 0);
+        assert!(Self::EXTERNAL_ROUND_CONSTANTS.len() == Self::NUM_FULL_ROUNDS * Self::WIDTH);
+        assert!(Self::INTERNAL_ROUND_CONSTANTS.len() == Self::NUM_PARTIAL_ROUNDS);
+        assert!(Self::WIDTH <= 3 || Self::INTERNAL_DIAG_M_1.len() == Self::WIDTH);
+    };
+}
+
+/// Applies the Poseidon2 permutation to a state of size `C::WIDTH`
+pub fn permute<C: Poseidon2Config>(state: &mut [C::Field]) {
+    let () = C::VALID;
+    assert_eq!(C::WIDTH, state.len());
+    let half_full_rounds = C::NUM_FULL_ROUNDS / 2;
+    let (rc_beginning, rc_end) = C::EXTERNAL_ROUND_CONSTANTS.split_at(half_full_rounds * C::WIDTH);
+
+    matmul_external(state);
+    for rc in rc_beginning.chunks(C::WIDTH) {
+        apply_full_round::<C>(state, rc);
+    }
+    for rc in C::INTERNAL_ROUND_CONSTANTS {
+        state[0] += rc;
+        state[0] = state[0].pow([C::ALPHA]);
+        matmul_internal(state, C::INTERNAL_DIAG_M_1);
+    }
+    for rc in rc_end.chunks(C::WIDTH) {
+        apply_full_round::<C>(state, rc);
+    }
+}
+
+#[inline]
+fn apply_full_round<C: Poseidon2Config>(state: &mut [C::Field], rc: &[C::Field]) {
+    for (s, rc) in state.iter_mut().zip(rc) {
+        *s = (*s + rc).pow([C::ALPHA]);
+    }
+    matmul_external(state);
+}
+
+/// Multiplies each chunk of 4 elements by the 4x4 MDS matrix:
+///
+/// ```text
+/// [5 7 1 3]
+/// [4 6 1 1]
+/// [1 3 5 7]
+/// [1 1 4 6]
+/// ```
+#[inline]
+fn matmul_m4<F: Field>(state: &mut [F]) {
+    for chunk in state.chunks_exact_mut(4) {
+        let t0 = chunk[0] + chunk[1];
+        let t1 = chunk[2] + chunk[3];
+        let t2 = chunk[1].double() + t1;
+        let t3 = chunk[3].double() + t0;
+        let t4 = t1.double().double() + t3;
+        let t5 = t0.double().double() + t2;
+        let t6 = t3 + t5;
+        let t7 = t2 + t4;
+        chunk[0] = t6;
+        chunk[1] = t5;
+        chunk[2] = t7;
+        chunk[3] = t4;
+    }
+}
+
+#[inline]
+fn matmul_external<F: Field>(state: &mut [F]) {
+    match state.len() {
+        2 | 3 => {
+            // circ(2, 1) and circ(2, 1, 1)
+            let sum = state.iter().sum::<F>();
+            for s in state.iter_mut() {
+                *s += sum;
+            }
+        }
+        4 => matmul_m4(state),
+        // widths are checked by `Poseidon2Config::VALID`
+        _ => {
+            // circ(2*M4, M4, ..., M4)
+            matmul_m4(state);
+            let mut sums = [F::ZERO; 4];
+            for chunk in state.chunks_exact(4) {
+                for (sum, s) in sums.iter_mut().zip(chunk) {
+                    *sum += s;
+                }
+            }
+            for chunk in state.chunks_exact_mut(4) {
+                for (s, sum) in chunk.iter_mut().zip(&sums) {
+                    *s += sum;
+                }
+            }
+        }
+    }
+}
+
+#[inline]
+fn matmul_internal<F: Field>(state: &mut [F], diag_m_1: &[F]) {
+    let sum = state.iter().sum::<F>();
+    match state.len() {
+        // [[2, 1], [1, 3]]
+        2 => {
+            state[0] += sum;
+            state[1] = state[1].double() + sum;
+        }
+        // [[2, 1, 1], [1, 2, 1], [1, 1, 3]]
+        3 => {
+            state[0] += sum;
+            state[1] += sum;
+            state[2] = state[2].double() + sum;
+        }
+        _ => {
+            for (s, d) in state.iter_mut().zip(diag_m_1) {
+                *s = *s * d + sum;
+            }
+        }
+    }
+}
+
+/// Digest consisting of `N` field elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, CanonicalSerialize)]
+pub struct Poseidon2Digest<F: PrimeField, const N: usize>(pub [F; N]);
+
+impl<F: PrimeField, const N: usize> Valid for Poseidon2Digest<F, N> {
+    fn check(&self) -> Result<(), SerializationError> {
+        F::batch_check(self.0.iter())
+    }
+}
+
+impl<F: PrimeField, const N: usize> CanonicalDeserialize for Poseidon2Digest<F, N> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(Self(deserialize_array(reader, compress, validate)?))
+    }
+}
+
+impl<F: PrimeField, const N: usize> Default for Poseidon2Digest<F, N> {
+    fn default() -> Self {
+        Self([F::ZERO; N])
+    }
+}
+
+impl<F: PrimeField, const N: usize> Digest for Poseidon2Digest<F, N> {
+    const NUM_BYTES: usize = (F::MODULUS_BIT_SIZE as usize).div_ceil(8) * N;
+
+    /// Returns the little-endian bytes of each element. Elements occupy the
+    /// minimum number of bytes needed to represent the modulus.
+    fn as_bytes(&self) -> [u8; 32] {
+        let element_size = (F::MODULUS_BIT_SIZE as usize).div_ceil(8);
+        assert!(element_size * N <= 32, "digest does not fit in 32 bytes");
+        let mut bytes = [0; 32];
+        for (chunk, element) in bytes.chunks_mut(element_size).zip(self.0) {
+            let element_bytes = element.into_bigint().to_bytes_le();
+            chunk.copy_from_slice(&element_bytes[..element_size]);
+        }
+        bytes
+    }
+}
+
+/// Poseidon2 sponge outputting digests of `N` field elements.
+///
+/// The rate occupies the first `C::RATE` elements of the state and the
+/// capacity the rest. Digests are the first `N` elements of the rate.
+pub struct Poseidon2HashFn<C: Poseidon2Config, const N: usize>(PhantomData<C>);
+
+/// Poseidon2 over Goldilocks with a state width of 12 and 256 bit digests
+pub type Poseidon2GoldilocksHashFn = Poseidon2HashFn<goldilocks::Poseidon2Goldilocks12, 4>;
+
+/// Poseidon2 over the Stark252 field with a state width of 3 and digests of a
+/// single field element
+pub type Poseidon2Stark252HashFn = Poseidon2HashFn<stark252::Poseidon2Stark252W3, 1>;
+
+impl<C: Poseidon2Config, const N: usize> Poseidon2HashFn<C, N> {
+    /// Hashes a sequence of base field elements. Uses the same padding rule as
+    /// RPO: the first capacity element is set to 1 if the input length is not
+    /// a multiple of the rate and the final chunk is padded with a single 1
+    /// followed by zeros.
+    fn hash_base_elements(
+        elements: impl IntoIterator<Item = C::Field>,
+    ) -> Poseidon2Digest<C::Field, N> {
+        let elements = elements.into_iter().collect::<Vec<C::Field>>();
+        let mut state = vec![C::Field::ZERO; C::WIDTH];
+        if elements.len() % C::RATE != 0 {
+            state[C::RATE] = C::Field::ONE;
+        }
+
+        for chunk in elements.chunks(C::RATE) {
+            let rate = &mut state[..C::RATE];
+            rate[..chunk.len()].copy_from_slice(chunk);
+            if chunk.len() != C::RATE {
+                rate[chunk.len()] = C::Field::ONE;
+                rate[chunk.len() + 1..].fill(C::Field::ZERO);
+            }
+            permute::<C>(&mut state);
+        }
+
+        Self::digest(&state)
+    }
+
+    fn digest(state: &[C::Field]) -> Poseidon2Digest<C::Field, N> {
+        Poseidon2Digest(state[..N].try_into().unwrap())
+    }
+}
+
+impl<C: Poseidon2Config, const N: usize> HashFn for Poseidon2HashFn<C, N> {
+    type Digest = Poseidon2Digest<C::Field, N>;
+
+    /// Collision resistance is bounded by both the digest and capacity size
+    #[allow(clippy::cast_possible_truncation)]
+    const COLLISION_RESISTANCE: u32 = {
+        let capacity = C::WIDTH - C::RATE;
+        let num_elements = if N < capacity { N } else { capacity };
+        C::Field::MODULUS_BIT_SIZE * num_elements as u32 / 2
+    };
+
+    fn name() -> String {
+        format!("{}_digest{N}", C::NAME)
+    }
+
+    fn hash(bytes: impl IntoIterator<Item = u8>) -> Self::Digest {
+        let bytes = bytes.into_iter().collect::<Vec<u8>>();
+        Self::hash_chunks([&*bytes])
+    }
+
+    /// Packs bytes into field elements (little-endian) using as many bytes per
+    /// element as fit below the modulus. The final element has a 1 byte
+    /// appended to it so that inputs which only differ by trailing zeros
+    /// produce different digests.
+    fn hash_chunks<'a>(chunks: impl IntoIterator<Item = &'a [u8]>) -> Self::Digest {
+        let chunk_size = (C::Field::MODULUS_BIT_SIZE as usize - 1) / 8;
+        let mut bytes = chunks.into_iter().flatten().copied().collect::<Vec<u8>>();
+        bytes.push(1);
+        let elements = bytes
+            .chunks(chunk_size)
+            .map(C::Field::from_le_bytes_mod_order);
+        Self::hash_base_elements(elements)
+    }
+
+    fn merge(v0: &Self::Digest, v1: &Self::Digest) -> Self::Digest {
+        // no padding is required since both digests fit in the rate
+        debug_assert!(2 * N <= C::RATE);
+        let mut state = vec![C::Field::ZERO; C::WIDTH];
+        state[..N].copy_from_slice(&v0.0);
+        state[N..2 * N].copy_from_slice(&v1.0);
+        permute::<C>(&mut state);
+        Self::digest(&state)
+    }
+
+    fn merge_with_int(seed: &Self::Digest, value: u64) -> Self::Digest {
+        let elements = seed.0.into_iter().chain(int_to_elements(value));
+        Self::hash_base_elements(elements)
+    }
+}
+
+impl<C: Poseidon2Config, const N: usize> AlgebraicHashFn for Poseidon2HashFn<C, N> {
+    type Field = C::Field;
+
+    const STATE_WIDTH: usize = C::WIDTH;
+
+    const RATE_RANGE: Range<usize> = 0..C::RATE;
+
+    fn permute(state: &mut [C::Field]) {
+        permute::<C>(state);
+    }
+
+    fn digest_elements(digest: &Self::Digest) -> &[C::Field] {
+        &digest.0
+    }
+}
+
+impl<C: Poseidon2Config, F: Field<BasePrimeField = C::Field>, const N: usize> ElementHashFn<F>
+    for Poseidon2HashFn<C, N>
+{
+    /// Hashes extension field elements as a sequence of their base field
+    /// coefficients
+    fn hash_elements(elements: impl IntoIterator<Item = F>) -> Self::Digest {
+        let elements = elements.into_iter().collect::<Vec<F>>();
+        Self::hash_base_elements(elements.iter().flat_map(F::to_base_prime_field_elements))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+    use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+    use ministark_gpu::fields::p18446744069414584321::ark::Fq3;
+    use ministark_gpu::fields::p3618502788666131213697322783095070105623107215331596699973092056135872020481::ark::Fp as Fp252;
+
+    #[test]
+    fn goldilocks_merge_matches_hash_elements() {
+        let mut rng = test_rng();
+        let d0 = Poseidon2Digest(core::array::from_fn(|_| Fp::rand(&mut rng)));
+        let d1 = Poseidon2Digest(core::array::from_fn(|_| Fp::rand(&mut rng)));
+
+        let merged = Poseidon2GoldilocksHashFn::merge(&d0, &d1);
+
+        let elements = d0.0.into_iter().chain(d1.0);
+        assert_eq!(Poseidon2GoldilocksHashFn::hash_elements(elements), merged);
+    }
+
+    #[test]
+    fn stark252_merge_matches_hash_elements() {
+        let mut rng = test_rng();
+        let d0 = Poseidon2Digest([Fp252::rand(&mut rng)]);
+        let d1 = Poseidon2Digest([Fp252::rand(&mut rng)]);
+
+        let merged = Poseidon2Stark252HashFn::merge(&d0, &d1);
+
+        assert_eq!(
+            Poseidon2Stark252HashFn::hash_elements([d0.0[0], d1.0[0]]),
+            merged
+        );
+    }
+
+    #[test]
+    fn extension_elements_hash_as_base_coefficients() {
+        let mut rng = test_rng();
+        let elements = (0..5).map(|_| Fq3::rand(&mut rng)).collect::<Vec<Fq3>>();
+        let coeffs = elements.iter().flat_map(Fq3::to_base_prime_field_elements);
+
+        let expected = <Poseidon2GoldilocksHashFn as ElementHashFn<Fp>>::hash_elements(coeffs);
+
+        assert_eq!(expected, Poseidon2GoldilocksHashFn::hash_elements(elements));
+    }
+
+    #[test]
+    fn hash_bytes_distinguishes_trailing_zeros() {
+        let a = Poseidon2Stark252HashFn::hash([1, 2, 3]);
+        let b = Poseidon2Stark252HashFn::hash([1, 2, 3, 0]);
+
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn digest_bytes_match_serialization() {
+        let mut rng = test_rng();
+        let goldilocks = Poseidon2Digest::<Fp, 4>(core::array::from_fn(|_| Fp::rand(&mut rng)));
+        let stark252 = Poseidon2Digest([Fp252::rand(&mut rng)]);
+        let mut goldilocks_bytes = Vec::new();
+        let mut stark252_bytes = Vec::new();
+
+        goldilocks
+            .serialize_compressed(&mut goldilocks_bytes)
+            .unwrap();
+        stark252.serialize_compressed(&mut stark252_bytes).unwrap();
+
+        assert_eq!(goldilocks_bytes, goldilocks.as_bytes());
+        assert_eq!(stark252_bytes, stark252.as_bytes());
+    }
+
+    #[test]
+    fn collision_resistance_is_bounded_by_capacity() {
+        assert_eq!(128, Poseidon2GoldilocksHashFn::COLLISION_RESISTANCE);
+        assert_eq!(126, Poseidon2Stark252HashFn::COLLISION_RESISTANCE);
+    }
+}
@@ -0,0 +1,208 @@
+//! Poseidon2 instance over the Goldilocks field with a state width of 12
+//!
+//! Parameters are from the Horizen Labs reference implementation:
+//! <https://github.com/HorizenLabs/poseidon2/blob/main/plain_implementations/src/poseidon2/poseidon2_instance_goldilocks.rs>
+use super::Poseidon2Config;
+use crate::hash::goldilocks_fp as fp;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+
+/// Poseidon2 over Goldilocks with `t = 12`, `d = 7`, `R_F = 8` and `R_P = 22`
+pub struct Poseidon2Goldilocks12;
+
+impl Poseidon2Config for Poseidon2Goldilocks12 {
+    type Field = Fp;
+
+    const NAME: &'static str = "poseidon2_goldilocks_w12";
+
+    const WIDTH: usize = 12;
+
+    const RATE: usize = 8;
+
+    const ALPHA: u64 = 7;
+
+    const NUM_FULL_ROUNDS: usize = 8;
+
+    const NUM_PARTIAL_ROUNDS: usize = 22;
+
+    #[allow(clippy::unreadable_literal)]
+    const EXTERNAL_ROUND_CONSTANTS: &'static [Fp] = &EXTERNAL_ROUND_CONSTANTS;
+
+    #[allow(clippy::unreadable_literal)]
+    const INTERNAL_ROUND_CONSTANTS: &'static [Fp] = &INTERNAL_ROUND_CONSTANTS;
+
+    const INTERNAL_DIAG_M_1: &'static [Fp] = &INTERNAL_DIAG_M_1;
+}
+
+#[allow(clippy::unreadable_literal)]
+const INTERNAL_DIAG_M_1: [Fp; 12] = [
+    fp(0xc3b6c08e23ba9300),
+    fp(0xd84b5de94a324fb6),
+    fp(0x0d0c371c5b35b84f),
+    fp(0x7964f570e7188037),
+    fp(0x5daf18bbd996604b),
+    fp(0x6743bc47b9595257),
+    fp(0x5528b9362c59bb70),
+    fp(0xac45e25b7127b68b),
+    fp(0xa2077d7dfbb606b5),
+    fp(0xf3faac6faee378ae),
+    fp(0x0c6388b51545e883),
+    fp(0xd27dbb6944917b60),
+];
+
+#[allow(clippy::unreadable_literal)]
+const EXTERNAL_ROUND_CONSTANTS: [Fp; 96] = [
+    fp(0x13dcf33aba214f46),
+    fp(0x30b3b654a1da6d83),
+    fp(0x1fc634ada6159b56),
+    fp(0x937459964dc03466),
+    fp(0xedd2ef2ca7949924),
+    fp(0xede9affde0e22f68),
+    fp(0x8515b9d6bac9282d),
+    fp(0x6b5c07b4e9e900d8),
+    fp(0x1ec66368838c8a08),
+    fp(0x9042367d80d1fbab),
+    fp(0x400283564a3c3799),
+    fp(0x4a00be0466bca75e),
+    fp(0x7913beee58e3817f),
+    fp(0xf545e88532237d90),
+    fp(0x22f8cb8736042005),
+    fp(0x6f04990e247a2623),
+    fp(0xfe22e87ba37c38cd),
+    fp(0xd20e32c85ffe2815),
+    fp(0x117227674048fe73),
+    fp(0x4e9fb7ea98a6b145),
+    fp(0xe0866c232b8af08b),
+    fp(0x00bbc77916884964),
+    fp(0x7031c0fb990d7116),
+    fp(0x240a9e87cf35108f),
+    fp(0x2e6363a5a12244b3),
+    fp(0x5e1c3787d1b5011c),
+    fp(0x4132660e2a196e8b),
+    fp(0x3a013b648d3d4327),
+    fp(0xf79839f49888ea43),
+    fp(0xfe85658ebafe1439),
+    fp(0xb6889825a14240bd),
+    fp(0x578453605541382b),
+    fp(0x4508cda8f6b63ce9),
+    fp(0x9c3ef35848684c91),
+    fp(0x0812bde23c87178c),
+    fp(0xfe49638f7f722c14),
+    fp(0x8e3f688ce885cbf5),
+    fp(0xb8e110acf746a87d),
+    fp(0xb4b2e8973a6dabef),
+    fp(0x9e714c5da3d462ec),
+    fp(0x6438f9033d3d0c15),
+    fp(0x24312f7cf1a27199),
+    fp(0x23f843bb47acbf71),
+    fp(0x9183f11a34be9f01),
+    fp(0x839062fbb9d45dbf),
+    fp(0x24b56e7e6c2e43fa),
+    fp(0xe1683da61c962a72),
+    fp(0xa95c63971a19bfa7),
+    fp(0xc68be7c94882a24d),
+    fp(0xaf996d5d5cdaedd9),
+    fp(0x9717f025e7daf6a5),
+    fp(0x6436679e6e7216f4),
+    fp(0x8a223d99047af267),
+    fp(0xbb512e35a133ba9a),
+    fp(0xfbbf44097671aa03),
+    fp(0xf04058ebf6811e61),
+    fp(0x5cca84703fac7ffb),
+    fp(0x9b55c7945de6469f),
+    fp(0x8e05bf09808e934f),
+    fp(0x2ea900de876307d7),
+    fp(0x7748fff2b38dfb89),
+    fp(0x6b99a676dd3b5d81),
+    fp(0xac4bb7c627cf7c13),
+    fp(0xadb6ebe5e9e2f5ba),
+    fp(0x2d33378cafa24ae3),
+    fp(0x1e5b73807543f8c2),
+    fp(0x09208814bfebb10f),
+    fp(0x782e64b6bb5b93dd),
+    fp(0xadd5a48eac90b50f),
+    fp(0xadd4c54c736ea4b1),
+    fp(0xd58dbb86ed817fd8),
+    fp(0x6d5ed1a533f34ddd),
+    fp(0x28686aa3e36b7cb9),
+    fp(0x591abd3476689f36),
+    fp(0x047d766678f13875),
+    fp(0xa2a11112625f5b49),
+    fp(0x21fd10a3f8304958),
+    fp(0xf9b40711443b0280),
+    fp(0xd2697eb8b2bde88e),
+    fp(0x3493790b51731b3f),
+    fp(0x11caf9dd73764023),
+    fp(0x7acfb8f72878164e),
+    fp(0x744ec4db23cefc26),
+    fp(0x1e00e58f422c6340),
+    fp(0x21dd28d906a62dda),
+    fp(0xf32a46ab5f465b5f),
+    fp(0xbfce13201f3f7e6b),
+    fp(0xf30d2e7adb5304e2),
+    fp(0xecdf4ee4abad48e9),
+    fp(0xf94e82182d395019),
+    fp(0x4ee52e3744d887c5),
+    fp(0xa1341c7cac0083b2),
+    fp(0x2302fb26c30c834a),
+    fp(0xaea3c587273bf7d3),
+    fp(0xf798e24961823ec7),
+    fp(0x962deba3e9a2cd94),
+];
+
+#[allow(clippy::unreadable_literal)]
+const INTERNAL_ROUND_CONSTANTS: [Fp; 22] = [
+    fp(0x4adf842aa75d4316),
+    fp(0xf8fbb871aa4ab4eb),
+    fp(0x68e85b6eb2dd6aeb),
+    fp(0x07a0b06b2d270380),
+    fp(0xd94e0228bd282de4),
+    fp(0x8bdd91d3250c5278),
+    fp(0x209c68b88bba778f),
+    fp(0xb5e18cdab77f3877),
+    fp(0xb296a3e808da93fa),
+    fp(0x8370ecbda11a327e),
+    fp(0x3f9075283775dad8),
+    fp(0xb78095bb23c6aa84),
+    fp(0x3f36b9fe72ad4e5f),
+    fp(0x69bc96780b10b553),
+    fp(0x3f1d341f2eb7b881),
+    fp(0x4e939e9815838818),
+    fp(0xda366b3ae2a31604),
+    fp(0xbc89db1e7287d509),
+    fp(0x6102f411f9ef5659),
+    fp(0x58725c5e7ac1f0ab),
+    fp(0x0df5856c798883e7),
+    fp(0xf7bb62a8da4c961b),
+];
+
+#[cfg(test)]
+#[allow(clippy::unreadable_literal)]
+mod tests {
+    use super::*;
+    use crate::hash::poseidon2::permute;
+    use ark_ff::PrimeField;
+
+    #[test]
+    fn permutation_matches_reference_implementation() {
+        // test vector from the Horizen Labs reference implementation
+        let mut state: [Fp; 12] = core::array::from_fn(|i| Fp::from(i as u64));
+
+        permute::<Poseidon2Goldilocks12>(&mut state);
+
+        let expected = [
+            0x01eaef96bdf1c0c1,
+            0x1f0d2cc525b2540c,
+            0x6282c1dfe1e0358d,
+            0xe780d721f698e1e6,
+            0x280c0b6f753d833b,
+            0x1b942dd5023156ab,
+            0x43f0df3fcccb8398,
+            0xe8e8190585489025,
+            0x56bdbf72f77ada22,
+            0x7911c32bf9dcd705,
+            0xec467926508fbe67,
+            0x6a50450ddf85a6ed,
+        ];
+        assert_eq!(expected, state.map(|s| s.into_bigint().0[0]));
+    }
+}
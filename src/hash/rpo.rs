@@ -0,0 +1,678 @@
+//! Rescue Prime Optimized hash function over the Goldilocks field
+//!
+//! CPU implementation of RPO-256 from <https://eprint.iacr.org/2022/1577.pdf>.
+//! Parameters, padding rules and the Merkle node construction match the Metal
+//! shaders in `ministark-gpu` and the implementation in Miden:
+//! <https://github.com/0xPolygonMiden/crypto/tree/main/src/hash/rpo>
+use super::goldilocks_fp as fp;
+use super::AlgebraicHashFn;
+use super::Digest;
+use super::ElementHashFn;
+use super::HashFn;
+use alloc::vec::Vec;
+use ark_ff::Field;
+use ark_ff::PrimeField;
+use ark_ff::Zero;
+use ark_serialize::CanonicalDeserialize;
+use ark_serialize::CanonicalSerialize;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+use ministark_gpu::fields::p18446744069414584321::ark::Fq3;
+
+/// Number of field elements in the hasher state
+pub const STATE_WIDTH: usize = 12;
+
+/// The capacity portion of the state is located in elements 0, 1, 2 and 3
+const CAPACITY_RANGE: core::ops::Range<usize> = 0..4;
+
+/// The rate portion of the state is located in elements 4 through 11
+const RATE_RANGE: core::ops::Range<usize> = 4..12;
+const RATE_WIDTH: usize = RATE_RANGE.end - RATE_RANGE.start;
+
+/// The digest is the first half of the rate portion of the state
+const DIGEST_RANGE: core::ops::Range<usize> = 4..8;
+const DIGEST_SIZE: usize = DIGEST_RANGE.end - DIGEST_RANGE.start;
+
+/// Number of rounds of the permutation. 7 rounds gives 128 bits of security.
+pub const NUM_ROUNDS: usize = 7;
+
+/// Number of bytes packed into a single field element when hashing bytes
+const BINARY_CHUNK_SIZE: usize = 7;
+
+/// Goldilocks modulus
+const MODULUS: u64 = 0xFFFF_FFFF_0000_0001;
+
+/// RPO-256 digest consisting of 4 Goldilocks field elements
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize,
+)]
+pub struct Rpo256Digest(pub [Fp; DIGEST_SIZE]);
+
+impl Rpo256Digest {
+    pub const fn new(elements: [Fp; DIGEST_SIZE]) -> Self {
+        Self(elements)
+    }
+
+    pub const fn as_elements(&self) -> &[Fp; DIGEST_SIZE] {
+        &self.0
+    }
+}
+
+impl Digest for Rpo256Digest {
+    fn as_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (chunk, element) in bytes.chunks_mut(8).zip(self.0) {
+            let value = element.into_bigint().0[0];
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+impl From<[Fp; DIGEST_SIZE]> for Rpo256Digest {
+    fn from(elements: [Fp; DIGEST_SIZE]) -> Self {
+        Self(elements)
+    }
+}
+
+impl From<Rpo256Digest> for [Fp; DIGEST_SIZE] {
+    fn from(digest: Rpo256Digest) -> Self {
+        digest.0
+    }
+}
+
+/// Rescue Prime Optimized hash function with 256 bit output
+pub struct Rpo256HashFn;
+
+impl Rpo256HashFn {
+    /// Hashes a sequence of base field elements. Uses RPO's padding rule: the
+    /// first capacity element is set to 1 if the input length is not a
+    /// multiple of the rate and the final chunk is padded with a single 1
+    /// followed by zeros.
+    fn hash_base_elements(elements: impl IntoIterator<Item = Fp>) -> Rpo256Digest {
+        let elements = elements.into_iter().collect::<Vec<Fp>>();
+        let mut state = [Fp::zero(); STATE_WIDTH];
+        if elements.len() % RATE_WIDTH != 0 {
+            state[CAPACITY_RANGE.start] = Fp::ONE;
+        }
+
+        for chunk in elements.chunks(RATE_WIDTH) {
+            let rate = &mut state[RATE_RANGE];
+            rate[..chunk.len()].copy_from_slice(chunk);
+            if chunk.len() != RATE_WIDTH {
+                rate[chunk.len()] = Fp::ONE;
+                rate[chunk.len() + 1..].fill(Fp::zero());
+            }
+            permute(&mut state);
+        }
+
+        Rpo256Digest(state[DIGEST_RANGE].try_into().unwrap())
+    }
+}
+
+impl HashFn for Rpo256HashFn {
+    type Digest = Rpo256Digest;
+
+    const COLLISION_RESISTANCE: u32 = 128;
+
+    fn name() -> String {
+        "rpo256".to_string()
+    }
+
+    fn hash(bytes: impl IntoIterator<Item = u8>) -> Rpo256Digest {
+        let bytes = bytes.into_iter().collect::<Vec<u8>>();
+        Self::hash_chunks([&*bytes])
+    }
+
+    /// Packs bytes into field elements 7 bytes at a time (little-endian). The
+    /// final element has a 1 byte appended to it so that inputs which only
+    /// differ by trailing zeros produce different digests.
+    fn hash_chunks<'a>(chunks: impl IntoIterator<Item = &'a [u8]>) -> Rpo256Digest {
+        let mut bytes = chunks.into_iter().flatten().copied().collect::<Vec<u8>>();
+        bytes.push(1);
+        let elements = bytes.chunks(BINARY_CHUNK_SIZE).map(|chunk| {
+            let mut buf = [0; 8];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            Fp::from(u64::from_le_bytes(buf))
+        });
+        Self::hash_base_elements(elements)
+    }
+
+    fn merge(v0: &Rpo256Digest, v1: &Rpo256Digest) -> Rpo256Digest {
+        // no padding is required since the input is exactly the rate width
+        let mut state = [Fp::zero(); STATE_WIDTH];
+        state[RATE_RANGE.start..RATE_RANGE.start + DIGEST_SIZE].copy_from_slice(&v0.0);
+        state[RATE_RANGE.start + DIGEST_SIZE..RATE_RANGE.end].copy_from_slice(&v1.0);
+        permute(&mut state);
+        Rpo256Digest(state[DIGEST_RANGE].try_into().unwrap())
+    }
+
+    fn merge_with_int(seed: &Rpo256Digest, value: u64) -> Rpo256Digest {
+        // a u64 may not fit into a single field element so it is split into two
+        // limbs if needed. The length is recorded in the capacity.
+        let mut state = [Fp::zero(); STATE_WIDTH];
+        state[RATE_RANGE.start..RATE_RANGE.start + DIGEST_SIZE].copy_from_slice(&seed.0);
+        state[RATE_RANGE.start + DIGEST_SIZE] = Fp::from(value);
+        if value < MODULUS {
+            state[RATE_RANGE.start + DIGEST_SIZE + 1] = Fp::ONE;
+        } else {
+            state[RATE_RANGE.start + DIGEST_SIZE + 1] = Fp::from(value / MODULUS);
+            state[RATE_RANGE.start + DIGEST_SIZE + 2] = Fp::ONE;
+        }
+        state[CAPACITY_RANGE.start] = Fp::ONE;
+        permute(&mut state);
+        Rpo256Digest(state[DIGEST_RANGE].try_into().unwrap())
+    }
+}
+
+impl AlgebraicHashFn for Rpo256HashFn {
+    type Field = Fp;
+
+    const STATE_WIDTH: usize = STATE_WIDTH;
+
+    const RATE_RANGE: core::ops::Range<usize> = RATE_RANGE;
+
+    fn permute(state: &mut [Fp]) {
+        permute(state.try_into().unwrap());
+    }
+
+    fn digest_elements(digest: &Rpo256Digest) -> &[Fp] {
+        &digest.0
+    }
+}
+
+impl ElementHashFn<Fp> for Rpo256HashFn {
+    fn hash_elements(elements: impl IntoIterator<Item = Fp>) -> Rpo256Digest {
+        Self::hash_base_elements(elements)
+    }
+}
+
+impl ElementHashFn<Fq3> for Rpo256HashFn {
+    /// Hashes extension field elements as a sequence of their base field
+    /// coefficients
+    fn hash_elements(elements: impl IntoIterator<Item = Fq3>) -> Rpo256Digest {
+        let elements = elements.into_iter().collect::<Vec<Fq3>>();
+        Self::hash_base_elements(
+            elements
+                .iter()
+                .flat_map(Field::to_base_prime_field_elements),
+        )
+    }
+}
+
+/// Applies the RPO permutation to the provided state
+fn permute(state: &mut [Fp; STATE_WIDTH]) {
+    for round in 0..NUM_ROUNDS {
+        apply_round(state, round);
+    }
+}
+
+#[inline]
+fn apply_round(state: &mut [Fp; STATE_WIDTH], round: usize) {
+    // first half
+    apply_mds(state);
+    add_constants(state, &ARK1[round]);
+    apply_sbox(state);
+
+    // second half
+    apply_mds(state);
+    add_constants(state, &ARK2[round]);
+    apply_inv_sbox(state);
+}
+
+#[inline]
+fn apply_mds(state: &mut [Fp; STATE_WIDTH]) {
+    let mut result = [Fp::zero(); STATE_WIDTH];
+    for (i, r) in result.iter_mut().enumerate() {
+        for (j, s) in state.iter().enumerate() {
+            // the MDS matrix is circulant
+            *r += MDS[(STATE_WIDTH + j - i) % STATE_WIDTH] * s;
+        }
+    }
+    *state = result;
+}
+
+#[inline]
+fn add_constants(state: &mut [Fp; STATE_WIDTH], ark: &[Fp; STATE_WIDTH]) {
+    for (s, k) in state.iter_mut().zip(ark) {
+        *s += k;
+    }
+}
+
+/// Computes x^7 for each element of the state
+#[inline]
+fn apply_sbox(state: &mut [Fp; STATE_WIDTH]) {
+    for s in state {
+        let x2 = s.square();
+        let x4 = x2.square();
+        let x3 = x2 * *s;
+        *s = x3 * x4;
+    }
+}
+
+/// Computes x^10540996611094048183 for each element of the state. This is the
+/// inverse of x^7. Uses the same addition chain as Miden (72 multiplications).
+#[inline]
+fn apply_inv_sbox(state: &mut [Fp; STATE_WIDTH]) {
+    // 10540996611094048183 =
+    // 0b1001001001001001001001001001000110110110110110110110110110110111
+    let mut t1 = *state;
+    for t in &mut t1 {
+        *t = t.square();
+    }
+    let mut t2 = t1;
+    for t in &mut t2 {
+        *t = t.square();
+    }
+    let t3 = exp_acc::<3>(t2, t2);
+    let t4 = exp_acc::<6>(t3, t3);
+    let t5 = exp_acc::<12>(t4, t4);
+    let t6 = exp_acc::<6>(t5, t3);
+    let t7 = exp_acc::<31>(t6, t6);
+    for (i, s) in state.iter_mut().enumerate() {
+        let a = (t7[i].square() * t6[i]).square().square();
+        let b = t1[i] * t2[i] * *s;
+        *s = a * b;
+    }
+}
+
+/// Squares each element of `base` `M` times and multiplies by `tail`
+#[inline]
+fn exp_acc<const M: usize>(base: [Fp; STATE_WIDTH], tail: [Fp; STATE_WIDTH]) -> [Fp; STATE_WIDTH] {
+    let mut result = base;
+    for _ in 0..M {
+        for r in &mut result {
+            *r = r.square();
+        }
+    }
+    for (r, t) in result.iter_mut().zip(tail) {
+        *r *= t;
+    }
+    result
+}
+
+/// First row of the circulant MDS matrix
+const MDS: [Fp; STATE_WIDTH] = [
+    fp(7),
+    fp(23),
+    fp(8),
+    fp(26),
+    fp(13),
+    fp(10),
+    fp(9),
+    fp(7),
+    fp(6),
+    fp(22),
+    fp(21),
+    fp(8),
+];
+
+/// Round constants added in the first half of each round
+#[allow(clippy::unreadable_literal)]
+const ARK1: [[Fp; STATE_WIDTH]; NUM_ROUNDS] = [
+    [
+        fp(5789762306288267392),
+        fp(6522564764413701783),
+        fp(17809893479458208203),
+        fp(107145243989736508),
+        fp(6388978042437517382),
+        fp(15844067734406016715),
+        fp(9975000513555218239),
+        fp(3344984123768313364),
+        fp(9959189626657347191),
+        fp(12960773468763563665),
+        fp(9602914297752488475),
+        fp(16657542370200465908),
+    ],
+    [
+        fp(12987190162843096997),
+        fp(653957632802705281),
+        fp(4441654670647621225),
+        fp(4038207883745915761),
+        fp(5613464648874830118),
+        fp(13222989726778338773),
+        fp(3037761201230264149),
+        fp(16683759727265180203),
+        fp(8337364536491240715),
+        fp(3227397518293416448),
+        fp(8110510111539674682),
+        fp(2872078294163232137),
+    ],
+    [
+        fp(18072785500942327487),
+        fp(6200974112677013481),
+        fp(17682092219085884187),
+        fp(10599526828986756440),
+        fp(975003873302957338),
+        fp(8264241093196931281),
+        fp(10065763900435475170),
+        fp(2181131744534710197),
+        fp(6317303992309418647),
+        fp(1401440938888741532),
+        fp(8884468225181997494),
+        fp(13066900325715521532),
+    ],
+    [
+        fp(5674685213610121970),
+        fp(5759084860419474071),
+        fp(13943282657648897737),
+        fp(1352748651966375394),
+        fp(17110913224029905221),
+        fp(1003883795902368422),
+        fp(4141870621881018291),
+        fp(8121410972417424656),
+        fp(14300518605864919529),
+        fp(13712227150607670181),
+        fp(17021852944633065291),
+        fp(6252096473787587650),
+    ],
+    [
+        fp(4887609836208846458),
+        fp(3027115137917284492),
+        fp(9595098600469470675),
+        fp(10528569829048484079),
+        fp(7864689113198939815),
+        fp(17533723827845969040),
+        fp(5781638039037710951),
+        fp(17024078752430719006),
+        fp(109659393484013511),
+        fp(7158933660534805869),
+        fp(2955076958026921730),
+        fp(7433723648458773977),
+    ],
+    [
+        fp(16308865189192447297),
+        fp(11977192855656444890),
+        fp(12532242556065780287),
+        fp(14594890931430968898),
+        fp(7291784239689209784),
+        fp(5514718540551361949),
+        fp(10025733853830934803),
+        fp(7293794580341021693),
+        fp(6728552937464861756),
+        fp(6332385040983343262),
+        fp(13277683694236792804),
+        fp(2600778905124452676),
+    ],
+    [
+        fp(7123075680859040534),
+        fp(1034205548717903090),
+        fp(7717824418247931797),
+        fp(3019070937878604058),
+        fp(11403792746066867460),
+        fp(10280580802233112374),
+        fp(337153209462421218),
+        fp(13333398568519923717),
+        fp(3596153696935337464),
+        fp(8104208463525993784),
+        fp(14345062289456085693),
+        fp(17036731477169661256),
+    ],
+];
+
+/// Round constants added in the second half of each round
+#[allow(clippy::unreadable_literal)]
+const ARK2: [[Fp; STATE_WIDTH]; NUM_ROUNDS] = [
+    [
+        fp(6077062762357204287),
+        fp(15277620170502011191),
+        fp(5358738125714196705),
+        fp(14233283787297595718),
+        fp(13792579614346651365),
+        fp(11614812331536767105),
+        fp(14871063686742261166),
+        fp(10148237148793043499),
+        fp(4457428952329675767),
+        fp(15590786458219172475),
+        fp(10063319113072092615),
+        fp(14200078843431360086),
+    ],
+    [
+        fp(6202948458916099932),
+        fp(17690140365333231091),
+        fp(3595001575307484651),
+        fp(373995945117666487),
+        fp(1235734395091296013),
+        fp(14172757457833931602),
+        fp(707573103686350224),
+        fp(15453217512188187135),
+        fp(219777875004506018),
+        fp(17876696346199469008),
+        fp(17731621626449383378),
+        fp(2897136237748376248),
+    ],
+    [
+        fp(8023374565629191455),
+        fp(15013690343205953430),
+        fp(4485500052507912973),
+        fp(12489737547229155153),
+        fp(9500452585969030576),
+        fp(2054001340201038870),
+        fp(12420704059284934186),
+        fp(355990932618543755),
+        fp(9071225051243523860),
+        fp(12766199826003448536),
+        fp(9045979173463556963),
+        fp(12934431667190679898),
+    ],
+    [
+        fp(18389244934624494276),
+        fp(16731736864863925227),
+        fp(4440209734760478192),
+        fp(17208448209698888938),
+        fp(8739495587021565984),
+        fp(17000774922218161967),
+        fp(13533282547195532087),
+        fp(525402848358706231),
+        fp(16987541523062161972),
+        fp(5466806524462797102),
+        fp(14512769585918244983),
+        fp(10973956031244051118),
+    ],
+    [
+        fp(6982293561042362913),
+        fp(14065426295947720331),
+        fp(16451845770444974180),
+        fp(7139138592091306727),
+        fp(9012006439959783127),
+        fp(14619614108529063361),
+        fp(1394813199588124371),
+        fp(4635111139507788575),
+        fp(16217473952264203365),
+        fp(10782018226466330683),
+        fp(6844229992533662050),
+        fp(7446486531695178711),
+    ],
+    [
+        fp(3736792340494631448),
+        fp(577852220195055341),
+        fp(6689998335515779805),
+        fp(13886063479078013492),
+        fp(14358505101923202168),
+        fp(7744142531772274164),
+        fp(16135070735728404443),
+        fp(12290902521256031137),
+        fp(12059913662657709804),
+        fp(16456018495793751911),
+        fp(4571485474751953524),
+        fp(17200392109565783176),
+    ],
+    [
+        fp(17130398059294018733),
+        fp(519782857322261988),
+        fp(9625384390925085478),
+        fp(1664893052631119222),
+        fp(7629576092524553570),
+        fp(3485239601103661425),
+        fp(9755891797164033838),
+        fp(15218148195153269027),
+        fp(16460604813734957368),
+        fp(9643968136937729763),
+        fp(3611348709641382851),
+        fp(18256379591337759196),
+    ],
+];
+
+#[cfg(test)]
+#[allow(clippy::unreadable_literal)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+
+    #[test]
+    fn compile_time_conversion_matches_field() {
+        for value in [0, 1, 7, 23, MODULUS - 1, u64::MAX / 3] {
+            assert_eq!(Fp::from(value), fp(value));
+        }
+    }
+
+    #[test]
+    fn hash_elements_matches_reference_implementation() {
+        // test vector from Miden's RPO implementation
+        let expected = Rpo256Digest([
+            Fp::from(1502364727743950833u64),
+            Fp::from(5880949717274681448u64),
+            Fp::from(162790463902224431u64),
+            Fp::from(6901340476773664264u64),
+        ]);
+
+        let digest = Rpo256HashFn::hash_elements([Fp::zero()]);
+
+        assert_eq!(expected, digest);
+    }
+
+    #[test]
+    fn inv_sbox_inverts_sbox() {
+        let mut rng = test_rng();
+        let state: [Fp; STATE_WIDTH] = core::array::from_fn(|_| Fp::rand(&mut rng));
+        let mut actual = state;
+
+        apply_sbox(&mut actual);
+        apply_inv_sbox(&mut actual);
+
+        assert_eq!(state, actual);
+    }
+
+    #[test]
+    fn inv_sbox_matches_pow() {
+        let mut rng = test_rng();
+        let state: [Fp; STATE_WIDTH] = core::array::from_fn(|_| Fp::rand(&mut rng));
+        let mut actual = state;
+
+        apply_inv_sbox(&mut actual);
+
+        let expected = state.map(|s| s.pow([10540996611094048183]));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn merge_matches_hash_elements() {
+        let mut rng = test_rng();
+        let d0 = Rpo256Digest(core::array::from_fn(|_| Fp::rand(&mut rng)));
+        let d1 = Rpo256Digest(core::array::from_fn(|_| Fp::rand(&mut rng)));
+
+        let merged = Rpo256HashFn::merge(&d0, &d1);
+
+        let elements = d0.0.into_iter().chain(d1.0);
+        assert_eq!(Rpo256HashFn::hash_elements(elements), merged);
+    }
+
+    #[test]
+    fn hash_elements_pads_partial_chunks() {
+        let elements = [Fp::ONE, Fp::from(2u8), Fp::from(3u8)];
+        let padded = [elements[0], elements[1], elements[2], Fp::ONE];
+
+        let digest = Rpo256HashFn::hash_elements(elements);
+
+        assert_ne!(Rpo256HashFn::hash_elements(padded), digest);
+    }
+
+    #[test]
+    fn extension_elements_hash_as_base_coefficients() {
+        let mut rng = test_rng();
+        let elements = (0..5).map(|_| Fq3::rand(&mut rng)).collect::<Vec<Fq3>>();
+        let coeffs = elements
+            .iter()
+            .flat_map(Field::to_base_prime_field_elements);
+
+        let expected = <Rpo256HashFn as ElementHashFn<Fp>>::hash_elements(coeffs);
+
+        assert_eq!(expected, Rpo256HashFn::hash_elements(elements));
+    }
+
+    #[test]
+    fn merge_with_int_handles_values_above_modulus() {
+        let seed = Rpo256Digest(core::array::from_fn(|i| Fp::from(i as u64)));
+        let small = Rpo256HashFn::merge_with_int(&seed, 5);
+        let large = Rpo256HashFn::merge_with_int(&seed, MODULUS + 5);
+
+        assert_ne!(small, large);
+    }
+
+    #[test]
+    fn hash_bytes_distinguishes_trailing_zeros() {
+        let a = Rpo256HashFn::hash([1, 2, 3]);
+        let b = Rpo256HashFn::hash([1, 2, 3, 0]);
+
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn digest_serialization_round_trips() {
+        let mut rng = test_rng();
+        let digest = Rpo256Digest(core::array::from_fn(|_| Fp::rand(&mut rng)));
+        let mut bytes = Vec::new();
+
+        digest.serialize_compressed(&mut bytes).unwrap();
+
+        assert_eq!(bytes, digest.as_bytes());
+        let actual = Rpo256Digest::deserialize_compressed(&*bytes).unwrap();
+        assert_eq!(digest, actual);
+    }
+
+    #[cfg(all(feature = "gpu", target_arch = "aarch64", target_os = "macos"))]
+    #[test]
+    fn matches_gpu_implementation() {
+        use crate::utils::GpuAllocator;
+        use ministark_gpu::plan::gen_rpo_merkle_tree;
+        use ministark_gpu::plan::GpuRpo256ColumnMajor;
+
+        let n = 2048;
+        let mut rng = test_rng();
+        let num_cols = 11;
+        let cols = (0..num_cols)
+            .map(|_| {
+                let mut col = Vec::with_capacity_in(n, GpuAllocator);
+                col.extend((0..n).map(|_| Fp::rand(&mut rng)));
+                col
+            })
+            .collect::<Vec<_>>();
+
+        let requires_padding = num_cols % RATE_WIDTH != 0;
+        let mut hasher = GpuRpo256ColumnMajor::new(n, requires_padding);
+        for col in &cols {
+            hasher.update(col);
+        }
+        let gpu_leaves = pollster::block_on(hasher.finish());
+        let gpu_nodes = pollster::block_on(gen_rpo_merkle_tree(&gpu_leaves));
+
+        let cpu_leaves = (0..n)
+            .map(|row| Rpo256HashFn::hash_elements(cols.iter().map(|col| col[row])))
+            .collect::<Vec<Rpo256Digest>>();
+        for (gpu, cpu) in gpu_leaves.iter().zip(&cpu_leaves) {
+            assert_eq!(*gpu, cpu.0);
+        }
+        // nodes are stored in a vector of size n where the root is at index 1
+        let mut layer = cpu_leaves;
+        while layer.len() > 1 {
+            layer = layer
+                .chunks(2)
+                .map(|pair| Rpo256HashFn::merge(&pair[0], &pair[1]))
+                .collect();
+            let offset = layer.len();
+            for (i, node) in layer.iter().enumerate() {
+                assert_eq!(gpu_nodes[offset + i], node.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,429 @@
+//! Standalone FRI low degree test
+//!
+//! Proves that a batch of committed codewords are evaluations of polynomials
+//! below a degree bound. Unlike [`crate::stark::Stark`] this isn't tied to an
+//! AIR which allows FRI to be used as a polynomial commitment scheme inside
+//! other protocols. Codewords are committed to in bit-reversed order so query
+//! position `i` refers to the point `domain.element(bit_reverse_index(n, i))`.
+
+use crate::fri;
+use crate::fri::is_supported_folding_factor;
+use crate::fri::FriOptions;
+use crate::fri::FriProof;
+use crate::fri::FriProver;
+use crate::fri::FriVerifier;
+use crate::merkle;
+use crate::merkle::Commitment;
+use crate::merkle::MatrixMerkleTree;
+use crate::merkle::MerkleTree;
+use crate::random::draw_multiple;
+use crate::random::PublicCoin;
+use crate::utils::GpuAllocator;
+use crate::utils::GpuVec;
+use crate::Matrix;
+use crate::ProofOptions;
+use alloc::vec::Vec;
+use ark_ff::FftField;
+use ark_ff::Field;
+use ark_poly::domain::DomainCoeff;
+use ark_poly::EvaluationDomain;
+use ark_poly::Radix2EvaluationDomain;
+use ark_serialize::CanonicalDeserialize;
+use ark_serialize::CanonicalSerialize;
+use core::iter::zip;
+use core::marker::PhantomData;
+use ministark_gpu::utils::bit_reverse;
+use ministark_gpu::GpuField;
+use snafu::Snafu;
+
+/// Parameters of the low degree test. The blowup factor is the ratio between
+/// the size of the evaluation domain and the degree bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LdtOptions {
+    pub num_queries: usize,
+    pub folding_factor: usize,
+    pub max_remainder_coeffs: usize,
+    pub grinding_factor: u8,
+}
+
+impl LdtOptions {
+    pub const fn new(
+        num_queries: usize,
+        folding_factor: usize,
+        max_remainder_coeffs: usize,
+        grinding_factor: u8,
+    ) -> Self {
+        Self {
+            num_queries,
+            folding_factor,
+            max_remainder_coeffs,
+            grinding_factor,
+        }
+    }
+
+    /// Returns the FRI options for codewords over a domain of `domain_size`
+    /// with polynomials of degree less than `degree_bound`
+    pub const fn fri_options(
+        &self,
+        degree_bound: usize,
+        domain_size: usize,
+    ) -> Result<FriOptions, Error> {
+        if !degree_bound.is_power_of_two() || degree_bound >= domain_size {
+            return Err(Error::InvalidDegreeBound {
+                degree_bound,
+                domain_size,
+            });
+        }
+        if !is_supported_folding_factor(self.folding_factor) {
+            return Err(Error::UnsupportedFoldingFactor {
+                folding_factor: self.folding_factor,
+            });
+        }
+        if self.num_queries == 0 || self.num_queries > domain_size {
+            return Err(Error::InvalidNumQueries {
+                num_queries: self.num_queries,
+                domain_size,
+            });
+        }
+        if self.grinding_factor > ProofOptions::MAX_GRINDING_FACTOR {
+            return Err(Error::GrindingFactorTooLarge {
+                grinding_factor: self.grinding_factor,
+            });
+        }
+        let blowup_factor = domain_size / degree_bound;
+        let options = FriOptions::new(
+            blowup_factor,
+            self.folding_factor,
+            self.max_remainder_coeffs,
+        );
+        // the remainder must be a polynomial with at least one coefficient
+        if self.max_remainder_coeffs == 0 || options.remainder_size(domain_size) < blowup_factor {
+            return Err(Error::InvalidMaxRemainderCoeffs {
+                max_remainder_coeffs: self.max_remainder_coeffs,
+            });
+        }
+        Ok(options)
+    }
+}
+
+/// Commitment to a batch of codewords over a coset
+pub struct BatchCommitment<F: GpuField, M: MerkleTree>
+where
+    F::FftField: FftField,
+{
+    domain: Radix2EvaluationDomain<F::FftField>,
+    /// Codewords in bit-reversed order
+    evaluations: Matrix<F>,
+    merkle_tree: M,
+}
+
+impl<F: GpuField + Field + DomainCoeff<F::FftField>, M: MatrixMerkleTree<F>> BatchCommitment<F, M>
+where
+    F::FftField: FftField,
+{
+    /// Commits to the evaluations of polynomials over `domain`. Each column of
+    /// `polynomials` holds the coefficients of a polynomial.
+    pub fn from_polynomials(
+        polynomials: Matrix<F>,
+        domain: Radix2EvaluationDomain<F::FftField>,
+    ) -> Result<Self, Error> {
+        Self::check_num_columns(&polynomials)?;
+        if polynomials.num_rows() > domain.size() {
+            return Err(Error::DomainSizeMismatch {
+                num_rows: polynomials.num_rows(),
+                domain_size: domain.size(),
+            });
+        }
+        let evaluations = polynomials.into_bit_reversed_evaluations(domain);
+        Ok(Self::new(evaluations, domain))
+    }
+
+    /// Commits to codewords over `domain`. Each column of `evaluations` holds
+    /// the evaluations of a polynomial in the natural order of the domain.
+    pub fn from_evaluations(
+        mut evaluations: Matrix<F>,
+        domain: Radix2EvaluationDomain<F::FftField>,
+    ) -> Result<Self, Error> {
+        Self::check_num_columns(&evaluations)?;
+        if evaluations.num_rows() != domain.size() {
+            return Err(Error::DomainSizeMismatch {
+                num_rows: evaluations.num_rows(),
+                domain_size: domain.size(),
+            });
+        }
+        evaluations.bit_reverse_rows();
+        Ok(Self::new(evaluations, domain))
+    }
+
+    fn new(evaluations: Matrix<F>, domain: Radix2EvaluationDomain<F::FftField>) -> Self {
+        let merkle_tree = M::from_matrix(&evaluations);
+        Self {
+            domain,
+            evaluations,
+            merkle_tree,
+        }
+    }
+
+    fn check_num_columns(matrix: &Matrix<F>) -> Result<(), Error> {
+        if matrix.num_cols() == 0 {
+            return Err(Error::EmptyBatch);
+        }
+        Ok(())
+    }
+
+    pub fn root(&self) -> M::Root {
+        self.merkle_tree.root()
+    }
+
+    pub const fn domain(&self) -> Radix2EvaluationDomain<F::FftField> {
+        self.domain
+    }
+
+    /// Returns the committed codewords in bit-reversed order
+    pub const fn evaluations(&self) -> &Matrix<F> {
+        &self.evaluations
+    }
+}
+
+/// Proof that a batch of committed codewords have low degree
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
+pub struct LdtProof<F: Field, M: MatrixMerkleTree<F>> {
+    pub fri_proof: FriProof<F, M::Root, M>,
+    pub pow_nonce: u64,
+    /// Rows of the committed codewords at the query positions
+    pub query_values: Vec<F>,
+    pub query_proof: M::Proof,
+}
+
+/// Generates a proof that the committed codewords are evaluations of
+/// polynomials of degree less than `degree_bound`. The commitment is absorbed
+/// into the public coin before any randomness is drawn.
+pub fn prove<
+    F: GpuField + Field + DomainCoeff<F::FftField>,
+    M: MatrixMerkleTree<F>,
+    P: PublicCoin<Field = F, Digest = <M::Root as Commitment>::Digest>,
+>(
+    options: LdtOptions,
+    commitment: &BatchCommitment<F, M>,
+    degree_bound: usize,
+    public_coin: &mut P,
+) -> Result<LdtProof<F, M>, ProvingError>
+where
+    F::FftField: FftField,
+{
+    let domain = commitment.domain;
+    let domain_size = domain.size();
+    let fri_options = options.fri_options(degree_bound, domain_size)?;
+
+    public_coin.reseed_with_commitment(&commitment.root());
+    let evaluations = &commitment.evaluations;
+    let coeffs = draw_multiple(public_coin, evaluations.num_cols());
+    let mut combined = Vec::new_in(GpuAllocator);
+    combined.resize(domain_size, F::zero());
+    for (column, coeff) in zip(&evaluations.0, coeffs) {
+        for (acc, v) in zip(&mut combined, column) {
+            *acc += *v * coeff;
+        }
+    }
+    check_degree(&combined, domain, degree_bound)?;
+
+    let mut fri_prover = FriProver::<F, M::Root, M>::new(fri_options, domain.coset_offset());
+    let mut channel = PublicCoinChannel(public_coin, PhantomData);
+    fri_prover.build_layers(&mut channel, combined)?;
+
+    let mut pow_nonce = 0;
+    if options.grinding_factor != 0 {
+        pow_nonce = public_coin
+            .grind_proof_of_work(options.grinding_factor)
+            .ok_or(ProvingError::ProofOfWorkNotFound {
+                grinding_factor: options.grinding_factor,
+            })?;
+        public_coin.reseed_with_int(pow_nonce);
+    }
+
+    let positions = Vec::from_iter(public_coin.draw_queries(options.num_queries, domain_size));
+    let fri_proof = fri_prover.into_proof(&positions)?;
+    let query_proof = commitment.merkle_tree.prove_rows(&positions)?;
+    let query_values = positions
+        .iter()
+        .flat_map(|&position| evaluations.get_row(position).unwrap())
+        .collect();
+
+    Ok(LdtProof {
+        fri_proof,
+        pow_nonce,
+        query_values,
+        query_proof,
+    })
+}
+
+/// Verifies a proof that `num_columns` codewords over `domain` committed to by
+/// `root` are evaluations of polynomials of degree less than `degree_bound`.
+///
+/// Returns the query positions so the queried values in the proof can be used
+/// by the caller.
+pub fn verify<
+    F: GpuField + Field + DomainCoeff<F::FftField>,
+    M: MatrixMerkleTree<F>,
+    P: PublicCoin<Field = F, Digest = <M::Root as Commitment>::Digest>,
+>(
+    options: LdtOptions,
+    root: &M::Root,
+    num_columns: usize,
+    domain: Radix2EvaluationDomain<F::FftField>,
+    degree_bound: usize,
+    proof: LdtProof<F, M>,
+    public_coin: &mut P,
+) -> Result<Vec<usize>, VerificationError>
+where
+    F::FftField: FftField,
+{
+    let domain_size = domain.size();
+    let fri_options = options.fri_options(degree_bound, domain_size)?;
+    if num_columns == 0 {
+        return Err(Error::EmptyBatch.into());
+    }
+    let expected_num_values = options.num_queries * num_columns;
+    if proof.query_values.len() != expected_num_values {
+        return Err(VerificationError::QueryValueCountMismatch {
+            expected: expected_num_values,
+            actual: proof.query_values.len(),
+        });
+    }
+
+    public_coin.reseed_with_commitment(root);
+    let coeffs = draw_multiple(public_coin, num_columns);
+    let fri_verifier = FriVerifier::<F, M::Root, M>::new(
+        public_coin,
+        fri_options,
+        proof.fri_proof,
+        degree_bound - 1,
+        domain.coset_offset(),
+    )?;
+
+    if options.grinding_factor != 0 {
+        if !public_coin.verify_proof_of_work(options.grinding_factor, proof.pow_nonce) {
+            return Err(VerificationError::ProofOfWork);
+        }
+        public_coin.reseed_with_int(proof.pow_nonce);
+    }
+
+    let positions = Vec::from_iter(public_coin.draw_queries(options.num_queries, domain_size));
+    let rows = proof.query_values.chunks(num_columns).collect::<Vec<_>>();
+    M::verify_rows(root, &positions, &rows, proof.query_proof)
+        .map_err(|_| VerificationError::QueryDoesNotMatchCommitment)?;
+
+    let evaluations = rows
+        .iter()
+        .map(|row| zip(*row, &coeffs).map(|(v, coeff)| *v * coeff).sum())
+        .collect::<Vec<F>>();
+    fri_verifier.verify(&positions, &evaluations)?;
+    Ok(positions)
+}
+
+/// Checks the bit-reversed codeword is a polynomial of degree less than
+/// `degree_bound`
+fn check_degree<F: GpuField + Field + DomainCoeff<F::FftField>>(
+    codeword: &GpuVec<F>,
+    domain: Radix2EvaluationDomain<F::FftField>,
+    degree_bound: usize,
+) -> Result<(), ProvingError>
+where
+    F::FftField: FftField,
+{
+    let mut evaluations = codeword.to_vec();
+    bit_reverse(&mut evaluations);
+    let coeffs = domain.ifft(&evaluations);
+    if coeffs[degree_bound..].iter().all(F::is_zero) {
+        Ok(())
+    } else {
+        Err(ProvingError::DegreeBoundExceeded { degree_bound })
+    }
+}
+
+/// Forwards FRI commitments to a public coin
+struct PublicCoinChannel<'a, P, D>(&'a mut P, PhantomData<D>);
+
+impl<P: PublicCoin, D: Commitment<Digest = P::Digest>> fri::ProverChannel
+    for PublicCoinChannel<'_, P, D>
+where
+    P::Field: GpuField,
+{
+    type Commitment = D;
+    type Field = P::Field;
+
+    fn commit_fri_layer(&mut self, layer_root: D) {
+        self.0.reseed_with_commitment(&layer_root);
+    }
+
+    fn commit_remainder(&mut self, remainder_coeffs: &[P::Field]) {
+        self.0.reseed_with_field_element_vector(remainder_coeffs);
+    }
+
+    fn draw_fri_alpha(&mut self) -> P::Field {
+        self.0.draw()
+    }
+}
+
+/// Errors that are returned for invalid low degree test parameters
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display(
+        "degree bound {degree_bound} must be a power of two less than the domain size \
+         ({domain_size})"
+    ))]
+    InvalidDegreeBound {
+        degree_bound: usize,
+        domain_size: usize,
+    },
+    #[snafu(display("folding factor {folding_factor} is not supported"))]
+    UnsupportedFoldingFactor { folding_factor: usize },
+    #[snafu(display("{num_queries} queries can't be drawn from a domain of size {domain_size}"))]
+    InvalidNumQueries {
+        num_queries: usize,
+        domain_size: usize,
+    },
+    #[snafu(display("grinding factor {grinding_factor} is too large"))]
+    GrindingFactorTooLarge { grinding_factor: u8 },
+    #[snafu(display("max remainder coefficients ({max_remainder_coeffs}) is too small"))]
+    InvalidMaxRemainderCoeffs { max_remainder_coeffs: usize },
+    #[snafu(display("batch must contain at least one codeword"))]
+    EmptyBatch,
+    #[snafu(display("matrix with {num_rows} rows doesn't fit a domain of size {domain_size}"))]
+    DomainSizeMismatch { num_rows: usize, domain_size: usize },
+}
+
+/// Errors that are returned when generating a low degree proof
+#[derive(Debug, Snafu)]
+pub enum ProvingError {
+    #[snafu(context(false))]
+    #[snafu(display("{source}"))]
+    InvalidParameters { source: Error },
+    #[snafu(display("committed codewords exceed the degree bound {degree_bound}"))]
+    DegreeBoundExceeded { degree_bound: usize },
+    #[snafu(display("no proof of work nonce found for grinding factor {grinding_factor}"))]
+    ProofOfWorkNotFound { grinding_factor: u8 },
+    #[snafu(context(false))]
+    #[snafu(display("failed to open the commitment: {source}"))]
+    Merkle { source: merkle::Error },
+    #[snafu(context(false))]
+    #[snafu(display("{source}"))]
+    Fri { source: fri::ProvingError },
+}
+
+/// Errors that are returned during verification of a low degree proof
+#[derive(Debug, Snafu)]
+pub enum VerificationError {
+    #[snafu(context(false))]
+    #[snafu(display("{source}"))]
+    InvalidParameters { source: Error },
+    #[snafu(display("proof has {actual} query values but {expected} were expected"))]
+    QueryValueCountMismatch { expected: usize, actual: usize },
+    #[snafu(display("insufficient proof of work"))]
+    ProofOfWork,
+    #[snafu(display("queries do not resolve to the commitment"))]
+    QueryDoesNotMatchCommitment,
+    #[snafu(context(false))]
+    #[snafu(display("fri verification failed: {source}"))]
+    FriVerification { source: fri::VerificationError },
+}
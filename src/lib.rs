@@ -33,6 +33,7 @@
 #[macro_use]
 pub mod macros;
 pub mod air;
+pub mod arguments;
 pub mod challenges;
 pub mod channel;
 pub mod composer;
@@ -44,12 +45,19 @@ pub mod expression;
 pub mod fri;
 pub mod hash;
 pub mod hints;
+pub mod ldt;
+pub mod logup;
 pub mod matrix;
 pub mod merkle;
 pub mod proof;
 pub mod prover;
 pub mod random;
+pub mod security;
+pub mod selectors;
 pub mod stark;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod tamper;
+pub mod telemetry;
 pub mod trace;
 pub mod utils;
 pub mod verifier;
@@ -69,7 +77,9 @@ use core::ops::Mul;
 use core::ops::MulAssign;
 use core::ops::Sub;
 use core::ops::SubAssign;
+use fri::FoldingSchedule;
 use fri::FriOptions;
+use fri::RemainderCommitment;
 pub use matrix::Matrix;
 use ministark_gpu::GpuAdd;
 use ministark_gpu::GpuFftField;
@@ -89,7 +99,15 @@ pub struct ProofOptions {
     pub lde_blowup_factor: u8,
     pub grinding_factor: u8,
     pub fri_folding_factor: u8,
+    /// Folding factors of the first FRI layers. See
+    /// [`ProofOptions::with_fri_folding_schedule`]
+    pub fri_folding_schedule: FoldingSchedule,
     pub fri_max_remainder_coeffs: u8,
+    /// How the FRI remainder is committed to. See
+    /// [`ProofOptions::with_fri_remainder_commitment`]
+    pub fri_remainder_commitment: RemainderCommitment,
+    /// Hides the witness. See [`ProofOptions::with_zero_knowledge`]
+    pub zero_knowledge: bool,
 }
 
 impl ProofOptions {
@@ -117,17 +135,74 @@ impl ProofOptions {
             lde_blowup_factor,
             grinding_factor,
             fri_folding_factor,
+            fri_folding_schedule: FoldingSchedule::new(&[]),
             fri_max_remainder_coeffs,
+            fri_remainder_commitment: RemainderCommitment::Coefficients,
+            zero_knowledge: false,
         }
     }
 
-    pub fn into_fri_options(self) -> FriOptions {
+    /// Sets the folding factors of the first FRI layers e.g. `[8, 8, 4, 2]`.
+    /// Layers after the schedule fold by `fri_folding_factor`. This trades
+    /// proof size against the verifier's cost per layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the schedule is longer than [`FoldingSchedule::MAX_LEN`] or
+    /// contains a folding factor other than 2, 4, 8 or 16
+    pub const fn with_fri_folding_schedule(mut self, folding_factors: &[u8]) -> Self {
+        self.fri_folding_schedule = FoldingSchedule::new(folding_factors);
+        self
+    }
+
+    /// Sets how the FRI remainder is committed to. Committing to the remainder
+    /// codeword adds a commitment to the proof but keeps the coefficients of
+    /// the remainder polynomial out of the transcript.
+    pub const fn with_fri_remainder_commitment(
+        mut self,
+        remainder_commitment: RemainderCommitment,
+    ) -> Self {
+        self.fri_remainder_commitment = remainder_commitment;
+        self
+    }
+
+    /// Enables or disables zero knowledge proofs.
+    ///
+    /// In zero knowledge mode the trace is padded with as many random rows as
+    /// it has rows which doubles the degree of the trace polynomials. Trace
+    /// commitments use salted leaves, the segments of the composition
+    /// polynomial are masked with random polynomials that cancel out and the
+    /// DEEP composition polynomial is masked with a random polynomial. Since
+    /// the degree of the polynomials doubles the LDE blowup factor available to
+    /// FRI is halved so the LDE blowup factor must be at least 4.
+    ///
+    /// The random rows hide at most as many openings as the trace has rows so
+    /// `num_queries` plus the number of row offsets the constraints reference
+    /// must not exceed the trace length.
+    pub const fn with_zero_knowledge(mut self, zero_knowledge: bool) -> Self {
+        self.zero_knowledge = zero_knowledge;
+        self
+    }
+
+    /// Returns the blowup factor of the polynomial committed to with FRI
+    pub const fn fri_blowup_factor(&self) -> usize {
+        let lde_blowup_factor = self.lde_blowup_factor as usize;
+        if self.zero_knowledge {
+            lde_blowup_factor / 2
+        } else {
+            lde_blowup_factor
+        }
+    }
+
+    pub const fn into_fri_options(self) -> FriOptions {
         // TODO: move fri params into struct
         FriOptions::new(
-            self.lde_blowup_factor.into(),
-            self.fri_folding_factor.into(),
-            self.fri_max_remainder_coeffs.into(),
+            self.fri_blowup_factor(),
+            self.fri_folding_factor as usize,
+            self.fri_max_remainder_coeffs as usize,
         )
+        .with_folding_schedule(self.fri_folding_schedule)
+        .with_remainder_commitment(self.fri_remainder_commitment)
     }
 }
 
@@ -0,0 +1,222 @@
+//! `LogUp` lookup argument <https://eprint.iacr.org/2022/1530>
+//!
+//! Proves every looked-up value is a row of a table by checking
+//! `Σ 1/(α - f) = Σ m/(α - t)` where `f` are the looked-up values, `t` are the
+//! table values and `m` are the number of times each table row is looked up.
+//! Tuples are compressed into a single value `v_0 + β*v_1 + β^2*v_2 + ...`.
+//!
+//! Each looked-up and table value has an extension column that holds its
+//! fraction and a final column sums the fractions of all previous rows. The
+//! last value of this running sum is checked against a hint.
+use crate::arguments::compress;
+use crate::arguments::compress_values;
+use crate::arguments::ArgumentExpr;
+use crate::arguments::RowEvaluator;
+use crate::challenges::Challenges;
+use crate::constraints::AlgebraicItem;
+use crate::constraints::Constraint;
+use crate::constraints::ExecutionTraceColumn;
+use crate::constraints::Hint;
+use crate::constraints::VerifierChallenge;
+use crate::expression::Expr;
+use crate::selectors::every_row;
+use crate::selectors::every_row_except_last;
+use crate::selectors::first_row;
+use crate::selectors::last_row;
+use crate::trace::Trace;
+use crate::utils::FieldVariant;
+use crate::utils::GpuAllocator;
+use crate::Matrix;
+use crate::StarkExtensionOf;
+use alloc::vec::Vec;
+use ark_ff::batch_inversion;
+use ark_ff::FftField;
+use ministark_gpu::GpuFftField;
+
+/// Expression over the execution trace that is looked up or part of a table
+pub type LookupExpr<Fp, Fq> = ArgumentExpr<Fp, Fq>;
+
+/// Builds the extension columns, constraints and hint of a `LogUp` argument
+#[derive(Clone)]
+pub struct LogUp<Fp: 'static, Fq: 'static> {
+    alpha: usize,
+    beta: usize,
+    first_column: usize,
+    final_sum_hint: usize,
+    lookups: Vec<Vec<LookupExpr<Fp, Fq>>>,
+    tables: Vec<(Vec<LookupExpr<Fp, Fq>>, usize)>,
+}
+
+impl<Fp, Fq> LogUp<Fp, Fq> {
+    /// `alpha` is the challenge fractions are taken at and `beta` compresses
+    /// tuples. The argument's columns start at `first_column` and the final
+    /// value of the running sum must equal `final_sum_hint`.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(
+        alpha: impl VerifierChallenge,
+        beta: impl VerifierChallenge,
+        first_column: impl ExecutionTraceColumn,
+        final_sum_hint: impl Hint,
+    ) -> Self {
+        Self {
+            alpha: alpha.index(),
+            beta: beta.index(),
+            first_column: first_column.index(),
+            final_sum_hint: final_sum_hint.index(),
+            lookups: Vec::new(),
+            tables: Vec::new(),
+        }
+    }
+
+    /// Adds a tuple that is looked up in every row
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tuple is empty
+    pub fn lookup(mut self, values: impl IntoIterator<Item = LookupExpr<Fp, Fq>>) -> Self {
+        let values = Vec::from_iter(values);
+        assert!(!values.is_empty(), "looked-up tuple is empty");
+        self.lookups.push(values);
+        self
+    }
+
+    /// Adds a table with a row of values in every row of the trace. The column
+    /// `multiplicity` holds the number of times each row is looked up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tuple is empty
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn table(
+        mut self,
+        values: impl IntoIterator<Item = LookupExpr<Fp, Fq>>,
+        multiplicity: impl ExecutionTraceColumn,
+    ) -> Self {
+        let values = Vec::from_iter(values);
+        assert!(!values.is_empty(), "table tuple is empty");
+        self.tables.push((values, multiplicity.index()));
+        self
+    }
+
+    /// Returns the number of extension columns used by the argument
+    pub const fn num_columns(&self) -> usize {
+        self.lookups.len() + self.tables.len() + 1
+    }
+
+    /// Returns the index of the column that holds the running sum
+    pub const fn running_sum_column(&self) -> usize {
+        self.first_column + self.lookups.len() + self.tables.len()
+    }
+
+    /// Returns the hint the final value of the running sum is checked against.
+    /// The final sum is zero if every lookup is into a table in the trace.
+    /// Lookups into tables outside the trace contribute their fractions
+    /// instead. See [`LogUp::fraction`].
+    pub const fn final_sum_hint(&self, final_sum: Fq) -> (usize, Fq) {
+        (self.final_sum_hint, final_sum)
+    }
+
+    /// Returns the columns of each looked-up tuple followed by the columns of
+    /// each table and their multiplicities
+    fn fraction_columns(
+        &self,
+    ) -> impl Iterator<Item = (usize, &[LookupExpr<Fp, Fq>], Option<usize>)> {
+        let lookups = self.lookups.iter().map(|values| (&**values, None));
+        let tables = (self.tables.iter()).map(|(values, m)| (&**values, Some(*m)));
+        (self.first_column..)
+            .zip(lookups.chain(tables))
+            .map(|(column, (values, m))| (column, values, m))
+    }
+}
+
+impl<Fp: GpuFftField<FftField = Fp> + FftField, Fq: StarkExtensionOf<Fp>> LogUp<Fp, Fq> {
+    /// Returns the fraction `multiplicity / (α - (v_0 + β*v_1 + ...))` of a
+    /// tuple. Used to compute the final sum of lookups into tables that
+    /// aren't in the trace.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tuple is empty
+    pub fn fraction(&self, challenges: &Challenges<Fq>, values: &[Fq], multiplicity: Fq) -> Fq {
+        let compressed = compress_values(challenges, self.beta, values);
+        multiplicity / (challenges[self.alpha] - compressed)
+    }
+
+    /// Returns the constraints that check the fraction columns and the running
+    /// sum. The running sum includes the fractions of its own row.
+    pub fn constraints(&self, trace_len: usize) -> Vec<Constraint<FieldVariant<Fp, Fq>>> {
+        use AlgebraicItem::*;
+        let one = Constant(FieldVariant::Fp(Fp::one()));
+        let every_row = every_row(trace_len);
+
+        let mut constraints = Vec::new();
+        let delta = |offset: isize| {
+            let zero = Expr::from(Constant(FieldVariant::Fp(Fp::zero())));
+            (self.fraction_columns()).fold(zero, |delta, (column, _, multiplicity)| {
+                match multiplicity {
+                    None => delta + column.offset(offset),
+                    Some(_) => delta - column.offset(offset),
+                }
+            })
+        };
+        let (delta_curr, delta_next) = (delta(0), delta(1));
+
+        for (column, values, multiplicity) in self.fraction_columns() {
+            let denominator = self.alpha.challenge() - compress(self.beta, values);
+            let numerator = multiplicity.map_or_else(|| one.into(), |m| m.curr());
+            constraints.push((column.curr() * denominator - numerator) * &every_row);
+        }
+        let running_sum = self.running_sum_column();
+        constraints.extend([
+            (running_sum.curr() - delta_curr) * first_row(),
+            (running_sum.next() - running_sum.curr() - delta_next)
+                * every_row_except_last(trace_len),
+            (running_sum.curr() - self.final_sum_hint.hint()) * last_row(trace_len),
+        ]);
+        constraints.into_iter().map(Constraint::new).collect()
+    }
+
+    /// Builds the columns of the argument. Trace columns after the base
+    /// columns are read from the extension trace `segments` built so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a looked-up or table expression references a hint
+    pub fn build_columns(
+        &self,
+        trace: &impl Trace<Fp = Fp, Fq = Fq>,
+        challenges: &Challenges<Fq>,
+        segments: &[Matrix<Fq>],
+    ) -> Matrix<Fq> {
+        let rows = RowEvaluator::new(trace, challenges, segments);
+        let trace_len = trace.len();
+        let mut columns = Vec::new();
+        let mut running_sum = vec![Fq::zero(); trace_len];
+        for (_, values, multiplicity) in self.fraction_columns() {
+            let denominator = self.alpha.challenge() - compress(self.beta, values);
+            let mut fractions = (0..trace_len)
+                .map(|row| rows.eval(row, &denominator))
+                .collect::<Vec<Fq>>();
+            batch_inversion(&mut fractions);
+            if let Some(m) = multiplicity {
+                for (row, fraction) in fractions.iter_mut().enumerate() {
+                    *fraction *= rows.eval(row, &m.curr());
+                }
+            }
+            for (sum, fraction) in running_sum.iter_mut().zip(&fractions) {
+                match multiplicity {
+                    None => *sum += fraction,
+                    Some(_) => *sum -= fraction,
+                }
+            }
+            columns.push(fractions.to_vec_in(GpuAllocator));
+        }
+        let mut acc = Fq::zero();
+        for sum in &mut running_sum {
+            acc += *sum;
+            *sum = acc;
+        }
+        columns.push(running_sum.to_vec_in(GpuAllocator));
+        Matrix::new(columns)
+    }
+}
@@ -1,11 +1,18 @@
 use crate::hash::Digest;
 use crate::hash::ElementHashFn;
 use crate::hash::HashFn;
+use crate::utils::deserialize_vec;
 use crate::Matrix;
+use alloc::string::String;
 use alloc::vec::Vec;
 use ark_ff::Field;
 use ark_serialize::CanonicalDeserialize;
 use ark_serialize::CanonicalSerialize;
+use ark_serialize::Compress;
+use ark_serialize::Read;
+use ark_serialize::SerializationError;
+use ark_serialize::Validate;
+use rand::RngCore;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use snafu::Snafu;
@@ -27,9 +34,40 @@ pub enum Error {
     InvalidProof,
 }
 
+/// Commitment to a merkle tree e.g. the root or a merkle cap
+pub trait Commitment:
+    Debug + Default + Clone + Eq + Send + Sync + CanonicalSerialize + CanonicalDeserialize
+{
+    type Digest: Digest;
+
+    /// Returns the digests that make up the commitment. These are absorbed
+    /// into the public coin in order.
+    fn digests(&self) -> &[Self::Digest];
+}
+
+impl<D: Digest> Commitment for D {
+    type Digest = D;
+
+    fn digests(&self) -> &[D] {
+        core::slice::from_ref(self)
+    }
+}
+
+/// The `2^k` nodes at height `k` of a merkle tree (ordered left to right)
+#[derive(Debug, Default, Clone, PartialEq, Eq, CanonicalDeserialize, CanonicalSerialize)]
+pub struct MerkleCap<D: Digest>(pub Vec<D>);
+
+impl<D: Digest> Commitment for MerkleCap<D> {
+    type Digest = D;
+
+    fn digests(&self) -> &[D] {
+        &self.0
+    }
+}
+
 pub trait MerkleTree: Sized + Send + Sync + Clone {
     type Proof: CanonicalSerialize + CanonicalDeserialize + Clone + Send + Sync;
-    type Root: Digest;
+    type Root: Commitment;
 
     /// Returns the root of the merkle tree
     fn root(&self) -> Self::Root;
@@ -50,6 +88,29 @@ pub trait MerkleTree: Sized + Send + Sync + Clone {
 
     /// Returns the number of security bits
     fn security_level_bits() -> u32;
+
+    /// Returns a name that identifies the tree and its hash function e.g. in
+    /// serialized proofs
+    fn name() -> String;
+
+    /// Deserializes a commitment. The number of digests is bounded before
+    /// anything is allocated.
+    fn deserialize_root(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self::Root, SerializationError>;
+
+    /// Deserializes a proof of at most `num_indices` leaves in a tree with a
+    /// height of at most `max_height`. Lengths are bounded before anything is
+    /// allocated.
+    fn deserialize_proof(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+        num_indices: usize,
+        max_height: u32,
+    ) -> Result<Self::Proof, SerializationError>;
 }
 
 // TODO: all these merkle tree abstractions are way out of control. need to
@@ -63,6 +124,9 @@ pub trait MerkleTreeConfig: Send + Sync + Sized + 'static {
     fn hash_nodes(depth: u32, n0: &Self::Digest, n1: &Self::Digest) -> Self::Digest;
 
     fn security_level_bits() -> u32;
+
+    /// Returns a name that identifies the tree's hash functions
+    fn name() -> String;
 }
 
 /// Merkle View contains information needed to verify multiple Merkle paths.
@@ -80,6 +144,38 @@ pub struct MerkleView<
     pub height: u32,
 }
 
+impl<N, L> MerkleView<N, L>
+where
+    N: CanonicalDeserialize + CanonicalSerialize + Clone,
+    L: CanonicalDeserialize + CanonicalSerialize + Clone,
+{
+    /// Deserializes a view of at most `num_indices` leaves in a tree with a
+    /// height of at most `max_height`
+    pub fn deserialize_bounded(
+        mut reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+        num_indices: usize,
+        max_height: u32,
+    ) -> Result<Self, SerializationError> {
+        // each path has at most `max_height - 1` nodes above the leaves
+        let max_nodes = num_indices.saturating_mul(max_height as usize);
+        let nodes = deserialize_vec(&mut reader, compress, validate, max_nodes)?;
+        let initial_leaves = deserialize_vec(&mut reader, compress, validate, num_indices)?;
+        let sibling_leaves = deserialize_vec(&mut reader, compress, validate, num_indices)?;
+        let height = u32::deserialize_with_mode(&mut reader, compress, validate)?;
+        if height > max_height {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(Self {
+            nodes,
+            initial_leaves,
+            sibling_leaves,
+            height,
+        })
+    }
+}
+
 /// Merkle tree implemented as a full power-of-two arity tree.
 ///
 /// ```text
@@ -136,17 +232,25 @@ impl<C: MerkleTreeConfig> MerkleTreeImpl<C> {
     fn height(&self) -> u32 {
         self.leaves.len().ilog2()
     }
-}
-
-impl<C: MerkleTreeConfig> MerkleTree for MerkleTreeImpl<C> {
-    type Proof = MerkleView<C::Digest, C::Leaf>;
-    type Root = C::Digest;
 
-    fn root(&self) -> C::Digest {
-        self.nodes[1].clone()
+    /// Returns the `2^cap_height` nodes at height `cap_height` of the tree
+    pub fn cap(&self, cap_height: u32) -> Vec<C::Digest> {
+        let cap_start = 1 << cap_height;
+        self.nodes[cap_start..cap_start * 2].to_vec()
     }
 
-    fn prove(&self, indices: &[usize]) -> Result<MerkleView<C::Digest, C::Leaf>, Error> {
+    /// Generates a merkle proof with paths that end at the merkle cap of height
+    /// `cap_height`. A `cap_height` of zero gives paths that end at the root.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the leaf index is out of bounds.
+    pub fn prove_with_cap(
+        &self,
+        indices: &[usize],
+        cap_height: u32,
+    ) -> Result<MerkleView<C::Digest, C::Leaf>, Error> {
+        assert!(cap_height < self.height());
         let num_leaves = self.leaves.len();
         for &i in indices {
             if i >= num_leaves {
@@ -181,12 +285,16 @@ impl<C: MerkleTreeConfig> MerkleTree for MerkleTreeImpl<C> {
         }
 
         // handle internal nodes
+        let cap_start = 1 << cap_height;
         let mut nodes = Vec::new();
         while let Some(index) = node_queue.pop_front() {
-            if index > 2 {
-                node_queue.push_back(index >> 1);
+            // nodes in the cap are known by the verifier
+            if index < cap_start * 2 {
+                continue;
             }
 
+            node_queue.push_back(index >> 1);
+
             if let Some(next_index) = node_queue.front() {
                 let are_siblings = index ^ 1 == *next_index;
                 if are_siblings {
@@ -206,12 +314,22 @@ impl<C: MerkleTreeConfig> MerkleTree for MerkleTreeImpl<C> {
         })
     }
 
-    fn verify(
-        root: &C::Digest,
+    /// Verifies a merkle proof against a merkle cap. The height of the cap is
+    /// determined by the number of nodes in the cap.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the proof fails verification.
+    pub fn verify_with_cap(
+        cap: &[C::Digest],
         proof: MerkleView<C::Digest, C::Leaf>,
         indices: &[usize],
     ) -> Result<(), Error> {
         let height = proof.height;
+        if height >= usize::BITS || !cap.len().is_power_of_two() || cap.len().ilog2() >= height {
+            return Err(Error::InvalidProof);
+        }
+
         let num_leaves = 1 << height;
         for &i in indices {
             if i >= num_leaves {
@@ -222,6 +340,9 @@ impl<C: MerkleTreeConfig> MerkleTree for MerkleTreeImpl<C> {
         let mut indices = indices.to_vec();
         indices.sort_unstable();
         indices.dedup();
+        if indices.len() != proof.initial_leaves.len() {
+            return Err(Error::InvalidProof);
+        }
 
         // handle leaves and specify the internal node indices
         let mut node_queue = VecDeque::new();
@@ -240,7 +361,7 @@ impl<C: MerkleTreeConfig> MerkleTree for MerkleTreeImpl<C> {
                 }
             }
 
-            let sibling = siblings.pop_front().unwrap();
+            let sibling = siblings.pop_front().ok_or(Error::InvalidProof)?;
             let running_hash = if index % 2 == 0 {
                 C::hash_leaves(height - 1, &leaf, &sibling)
             } else {
@@ -248,23 +369,24 @@ impl<C: MerkleTreeConfig> MerkleTree for MerkleTreeImpl<C> {
             };
             node_queue.push_back((node_index, running_hash));
         }
-        assert!(siblings.is_empty());
+        if !siblings.is_empty() {
+            return Err(Error::InvalidProof);
+        }
 
         // handle internal nodes
+        let cap_start = cap.len();
         let mut nodes = VecDeque::from_iter(proof.nodes);
         while let Some((index, hash)) = node_queue.pop_front() {
-            let depth = index.ilog2();
-
-            if depth == 0 {
-                assert!(node_queue.is_empty());
-                // compare against the root
-                return if *root == hash {
-                    Ok(())
-                } else {
-                    Err(Error::InvalidProof)
-                };
+            if index < cap_start * 2 {
+                // compare against the cap
+                if cap[index - cap_start] != hash {
+                    return Err(Error::InvalidProof);
+                }
+                continue;
             }
 
+            let depth = index.ilog2();
+
             if let Some((next_index, next_hash)) = node_queue.front() {
                 let are_siblings = index ^ 1 == *next_index;
                 if are_siblings {
@@ -275,7 +397,7 @@ impl<C: MerkleTreeConfig> MerkleTree for MerkleTreeImpl<C> {
                 }
             }
 
-            let sibling = nodes.pop_front().unwrap();
+            let sibling = nodes.pop_front().ok_or(Error::InvalidProof)?;
             let running_hash = if index % 2 == 0 {
                 C::hash_nodes(depth - 1, &hash, &sibling)
             } else {
@@ -284,12 +406,59 @@ impl<C: MerkleTreeConfig> MerkleTree for MerkleTreeImpl<C> {
             node_queue.push_back((index >> 1, running_hash));
         }
 
+        if !nodes.is_empty() {
+            return Err(Error::InvalidProof);
+        }
+
         Ok(())
     }
+}
+
+impl<C: MerkleTreeConfig> MerkleTree for MerkleTreeImpl<C> {
+    type Proof = MerkleView<C::Digest, C::Leaf>;
+    type Root = C::Digest;
+
+    fn root(&self) -> C::Digest {
+        self.nodes[1].clone()
+    }
+
+    fn prove(&self, indices: &[usize]) -> Result<MerkleView<C::Digest, C::Leaf>, Error> {
+        self.prove_with_cap(indices, 0)
+    }
+
+    fn verify(
+        root: &C::Digest,
+        proof: MerkleView<C::Digest, C::Leaf>,
+        indices: &[usize],
+    ) -> Result<(), Error> {
+        Self::verify_with_cap(core::slice::from_ref(root), proof, indices)
+    }
 
     fn security_level_bits() -> u32 {
         C::security_level_bits()
     }
+
+    fn name() -> String {
+        C::name()
+    }
+
+    fn deserialize_root(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<C::Digest, SerializationError> {
+        C::Digest::deserialize_with_mode(reader, compress, validate)
+    }
+
+    fn deserialize_proof(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+        num_indices: usize,
+        max_height: u32,
+    ) -> Result<Self::Proof, SerializationError> {
+        MerkleView::deserialize_bounded(reader, compress, validate, num_indices, max_height)
+    }
 }
 
 /// Merkle tree that supports proving/verifying rows of a matrix
@@ -299,6 +468,10 @@ impl<C: MerkleTreeConfig> MerkleTree for MerkleTreeImpl<C> {
 pub trait MatrixMerkleTree<T>: MerkleTree + Sized {
     fn from_matrix(m: &Matrix<T>) -> Self;
 
+    /// Builds a tree with each leaf salted with randomness from `rng`. Salting
+    /// ensures the leaf hashes don't reveal the contents of unopened rows.
+    fn from_matrix_salted(m: &Matrix<T>, rng: &mut impl RngCore) -> Self;
+
     fn prove_rows(&self, row_ids: &[usize]) -> Result<Self::Proof, Error> {
         self.prove(row_ids)
     }
@@ -311,29 +484,74 @@ pub trait MatrixMerkleTree<T>: MerkleTree + Sized {
     ) -> Result<(), Error>;
 }
 
+/// Proof that rows belong to a [`MatrixMerkleTreeImpl`]
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalDeserialize, CanonicalSerialize)]
+pub struct MatrixMerkleProof<D: Digest> {
+    pub view: MerkleView<D, D>,
+    /// Salts of the proven rows (sorted by row index without duplicates).
+    /// Empty if the leaves of the tree are not salted.
+    pub salts: Vec<D>,
+}
+
+impl<D: Digest> MatrixMerkleProof<D> {
+    /// Deserializes a proof of at most `num_indices` rows in a tree with a
+    /// height of at most `max_height`
+    pub fn deserialize_bounded(
+        mut reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+        num_indices: usize,
+        max_height: u32,
+    ) -> Result<Self, SerializationError> {
+        let view = MerkleView::deserialize_bounded(
+            &mut reader,
+            compress,
+            validate,
+            num_indices,
+            max_height,
+        )?;
+        let salts = deserialize_vec(&mut reader, compress, validate, num_indices)?;
+        Ok(Self { view, salts })
+    }
+}
+
 pub struct MatrixMerkleTreeImpl<H: HashFn> {
     merkle_tree: MerkleTreeImpl<HashedLeafConfig<H>>,
+    salts: Option<Vec<H::Digest>>,
 }
 
 impl<H: HashFn> Clone for MatrixMerkleTreeImpl<H> {
     fn clone(&self) -> Self {
         Self {
             merkle_tree: self.merkle_tree.clone(),
+            salts: self.salts.clone(),
         }
     }
 }
 
 impl<H: HashFn> MatrixMerkleTreeImpl<H> {
-    fn new(leaves: Vec<H::Digest>) -> Result<Self, Error> {
+    fn new(leaves: Vec<H::Digest>, salts: Option<Vec<H::Digest>>) -> Result<Self, Error> {
         assert!(leaves.len().is_power_of_two());
         Ok(Self {
             merkle_tree: MerkleTreeImpl::new(leaves)?,
+            salts,
+        })
+    }
+
+    /// Returns the salts of the leaves at the given indices (sorted by index
+    /// without duplicates). Empty if the leaves are not salted.
+    fn salts(&self, indices: &[usize]) -> Vec<H::Digest> {
+        self.salts.as_ref().map_or_else(Vec::new, |salts| {
+            let mut indices = indices.to_vec();
+            indices.sort_unstable();
+            indices.dedup();
+            indices.into_iter().map(|i| salts[i].clone()).collect()
         })
     }
 }
 
 impl<H: HashFn> MerkleTree for MatrixMerkleTreeImpl<H> {
-    type Proof = MerkleView<H::Digest, H::Digest>;
+    type Proof = MatrixMerkleProof<H::Digest>;
     type Root = H::Digest;
 
     fn root(&self) -> Self::Root {
@@ -341,23 +559,61 @@ impl<H: HashFn> MerkleTree for MatrixMerkleTreeImpl<H> {
     }
 
     fn prove(&self, indices: &[usize]) -> Result<Self::Proof, Error> {
-        self.merkle_tree.prove(indices)
+        let view = self.merkle_tree.prove(indices)?;
+        let salts = self.salts(indices);
+        Ok(MatrixMerkleProof { view, salts })
     }
 
     fn verify(root: &Self::Root, proof: Self::Proof, indices: &[usize]) -> Result<(), Error> {
-        MerkleTreeImpl::<HashedLeafConfig<H>>::verify(root, proof, indices)
+        MerkleTreeImpl::<HashedLeafConfig<H>>::verify(root, proof.view, indices)
     }
 
     fn security_level_bits() -> u32 {
         H::COLLISION_RESISTANCE
     }
+
+    fn name() -> String {
+        H::name()
+    }
+
+    fn deserialize_root(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<H::Digest, SerializationError> {
+        H::Digest::deserialize_with_mode(reader, compress, validate)
+    }
+
+    fn deserialize_proof(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+        num_indices: usize,
+        max_height: u32,
+    ) -> Result<Self::Proof, SerializationError> {
+        MatrixMerkleProof::deserialize_bounded(reader, compress, validate, num_indices, max_height)
+    }
 }
 
 impl<F: Field, H: ElementHashFn<F> + Send + Sync + 'static> MatrixMerkleTree<F>
     for MatrixMerkleTreeImpl<H>
 {
     fn from_matrix(m: &Matrix<F>) -> Self {
-        Self::new(hash_rows::<F, H>(m)).unwrap()
+        Self::new(hash_rows::<F, H>(m), None).unwrap()
+    }
+
+    fn from_matrix_salted(m: &Matrix<F>, rng: &mut impl RngCore) -> Self {
+        let salts = (0..m.num_rows())
+            .map(|_| {
+                let mut bytes = [0; 32];
+                rng.fill_bytes(&mut bytes);
+                H::hash(bytes)
+            })
+            .collect::<Vec<H::Digest>>();
+        let leaves = zip(hash_rows::<F, H>(m), &salts)
+            .map(|(row_hash, salt)| H::merge(&row_hash, salt))
+            .collect();
+        Self::new(leaves, Some(salts)).unwrap()
     }
 
     fn verify_rows(
@@ -366,17 +622,110 @@ impl<F: Field, H: ElementHashFn<F> + Send + Sync + 'static> MatrixMerkleTree<F>
         rows: &[impl AsRef<[F]>],
         proof: Self::Proof,
     ) -> Result<(), Error> {
-        // remove duplicates and sort
-        let mut instances = zip(row_ids, rows).collect::<Vec<_>>();
-        instances.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
-        instances.dedup_by(|(a, _), (b, _)| a == b);
+        let (indices, initial_leaves) = row_leaves::<F, H>(row_ids, rows, &proof.salts)?;
+        if proof.view.initial_leaves == initial_leaves {
+            Self::verify(root, proof, &indices)
+        } else {
+            Err(Error::InvalidProof)
+        }
+    }
+}
 
-        let (indices, rows): (Vec<_>, Vec<_>) = instances.into_iter().unzip();
-        let initial_leaves = rows
-            .iter()
-            .map(|r| H::hash_elements(r.as_ref().iter().copied()))
-            .collect::<Vec<_>>();
-        if proof.initial_leaves == initial_leaves {
+/// Matrix merkle tree that commits to a [`MerkleCap`] of height `CAP_HEIGHT`
+/// rather than the root.
+///
+/// Paths in proofs end at the cap which makes them `CAP_HEIGHT` nodes shorter
+/// than paths for [`MatrixMerkleTreeImpl`]. The cap height is reduced for trees
+/// that are too small to have a cap of `CAP_HEIGHT`.
+pub struct MatrixMerkleCapTreeImpl<H: HashFn, const CAP_HEIGHT: u32>(MatrixMerkleTreeImpl<H>);
+
+impl<H: HashFn, const CAP_HEIGHT: u32> MatrixMerkleCapTreeImpl<H, CAP_HEIGHT> {
+    /// Returns the height of the cap for a tree of the given height
+    fn cap_height(height: u32) -> u32 {
+        CAP_HEIGHT.min(height - 1)
+    }
+}
+
+impl<H: HashFn, const CAP_HEIGHT: u32> Clone for MatrixMerkleCapTreeImpl<H, CAP_HEIGHT> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<H: HashFn, const CAP_HEIGHT: u32> MerkleTree for MatrixMerkleCapTreeImpl<H, CAP_HEIGHT> {
+    type Proof = MatrixMerkleProof<H::Digest>;
+    type Root = MerkleCap<H::Digest>;
+
+    fn root(&self) -> Self::Root {
+        let merkle_tree = &self.0.merkle_tree;
+        MerkleCap(merkle_tree.cap(Self::cap_height(merkle_tree.height())))
+    }
+
+    fn prove(&self, indices: &[usize]) -> Result<Self::Proof, Error> {
+        let merkle_tree = &self.0.merkle_tree;
+        let cap_height = Self::cap_height(merkle_tree.height());
+        let view = merkle_tree.prove_with_cap(indices, cap_height)?;
+        let salts = self.0.salts(indices);
+        Ok(MatrixMerkleProof { view, salts })
+    }
+
+    fn verify(root: &Self::Root, proof: Self::Proof, indices: &[usize]) -> Result<(), Error> {
+        let height = proof.view.height;
+        if height == 0 || root.0.len() != 1 << Self::cap_height(height) {
+            return Err(Error::InvalidProof);
+        }
+        MerkleTreeImpl::<HashedLeafConfig<H>>::verify_with_cap(&root.0, proof.view, indices)
+    }
+
+    fn security_level_bits() -> u32 {
+        H::COLLISION_RESISTANCE
+    }
+
+    fn name() -> String {
+        format!("{}_cap{CAP_HEIGHT}", H::name())
+    }
+
+    fn deserialize_root(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self::Root, SerializationError> {
+        let max_len = 1usize.checked_shl(CAP_HEIGHT).unwrap_or(usize::MAX);
+        Ok(MerkleCap(deserialize_vec(
+            reader, compress, validate, max_len,
+        )?))
+    }
+
+    fn deserialize_proof(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+        num_indices: usize,
+        max_height: u32,
+    ) -> Result<Self::Proof, SerializationError> {
+        MatrixMerkleProof::deserialize_bounded(reader, compress, validate, num_indices, max_height)
+    }
+}
+
+impl<F: Field, H: ElementHashFn<F> + Send + Sync + 'static, const CAP_HEIGHT: u32>
+    MatrixMerkleTree<F> for MatrixMerkleCapTreeImpl<H, CAP_HEIGHT>
+{
+    fn from_matrix(m: &Matrix<F>) -> Self {
+        Self(MatrixMerkleTreeImpl::from_matrix(m))
+    }
+
+    fn from_matrix_salted(m: &Matrix<F>, rng: &mut impl RngCore) -> Self {
+        Self(MatrixMerkleTreeImpl::from_matrix_salted(m, rng))
+    }
+
+    fn verify_rows(
+        root: &Self::Root,
+        row_ids: &[usize],
+        rows: &[impl AsRef<[F]>],
+        proof: Self::Proof,
+    ) -> Result<(), Error> {
+        let (indices, initial_leaves) = row_leaves::<F, H>(row_ids, rows, &proof.salts)?;
+        if proof.view.initial_leaves == initial_leaves {
             Self::verify(root, proof, &indices)
         } else {
             Err(Error::InvalidProof)
@@ -384,6 +733,197 @@ impl<F: Field, H: ElementHashFn<F> + Send + Sync + 'static> MatrixMerkleTree<F>
     }
 }
 
+/// Proof that rows belong to a [`CompactMatrixMerkleTreeImpl`].
+///
+/// Unlike [`MatrixMerkleProof`] the leaves of the proven rows are omitted by
+/// [`MatrixMerkleTree::prove_rows`] since the verifier recomputes them from the
+/// rows it holds. As with [`MerkleView`] authentication nodes shared between
+/// the paths of multiple rows are only included once. Proofs of different
+/// commitments are not batched together.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalDeserialize, CanonicalSerialize)]
+pub struct CompactMerkleProof<D: Digest> {
+    /// Leaves of the proven indices (sorted by index without duplicates).
+    /// Empty if the proof was generated with [`MatrixMerkleTree::prove_rows`].
+    pub leaves: Vec<D>,
+    /// Sibling leaves followed by sibling nodes that can't be recomputed from
+    /// the proven leaves. Ordered from the bottom of the tree to the top.
+    pub nodes: Vec<D>,
+    /// Salts of the proven rows (sorted by row index without duplicates).
+    /// Empty if the leaves of the tree are not salted.
+    pub salts: Vec<D>,
+    pub height: u32,
+}
+
+/// Matrix merkle tree that generates [`CompactMerkleProof`]s. Commitments are
+/// identical to [`MatrixMerkleTreeImpl`] but proofs are smaller.
+pub struct CompactMatrixMerkleTreeImpl<H: HashFn>(MatrixMerkleTreeImpl<H>);
+
+impl<H: HashFn> Clone for CompactMatrixMerkleTreeImpl<H> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<H: HashFn> MerkleTree for CompactMatrixMerkleTreeImpl<H> {
+    type Proof = CompactMerkleProof<H::Digest>;
+    type Root = H::Digest;
+
+    fn root(&self) -> Self::Root {
+        self.0.root()
+    }
+
+    fn prove(&self, indices: &[usize]) -> Result<Self::Proof, Error> {
+        let MatrixMerkleProof { view, salts } = self.0.prove(indices)?;
+        let MerkleView {
+            nodes,
+            initial_leaves,
+            sibling_leaves,
+            height,
+        } = view;
+        Ok(CompactMerkleProof {
+            leaves: initial_leaves,
+            nodes: [sibling_leaves, nodes].concat(),
+            salts,
+            height,
+        })
+    }
+
+    fn verify(root: &Self::Root, proof: Self::Proof, indices: &[usize]) -> Result<(), Error> {
+        let CompactMerkleProof {
+            leaves,
+            mut nodes,
+            height,
+            ..
+        } = proof;
+
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+
+        // the number of leaves whose sibling leaf is not proven
+        let mut num_sibling_leaves = 0;
+        let mut i = 0;
+        while i < indices.len() {
+            let are_siblings = indices.get(i + 1) == Some(&(indices[i] ^ 1));
+            i += if are_siblings { 2 } else { 1 };
+            num_sibling_leaves += usize::from(!are_siblings);
+        }
+
+        if leaves.len() != indices.len() || nodes.len() < num_sibling_leaves {
+            return Err(Error::InvalidProof);
+        }
+
+        let internal_nodes = nodes.split_off(num_sibling_leaves);
+        let view = MerkleView {
+            nodes: internal_nodes,
+            initial_leaves: leaves,
+            sibling_leaves: nodes,
+            height,
+        };
+        MerkleTreeImpl::<HashedLeafConfig<H>>::verify(root, view, &indices)
+    }
+
+    fn security_level_bits() -> u32 {
+        H::COLLISION_RESISTANCE
+    }
+
+    fn name() -> String {
+        format!("{}_compact", H::name())
+    }
+
+    fn deserialize_root(
+        reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<H::Digest, SerializationError> {
+        H::Digest::deserialize_with_mode(reader, compress, validate)
+    }
+
+    fn deserialize_proof(
+        mut reader: impl Read,
+        compress: Compress,
+        validate: Validate,
+        num_indices: usize,
+        max_height: u32,
+    ) -> Result<Self::Proof, SerializationError> {
+        // sibling leaves and nodes together make up at most `max_height`
+        // digests per path
+        let max_nodes = num_indices.saturating_mul(max_height as usize);
+        let leaves = deserialize_vec(&mut reader, compress, validate, num_indices)?;
+        let nodes = deserialize_vec(&mut reader, compress, validate, max_nodes)?;
+        let salts = deserialize_vec(&mut reader, compress, validate, num_indices)?;
+        let height = u32::deserialize_with_mode(&mut reader, compress, validate)?;
+        if height > max_height {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(CompactMerkleProof {
+            leaves,
+            nodes,
+            salts,
+            height,
+        })
+    }
+}
+
+impl<F: Field, H: ElementHashFn<F> + Send + Sync + 'static> MatrixMerkleTree<F>
+    for CompactMatrixMerkleTreeImpl<H>
+{
+    fn from_matrix(m: &Matrix<F>) -> Self {
+        Self(MatrixMerkleTreeImpl::from_matrix(m))
+    }
+
+    fn from_matrix_salted(m: &Matrix<F>, rng: &mut impl RngCore) -> Self {
+        Self(MatrixMerkleTreeImpl::from_matrix_salted(m, rng))
+    }
+
+    fn prove_rows(&self, row_ids: &[usize]) -> Result<Self::Proof, Error> {
+        let mut proof = self.prove(row_ids)?;
+        // the verifier recomputes the leaves from the rows
+        proof.leaves.clear();
+        Ok(proof)
+    }
+
+    fn verify_rows(
+        root: &Self::Root,
+        row_ids: &[usize],
+        rows: &[impl AsRef<[F]>],
+        proof: Self::Proof,
+    ) -> Result<(), Error> {
+        if !proof.leaves.is_empty() {
+            return Err(Error::InvalidProof);
+        }
+        let (indices, leaves) = row_leaves::<F, H>(row_ids, rows, &proof.salts)?;
+        Self::verify(root, CompactMerkleProof { leaves, ..proof }, &indices)
+    }
+}
+
+/// Returns the sorted and deduplicated row indices along with their leaves
+fn row_leaves<F: Field, H: ElementHashFn<F>>(
+    row_ids: &[usize],
+    rows: &[impl AsRef<[F]>],
+    salts: &[H::Digest],
+) -> Result<(Vec<usize>, Vec<H::Digest>), Error> {
+    // remove duplicates and sort
+    let mut instances = zip(row_ids, rows).collect::<Vec<_>>();
+    instances.sort_unstable_by_key(|(a, _)| *a);
+    instances.dedup_by(|(a, _), (b, _)| a == b);
+
+    let (indices, rows): (Vec<usize>, Vec<_>) = instances.into_iter().unzip();
+    let row_hashes = rows
+        .iter()
+        .map(|r| H::hash_elements(r.as_ref().iter().copied()));
+    let leaves = if salts.is_empty() {
+        row_hashes.collect()
+    } else if salts.len() == rows.len() {
+        zip(row_hashes, salts)
+            .map(|(row_hash, salt)| H::merge(&row_hash, salt))
+            .collect()
+    } else {
+        return Err(Error::InvalidProof);
+    };
+    Ok((indices, leaves))
+}
+
 pub struct HashedLeafConfig<H: HashFn>(PhantomData<H>);
 
 impl<H: HashFn> Clone for HashedLeafConfig<H> {
@@ -407,6 +947,10 @@ impl<H: HashFn> MerkleTreeConfig for HashedLeafConfig<H> {
     fn security_level_bits() -> u32 {
         H::COLLISION_RESISTANCE
     }
+
+    fn name() -> String {
+        H::name()
+    }
 }
 
 pub fn hash_rows<F: Field, H: ElementHashFn<F>>(matrix: &Matrix<F>) -> Vec<H::Digest> {
@@ -509,7 +1053,9 @@ pub fn build_merkle_nodes<C: MerkleTreeConfig>(leaves: &[C::Leaf]) -> Vec<C::Dig
 
 #[cfg(test)]
 mod tests {
+    use super::CompactMatrixMerkleTreeImpl;
     use super::Error;
+    use super::MatrixMerkleCapTreeImpl;
     use super::MatrixMerkleTree;
     use super::MatrixMerkleTreeImpl;
     use super::MerkleTree;
@@ -521,6 +1067,7 @@ mod tests {
     use crate::utils::SerdeOutput;
     use crate::Matrix;
     use ark_ff::MontFp as Fp;
+    use ark_serialize::CanonicalSerialize;
     use digest::Digest;
     use ministark_gpu::fields::p3618502788666131213697322783095070105623107215331596699973092056135872020481::ark::Fp;
     use sha2::Sha256;
@@ -537,6 +1084,30 @@ mod tests {
         MerkleTreeImpl::<UnhashedLeafConfig>::verify(&commitment, proof, &[i])
     }
 
+    #[test]
+    fn verify_fails_with_malformed_proof() -> Result<(), Error> {
+        let leaves = vec![1u32, 2, 3, 4, 5, 6, 7, 8];
+        let tree = MerkleTreeImpl::<UnhashedLeafConfig>::new(leaves)?;
+        let commitment = tree.root();
+        let i = 3;
+        let proof = tree.prove(&[i])?;
+
+        let mut missing_sibling = proof.clone();
+        missing_sibling.sibling_leaves.clear();
+        let mut extra_node = proof.clone();
+        extra_node.nodes.push(commitment.clone());
+        let mut large_height = proof;
+        large_height.height = 100;
+
+        for proof in [missing_sibling, extra_node, large_height] {
+            assert!(matches!(
+                MerkleTreeImpl::<UnhashedLeafConfig>::verify(&commitment, proof, &[i]),
+                Err(Error::InvalidProof)
+            ));
+        }
+        Ok(())
+    }
+
     #[test]
     fn prove_all_leaves() -> Result<(), Error> {
         let column: &[Fp] = &[Fp!("1"), Fp!("2"), Fp!("3"), Fp!("4")];
@@ -551,6 +1122,166 @@ mod tests {
         MatrixMerkleTreeImpl::<Sha256HashFn>::verify_rows(&commitment, &row_ids, &rows, proof)
     }
 
+    #[test]
+    fn prove_salted_rows() -> Result<(), Error> {
+        let column: &[Fp] = &[Fp!("1"), Fp!("2"), Fp!("3"), Fp!("4")];
+        let matrix = Matrix::new(vec![column.to_vec_in(GpuAllocator)]);
+        let tree = MatrixMerkleTreeImpl::<Sha256HashFn>::from_matrix_salted(&matrix, &mut rng());
+        let commitment = tree.root();
+        let row_ids = [3, 1];
+        let rows = row_ids.map(|i| [column[i]]);
+
+        let proof = MatrixMerkleTree::<Fp>::prove_rows(&tree, &row_ids)?;
+
+        assert_eq!(2, proof.salts.len());
+        MatrixMerkleTreeImpl::<Sha256HashFn>::verify_rows(&commitment, &row_ids, &rows, proof)
+    }
+
+    #[test]
+    fn salted_rows_fail_without_salts() -> Result<(), Error> {
+        let column: &[Fp] = &[Fp!("1"), Fp!("2"), Fp!("3"), Fp!("4")];
+        let matrix = Matrix::new(vec![column.to_vec_in(GpuAllocator)]);
+        let tree = MatrixMerkleTreeImpl::<Sha256HashFn>::from_matrix_salted(&matrix, &mut rng());
+        let commitment = tree.root();
+        let row_ids = [2];
+        let rows = row_ids.map(|i| [column[i]]);
+        let mut proof = MatrixMerkleTree::<Fp>::prove_rows(&tree, &row_ids)?;
+
+        proof.salts.clear();
+
+        assert!(matches!(
+            MatrixMerkleTreeImpl::<Sha256HashFn>::verify_rows(&commitment, &row_ids, &rows, proof),
+            Err(Error::InvalidProof)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn prove_compact_rows() -> Result<(), Error> {
+        let column = (0..64).map(Fp::from).collect::<Vec<Fp>>();
+        let matrix = Matrix::new(vec![column.to_vec_in(GpuAllocator)]);
+        let tree = CompactMatrixMerkleTreeImpl::<Sha256HashFn>::from_matrix(&matrix);
+        let commitment = tree.root();
+        let row_ids = [9, 8, 33, 60, 9, 2];
+        let rows = row_ids.map(|i| [column[i]]);
+
+        let proof = MatrixMerkleTree::<Fp>::prove_rows(&tree, &row_ids)?;
+
+        assert!(proof.leaves.is_empty());
+        CompactMatrixMerkleTreeImpl::<Sha256HashFn>::verify_rows(
+            &commitment,
+            &row_ids,
+            &rows,
+            proof,
+        )
+    }
+
+    #[test]
+    fn compact_proof_is_smaller() -> Result<(), Error> {
+        let column = (0..64).map(Fp::from).collect::<Vec<Fp>>();
+        let matrix = Matrix::new(vec![column.to_vec_in(GpuAllocator)]);
+        let tree = MatrixMerkleTreeImpl::<Sha256HashFn>::from_matrix(&matrix);
+        let compact_tree = CompactMatrixMerkleTreeImpl::<Sha256HashFn>::from_matrix(&matrix);
+        let row_ids = [1, 5, 17, 40];
+
+        let proof = MatrixMerkleTree::<Fp>::prove_rows(&tree, &row_ids)?;
+        let compact_proof = MatrixMerkleTree::<Fp>::prove_rows(&compact_tree, &row_ids)?;
+
+        assert_eq!(tree.root(), compact_tree.root());
+        assert!(compact_proof.compressed_size() < proof.compressed_size());
+        Ok(())
+    }
+
+    #[test]
+    fn compact_rows_fail_with_wrong_row() -> Result<(), Error> {
+        let column = (0..16).map(Fp::from).collect::<Vec<Fp>>();
+        let matrix = Matrix::new(vec![column.to_vec_in(GpuAllocator)]);
+        let tree = CompactMatrixMerkleTreeImpl::<Sha256HashFn>::from_matrix(&matrix);
+        let commitment = tree.root();
+        let row_ids = [3, 12];
+        let rows = [[column[3]], [column[11]]];
+        let proof = MatrixMerkleTree::<Fp>::prove_rows(&tree, &row_ids)?;
+
+        let res = CompactMatrixMerkleTreeImpl::<Sha256HashFn>::verify_rows(
+            &commitment,
+            &row_ids,
+            &rows,
+            proof,
+        );
+
+        assert!(matches!(res, Err(Error::InvalidProof)));
+        Ok(())
+    }
+
+    #[test]
+    fn prove_rows_with_cap() -> Result<(), Error> {
+        let column = (0..64).map(Fp::from).collect::<Vec<Fp>>();
+        let matrix = Matrix::new(vec![column.to_vec_in(GpuAllocator)]);
+        let tree = MatrixMerkleCapTreeImpl::<Sha256HashFn, 3>::from_matrix(&matrix);
+        let cap = tree.root();
+        let row_ids = [9, 8, 33, 60, 2];
+        let rows = row_ids.map(|i| [column[i]]);
+
+        let proof = MatrixMerkleTree::<Fp>::prove_rows(&tree, &row_ids)?;
+
+        assert_eq!(8, cap.0.len());
+        MatrixMerkleCapTreeImpl::<Sha256HashFn, 3>::verify_rows(&cap, &row_ids, &rows, proof)
+    }
+
+    #[test]
+    fn cap_paths_are_shorter() -> Result<(), Error> {
+        let column = (0..64).map(Fp::from).collect::<Vec<Fp>>();
+        let matrix = Matrix::new(vec![column.to_vec_in(GpuAllocator)]);
+        let tree = MatrixMerkleTreeImpl::<Sha256HashFn>::from_matrix(&matrix);
+        let cap_tree = MatrixMerkleCapTreeImpl::<Sha256HashFn, 2>::from_matrix(&matrix);
+        let row_ids = [7];
+
+        let proof = MatrixMerkleTree::<Fp>::prove_rows(&tree, &row_ids)?;
+        let cap_proof = MatrixMerkleTree::<Fp>::prove_rows(&cap_tree, &row_ids)?;
+
+        assert_eq!(proof.view.nodes.len() - 2, cap_proof.view.nodes.len());
+        Ok(())
+    }
+
+    #[test]
+    fn cap_height_is_reduced_for_small_trees() -> Result<(), Error> {
+        let column: &[Fp] = &[Fp!("1"), Fp!("2"), Fp!("3"), Fp!("4")];
+        let matrix = Matrix::new(vec![column.to_vec_in(GpuAllocator)]);
+        let tree = MatrixMerkleCapTreeImpl::<Sha256HashFn, 4>::from_matrix(&matrix);
+        let cap = tree.root();
+        let row_ids = [1];
+        let rows = row_ids.map(|i| [column[i]]);
+
+        let proof = MatrixMerkleTree::<Fp>::prove_rows(&tree, &row_ids)?;
+
+        assert_eq!(2, cap.0.len());
+        MatrixMerkleCapTreeImpl::<Sha256HashFn, 4>::verify_rows(&cap, &row_ids, &rows, proof)
+    }
+
+    #[test]
+    fn cap_rows_fail_with_wrong_cap() -> Result<(), Error> {
+        let column = (0..16).map(Fp::from).collect::<Vec<Fp>>();
+        let matrix = Matrix::new(vec![column.to_vec_in(GpuAllocator)]);
+        let tree = MatrixMerkleCapTreeImpl::<Sha256HashFn, 2>::from_matrix(&matrix);
+        let mut cap = tree.root();
+        let row_ids = [3, 12];
+        let rows = row_ids.map(|i| [column[i]]);
+        let proof = MatrixMerkleTree::<Fp>::prove_rows(&tree, &row_ids)?;
+
+        cap.0.swap(0, 3);
+
+        assert!(matches!(
+            MatrixMerkleCapTreeImpl::<Sha256HashFn, 2>::verify_rows(&cap, &row_ids, &rows, proof),
+            Err(Error::InvalidProof)
+        ));
+        Ok(())
+    }
+
+    fn rng() -> impl rand::RngCore {
+        use rand::SeedableRng;
+        rand_chacha::ChaCha20Rng::seed_from_u64(0)
+    }
+
     #[test]
     fn verify_hashed_leaves() -> Result<(), Error> {
         let leaves = [1u32, 2, 3, 4, 5, 6, 7, 8];
@@ -601,6 +1332,10 @@ mod tests {
         fn security_level_bits() -> u32 {
             Sha256HashFn::COLLISION_RESISTANCE
         }
+
+        fn name() -> String {
+            Sha256HashFn::name()
+        }
     }
 
     struct UnhashedLeafConfig;
@@ -622,5 +1357,9 @@ mod tests {
         fn security_level_bits() -> u32 {
             Sha256HashFn::COLLISION_RESISTANCE
         }
+
+        fn name() -> String {
+            Sha256HashFn::name()
+        }
     }
 }
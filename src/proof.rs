@@ -1,22 +1,47 @@
+use crate::air::extension_segment_widths;
+use crate::air::trace_arguments;
+use crate::air::AirConfig;
+use crate::fri::is_supported_folding_factor;
+use crate::fri::FriOptions;
 use crate::fri::FriProof;
+use crate::fri::LayerProof;
+use crate::fri::RemainderCommitment;
+use crate::hash::HashFn;
+use crate::merkle::MatrixMerkleTree;
 use crate::merkle::MerkleTree;
 use crate::random::PublicCoin;
+use crate::security::SecurityParams;
 use crate::stark::Stark;
 use crate::trace::Queries;
-use crate::utils::field_bits;
+use crate::utils::deserialize_array;
+use crate::utils::deserialize_vec;
+use crate::Air;
 use crate::ProofOptions;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use ark_ff::FftField;
+use ark_ff::Field;
 use ark_serialize::CanonicalDeserialize;
 use ark_serialize::CanonicalSerialize;
+use ark_serialize::Compress;
+use ark_serialize::Read;
+use ark_serialize::SerializationError;
 use ark_serialize::Valid;
+use ark_serialize::Validate;
+use core::iter::zip;
+use ministark_gpu::GpuField;
+use snafu::Snafu;
 
 /// A proof generated by a mini-stark prover
 pub struct Proof<C: Stark> {
     pub options: ProofOptions,
     pub trace_len: usize,
-    pub base_trace_commitment: C::Digest,
-    pub extension_trace_commitment: Option<C::Digest>,
-    pub composition_trace_commitment: C::Digest,
-    pub fri_proof: FriProof<C::Fq, C::Digest, C::MerkleTree>,
+    pub base_trace_commitment: <C::MerkleTree as MerkleTree>::Root,
+    /// Commitment to each extension trace segment
+    pub extension_trace_commitments: Vec<<C::MerkleTree as MerkleTree>::Root>,
+    pub composition_trace_commitment: <C::MerkleTree as MerkleTree>::Root,
+    pub fri_proof: FriProof<C::Fq, <C::MerkleTree as MerkleTree>::Root, C::MerkleTree>,
     pub pow_nonce: u64,
     pub trace_queries: Queries<C>,
     pub execution_trace_ood_evals: Vec<C::Fq>,
@@ -29,7 +54,7 @@ impl<C: Stark> Clone for Proof<C> {
             options: self.options,
             trace_len: self.trace_len,
             base_trace_commitment: self.base_trace_commitment.clone(),
-            extension_trace_commitment: self.extension_trace_commitment.clone(),
+            extension_trace_commitments: self.extension_trace_commitments.clone(),
             composition_trace_commitment: self.composition_trace_commitment.clone(),
             fri_proof: self.fri_proof.clone(),
             pow_nonce: self.pow_nonce,
@@ -50,7 +75,7 @@ impl<C: Stark> CanonicalSerialize for Proof<C> {
         self.trace_len.serialize_with_mode(&mut writer, compress)?;
         self.base_trace_commitment
             .serialize_with_mode(&mut writer, compress)?;
-        self.extension_trace_commitment
+        self.extension_trace_commitments
             .serialize_with_mode(&mut writer, compress)?;
         self.composition_trace_commitment
             .serialize_with_mode(&mut writer, compress)?;
@@ -69,7 +94,7 @@ impl<C: Stark> CanonicalSerialize for Proof<C> {
         self.options.serialized_size(compress)
             + self.trace_len.serialized_size(compress)
             + self.base_trace_commitment.serialized_size(compress)
-            + self.extension_trace_commitment.serialized_size(compress)
+            + self.extension_trace_commitments.serialized_size(compress)
             + self.composition_trace_commitment.serialized_size(compress)
             + self.fri_proof.serialized_size(compress)
             + self.pow_nonce.serialized_size(compress)
@@ -80,69 +105,566 @@ impl<C: Stark> CanonicalSerialize for Proof<C> {
 }
 
 impl<C: Stark> Valid for Proof<C> {
-    #[inline]
-    fn check(&self) -> Result<(), ark_serialize::SerializationError> {
-        Ok(())
+    fn check(&self) -> Result<(), SerializationError> {
+        self.validate().map_err(|_| SerializationError::InvalidData)
     }
 }
 
 impl<C: Stark> CanonicalDeserialize for Proof<C> {
-    fn deserialize_with_mode<R: ark_serialize::Read>(
+    fn deserialize_with_mode<R: Read>(
         mut reader: R,
-        compress: ark_serialize::Compress,
-        validate: ark_serialize::Validate,
-    ) -> Result<Self, ark_serialize::SerializationError> {
-        Ok(Self {
-            options: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
-            trace_len: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
-            base_trace_commitment: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
-            extension_trace_commitment: <_>::deserialize_with_mode(
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let options = ProofOptions::deserialize_with_mode(&mut reader, compress, validate)?;
+        let trace_len = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        // all lengths are bounded before any allocations are made
+        let shape = ProofShape::new::<C::AirConfig>(options, trace_len)
+            .map_err(|_| SerializationError::InvalidData)?;
+        let num_queries = shape.num_queries;
+        let proof = Self {
+            options,
+            trace_len,
+            base_trace_commitment: C::MerkleTree::deserialize_root(
+                &mut reader,
+                compress,
+                validate,
+            )?,
+            extension_trace_commitments: deserialize_roots::<C::MerkleTree>(
                 &mut reader,
                 compress,
                 validate,
+                shape.extension_segment_widths.len(),
             )?,
-            composition_trace_commitment: <_>::deserialize_with_mode(
+            composition_trace_commitment: C::MerkleTree::deserialize_root(
                 &mut reader,
                 compress,
                 validate,
             )?,
-            fri_proof: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
+            fri_proof: deserialize_fri_proof(&mut reader, compress, validate, &shape)?,
             pow_nonce: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
-            trace_queries: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
-            execution_trace_ood_evals: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
-            composition_trace_ood_evals: <_>::deserialize_with_mode(
+            trace_queries: Queries {
+                base_trace_values: deserialize_vec(
+                    &mut reader,
+                    compress,
+                    validate,
+                    num_queries * shape.num_base_columns,
+                )?,
+                extension_trace_values: deserialize_segment_values(
+                    &mut reader,
+                    compress,
+                    validate,
+                    &shape,
+                )?,
+                composition_trace_values: deserialize_vec(
+                    &mut reader,
+                    compress,
+                    validate,
+                    num_queries * shape.max_composition_columns,
+                )?,
+                base_trace_proof: C::MerkleTree::deserialize_proof(
+                    &mut reader,
+                    compress,
+                    validate,
+                    num_queries,
+                    shape.max_merkle_height,
+                )?,
+                extension_trace_proofs: deserialize_merkle_proofs::<C::MerkleTree>(
+                    &mut reader,
+                    compress,
+                    validate,
+                    shape.extension_segment_widths.len(),
+                    &shape,
+                )?,
+                composition_trace_proof: C::MerkleTree::deserialize_proof(
+                    &mut reader,
+                    compress,
+                    validate,
+                    num_queries,
+                    shape.max_merkle_height,
+                )?,
+            },
+            execution_trace_ood_evals: deserialize_vec(
+                &mut reader,
+                compress,
+                validate,
+                shape.num_trace_arguments,
+            )?,
+            composition_trace_ood_evals: deserialize_vec(
+                &mut reader,
+                compress,
+                validate,
+                shape.max_composition_columns,
+            )?,
+        };
+        if validate == Validate::Yes {
+            proof.check()?;
+        }
+        Ok(proof)
+    }
+}
+
+fn deserialize_fri_proof<F: Field, M: MatrixMerkleTree<F>>(
+    mut reader: impl Read,
+    compress: Compress,
+    validate: Validate,
+    shape: &ProofShape,
+) -> Result<FriProof<F, M::Root, M>, SerializationError> {
+    let num_layers = u64::deserialize_with_mode(&mut reader, compress, validate)?;
+    if num_layers != shape.num_fri_layers as u64 {
+        return Err(SerializationError::InvalidData);
+    }
+    let mut layers = Vec::with_capacity(shape.num_fri_layers);
+    for layer in 0..shape.num_fri_layers {
+        let folding_factor = shape.fri_options.layer_folding_factor(layer);
+        let max_layer_values = shape.num_queries * folding_factor;
+        layers.push(LayerProof {
+            flattenend_rows: deserialize_vec(&mut reader, compress, validate, max_layer_values)?,
+            merkle_proof: M::deserialize_proof(
                 &mut reader,
                 compress,
                 validate,
+                shape.num_queries,
+                shape.max_merkle_height,
             )?,
+            commitment: M::deserialize_root(&mut reader, compress, validate)?,
+        });
+    }
+    let remainder_commitment = if bool::deserialize_with_mode(&mut reader, compress, validate)? {
+        Some(M::deserialize_root(&mut reader, compress, validate)?)
+    } else {
+        None
+    };
+    let remainder_coeffs =
+        deserialize_vec(&mut reader, compress, validate, shape.num_remainder_coeffs)?;
+    Ok(FriProof {
+        layers,
+        remainder_commitment,
+        remainder_coeffs,
+    })
+}
+
+/// Deserializes a length prefixed vector of at most `max_len` commitments
+fn deserialize_roots<M: MerkleTree>(
+    mut reader: impl Read,
+    compress: Compress,
+    validate: Validate,
+    max_len: usize,
+) -> Result<Vec<M::Root>, SerializationError> {
+    let len = u64::deserialize_with_mode(&mut reader, compress, validate)?;
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= max_len)
+        .ok_or(SerializationError::InvalidData)?;
+    (0..len)
+        .map(|_| M::deserialize_root(&mut reader, compress, validate))
+        .collect()
+}
+
+/// Deserializes a length prefixed vector of at most `max_len` merkle proofs of
+/// the queried rows
+fn deserialize_merkle_proofs<M: MerkleTree>(
+    mut reader: impl Read,
+    compress: Compress,
+    validate: Validate,
+    max_len: usize,
+    shape: &ProofShape,
+) -> Result<Vec<M::Proof>, SerializationError> {
+    let len = u64::deserialize_with_mode(&mut reader, compress, validate)?;
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= max_len)
+        .ok_or(SerializationError::InvalidData)?;
+    (0..len)
+        .map(|_| {
+            M::deserialize_proof(
+                &mut reader,
+                compress,
+                validate,
+                shape.num_queries,
+                shape.max_merkle_height,
+            )
         })
+        .collect()
+}
+
+/// Deserializes the queried values of each extension trace segment
+fn deserialize_segment_values<T: CanonicalDeserialize>(
+    mut reader: impl Read,
+    compress: Compress,
+    validate: Validate,
+    shape: &ProofShape,
+) -> Result<Vec<Vec<T>>, SerializationError> {
+    let num_segments = u64::deserialize_with_mode(&mut reader, compress, validate)?;
+    if num_segments != shape.extension_segment_widths.len() as u64 {
+        return Err(SerializationError::InvalidData);
     }
+    shape
+        .extension_segment_widths
+        .iter()
+        .map(|num_columns| {
+            let max_len = shape.num_queries * num_columns;
+            deserialize_vec(&mut reader, compress, validate, max_len)
+        })
+        .collect()
 }
 
-impl<C: Stark> Proof<C> {
-    // adapted from Winterfell
-    // also https://github.com/starkware-libs/ethSTARK/blob/master/README.md#7-Measuring-Security
-    // https://eprint.iacr.org/2020/654.pdf section 7.2 for proven security
-    pub fn security_level_bits(&self) -> u32 {
-        let field_security = {
-            let lde_domain_size = self.trace_len * self.options.lde_blowup_factor as usize;
-            let extension_field_bits = field_bits::<C::Fq>();
-            extension_field_bits - lde_domain_size.ilog2()
-        };
+/// Bounds on the dimensions of a proof implied by its options and trace length
+struct ProofShape {
+    num_queries: usize,
+    num_base_columns: usize,
+    extension_segment_widths: Vec<usize>,
+    max_composition_segments: usize,
+    max_composition_columns: usize,
+    num_fri_layers: usize,
+    fri_options: FriOptions,
+    num_remainder_coeffs: usize,
+    /// Height of the largest merkle tree i.e. the tree over the lde domain
+    max_merkle_height: u32,
+    num_trace_arguments: usize,
+}
 
-        let fri_query_security = {
-            let grinding_factor = u32::from(self.options.grinding_factor);
-            let security_per_query = self.options.lde_blowup_factor.ilog2();
-            let num_fri_quiries = u32::from(self.options.num_queries);
-            security_per_query * num_fri_quiries + grinding_factor
+impl ProofShape {
+    fn new<A: AirConfig>(options: ProofOptions, trace_len: usize) -> Result<Self, Error> {
+        let ProofOptions {
+            num_queries,
+            lde_blowup_factor,
+            grinding_factor,
+            fri_folding_factor,
+            fri_folding_schedule,
+            fri_max_remainder_coeffs,
+            fri_remainder_commitment: _,
+            zero_knowledge,
+        } = options;
+        let valid_options = (ProofOptions::MIN_NUM_QUERIES..=ProofOptions::MAX_NUM_QUERIES)
+            .contains(&num_queries)
+            && lde_blowup_factor.is_power_of_two()
+            && (ProofOptions::MIN_BLOWUP_FACTOR..=ProofOptions::MAX_BLOWUP_FACTOR)
+                .contains(&lde_blowup_factor)
+            && grinding_factor <= ProofOptions::MAX_GRINDING_FACTOR
+            && is_supported_folding_factor(fri_folding_factor.into())
+            && fri_folding_schedule
+                .as_slice()
+                .iter()
+                .all(|&folding_factor| is_supported_folding_factor(folding_factor.into()))
+            && fri_max_remainder_coeffs != 0
+            // FRI queries are only sound if the codeword has redundancy
+            && (!zero_knowledge || options.fri_blowup_factor() >= 2);
+        if !valid_options {
+            return InvalidOptionsSnafu.fail();
+        }
+
+        // the lde domain must be a subgroup of the field
+        let num_queries = usize::from(num_queries);
+        let lde_domain_size = trace_len
+            .checked_mul(lde_blowup_factor.into())
+            .filter(|size| size.is_power_of_two())
+            .filter(|size| size.ilog2() <= A::Fp::TWO_ADICITY);
+        let lde_domain_size = match lde_domain_size {
+            Some(size) if trace_len.is_power_of_two() && num_queries <= size => size,
+            _ => return InvalidTraceLengthSnafu { trace_len }.fail(),
         };
 
-        let merkle_tree_security = C::MerkleTree::security_level_bits();
-        let public_coin_security = C::PublicCoin::security_level_bits();
+        let fri_options = options.into_fri_options();
+        let fri_blowup_factor = options.fri_blowup_factor();
+        Ok(Self {
+            num_queries,
+            num_base_columns: A::NUM_BASE_COLUMNS,
+            extension_segment_widths: extension_segment_widths::<A>(),
+            max_composition_segments: lde_blowup_factor.into(),
+            max_composition_columns: usize::from(lde_blowup_factor) + usize::from(zero_knowledge),
+            num_fri_layers: fri_options.num_layers(lde_domain_size),
+            fri_options,
+            num_remainder_coeffs: fri_options.remainder_size(lde_domain_size) / fri_blowup_factor,
+            max_merkle_height: lde_domain_size.ilog2(),
+            num_trace_arguments: trace_arguments(&A::constraints(trace_len)).len(),
+        })
+    }
+
+    fn check<C: Stark>(&self, proof: &Proof<C>) -> Result<(), Error> {
+        let queries = &proof.trace_queries;
+        let num_queries = self.num_queries;
+        check_len(
+            "base_trace_values",
+            queries.base_trace_values.len(),
+            |len| len == num_queries * self.num_base_columns,
+        )?;
+        let num_segments = self.extension_segment_widths.len();
+        if proof.extension_trace_commitments.len() != num_segments
+            || queries.extension_trace_values.len() != num_segments
+            || queries.extension_trace_proofs.len() != num_segments
+        {
+            return ExtensionTraceMismatchSnafu.fail();
+        }
+        for (values, num_columns) in zip(
+            &queries.extension_trace_values,
+            &self.extension_segment_widths,
+        ) {
+            check_len("extension_trace_values", values.len(), |len| {
+                len == num_queries * num_columns
+            })?;
+        }
+        check_len(
+            "composition_trace_values",
+            queries.composition_trace_values.len(),
+            |len| {
+                len % num_queries == 0
+                    && (1..=self.max_composition_columns).contains(&(len / num_queries))
+            },
+        )?;
+        check_len(
+            "composition_trace_ood_evals",
+            proof.composition_trace_ood_evals.len(),
+            |len| (1..=self.max_composition_segments).contains(&len),
+        )?;
+        check_len("fri_layers", proof.fri_proof.layers.len(), |len| {
+            len == self.num_fri_layers
+        })?;
+        for (i, layer) in proof.fri_proof.layers.iter().enumerate() {
+            let folding_factor = self.fri_options.layer_folding_factor(i);
+            check_len("fri_layer_values", layer.flattenend_rows.len(), |len| {
+                len % folding_factor == 0 && (1..=num_queries).contains(&(len / folding_factor))
+            })?;
+        }
+        let has_remainder_commitment =
+            self.fri_options.remainder_commitment() == RemainderCommitment::Codeword;
+        if proof.fri_proof.remainder_commitment.is_some() != has_remainder_commitment {
+            return FriRemainderCommitmentMismatchSnafu.fail();
+        }
+        check_len(
+            "fri_remainder_coeffs",
+            proof.fri_proof.remainder_coeffs.len(),
+            |len| len == self.num_remainder_coeffs,
+        )
+    }
+}
+
+fn check_len(
+    name: &'static str,
+    len: usize,
+    is_valid: impl FnOnce(usize) -> bool,
+) -> Result<(), Error> {
+    if is_valid(len) {
+        Ok(())
+    } else {
+        InvalidLengthSnafu { name, len }.fail()
+    }
+}
+
+/// Identifies serialized proofs. See [`ProofHeader`]
+pub const PROOF_MAGIC: [u8; 4] = *b"MSTK";
+
+/// Version of the proof serialization format
+pub const PROOF_FORMAT_VERSION: u16 = 1;
+
+/// Header that precedes a serialized proof. Describes which field, hash
+/// functions and AIR the proof is for so that mismatched proofs are rejected
+/// before the proof itself is deserialized.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize)]
+pub struct ProofHeader {
+    pub magic: [u8; 4],
+    pub version: u16,
+    /// Name of the extension field. See [`GpuField::field_name`]
+    pub field: String,
+    /// Name of the public coin's hash function. See [`HashFn::name`]
+    pub hash: String,
+    /// Name of the merkle tree used for commitments. See [`MerkleTree::name`]
+    pub merkle_tree: String,
+    /// Name of the AIR. See [`AirConfig::NAME`]
+    pub air: String,
+    /// Number of bytes in the serialized proof that follows the header
+    pub payload_len: u64,
+}
+
+impl Valid for ProofHeader {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for ProofHeader {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(Self {
+            magic: deserialize_array(&mut reader, compress, validate)?,
+            version: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
+            field: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
+            hash: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
+            merkle_tree: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
+            air: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
+            payload_len: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
+        })
+    }
+}
+
+impl ProofHeader {
+    pub fn new<C: Stark>(payload_len: u64) -> Self {
+        Self {
+            magic: PROOF_MAGIC,
+            version: PROOF_FORMAT_VERSION,
+            field: C::Fq::field_name(),
+            hash: <C::PublicCoin as PublicCoin>::HashFn::name(),
+            merkle_tree: C::MerkleTree::name(),
+            air: <C::AirConfig as AirConfig>::NAME.to_string(),
+            payload_len,
+        }
+    }
+
+    /// Checks the header describes a proof for `C`
+    pub fn check<C: Stark>(&self) -> Result<(), Error> {
+        let expected = Self::new::<C>(self.payload_len);
+        if self.magic != expected.magic {
+            return InvalidMagicSnafu.fail();
+        }
+        if self.version != expected.version {
+            return UnsupportedVersionSnafu {
+                version: self.version,
+            }
+            .fail();
+        }
+        if self.field != expected.field {
+            return FieldMismatchSnafu {
+                expected: expected.field,
+                actual: self.field.clone(),
+            }
+            .fail();
+        }
+        if self.hash != expected.hash {
+            return HashMismatchSnafu {
+                expected: expected.hash,
+                actual: self.hash.clone(),
+            }
+            .fail();
+        }
+        if self.merkle_tree != expected.merkle_tree {
+            return MerkleTreeMismatchSnafu {
+                expected: expected.merkle_tree,
+                actual: self.merkle_tree.clone(),
+            }
+            .fail();
+        }
+        if self.air != expected.air {
+            return AirMismatchSnafu {
+                expected: expected.air,
+                actual: self.air.clone(),
+            }
+            .fail();
+        }
+        Ok(())
+    }
+}
+
+/// Errors returned when a proof is malformed
+#[derive(Debug, PartialEq, Eq, Snafu)]
+pub enum Error {
+    #[snafu(display("proof header is malformed"))]
+    MalformedHeader,
+    #[snafu(display("proof header has an invalid magic number"))]
+    InvalidMagic,
+    #[snafu(display("proof format version `{version}` is not supported"))]
+    UnsupportedVersion { version: u16 },
+    #[snafu(display("expected a proof over `{expected}` but got a proof over `{actual}`"))]
+    FieldMismatch { expected: String, actual: String },
+    #[snafu(display("expected a proof using hash `{expected}` but got `{actual}`"))]
+    HashMismatch { expected: String, actual: String },
+    #[snafu(display("expected a proof using merkle tree `{expected}` but got `{actual}`"))]
+    MerkleTreeMismatch { expected: String, actual: String },
+    #[snafu(display("expected a proof for air `{expected}` but got `{actual}`"))]
+    AirMismatch { expected: String, actual: String },
+    #[snafu(display(
+        "header specifies a {expected} byte payload but the payload is {actual} bytes"
+    ))]
+    PayloadLengthMismatch { expected: u64, actual: u64 },
+    #[snafu(display("proof payload is malformed"))]
+    MalformedPayload,
+    #[snafu(display("proof options are invalid"))]
+    InvalidOptions,
+    #[snafu(display("trace length `{trace_len}` is invalid"))]
+    InvalidTraceLength { trace_len: usize },
+    #[snafu(display("`{name}` has an invalid length of `{len}`"))]
+    InvalidLength { name: &'static str, len: usize },
+    #[snafu(display("extension trace segments don't match the air"))]
+    ExtensionTraceMismatch,
+    #[snafu(display("fri remainder commitment is missing or unexpected"))]
+    FriRemainderCommitmentMismatch,
+}
+
+impl<C: Stark> Proof<C> {
+    /// Serializes the proof preceded by a [`ProofHeader`]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.serialize_compressed(&mut payload).unwrap();
+        let header = ProofHeader::new::<C>(payload.len() as u64);
+        let mut bytes = Vec::new();
+        header.serialize_compressed(&mut bytes).unwrap();
+        bytes.extend(payload);
+        bytes
+    }
+
+    /// Deserializes a proof serialized with [`Proof::to_bytes`]. The header is
+    /// checked against `C` and all lengths are checked against the proof's
+    /// options before they are allocated.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, Error> {
+        let header =
+            ProofHeader::deserialize_compressed(&mut bytes).map_err(|_| Error::MalformedHeader)?;
+        header.check::<C>()?;
+        let payload_len = bytes.len() as u64;
+        if header.payload_len != payload_len {
+            return PayloadLengthMismatchSnafu {
+                expected: header.payload_len,
+                actual: payload_len,
+            }
+            .fail();
+        }
+        let proof =
+            Self::deserialize_compressed(&mut bytes).map_err(|_| Error::MalformedPayload)?;
+        if !bytes.is_empty() {
+            return MalformedPayloadSnafu.fail();
+        }
+        Ok(proof)
+    }
+
+    /// Checks the dimensions of the proof are consistent with its options and
+    /// trace length
+    pub fn validate(&self) -> Result<(), Error> {
+        ProofShape::new::<C::AirConfig>(self.options, self.trace_len)?.check(self)
+    }
+
+    /// Checks the dimensions of the proof that depend on the AIR's constraints.
+    /// Complements [`Proof::validate`].
+    pub fn validate_with_air(&self, air: &Air<C::AirConfig>) -> Result<(), Error> {
+        let num_queries = usize::from(self.options.num_queries);
+        let num_composition_columns = air.num_composition_trace_cols();
+        check_len(
+            "composition_trace_values",
+            self.trace_queries.composition_trace_values.len(),
+            |len| len == num_queries * num_composition_columns,
+        )?;
+        // the zero knowledge mask isn't evaluated out of domain
+        check_len(
+            "composition_trace_ood_evals",
+            self.composition_trace_ood_evals.len(),
+            |len| len == air.num_composition_segments(),
+        )?;
+        check_len(
+            "execution_trace_ood_evals",
+            self.execution_trace_ood_evals.len(),
+            |len| len == air.trace_arguments().len(),
+        )
+    }
+
+    /// Returns the conjectured security level of the proof in bits. See
+    /// [`SecurityParams::conjectured_security_bits`]
+    pub fn security_level_bits(&self) -> u32 {
+        SecurityParams::new::<C>(self.trace_len, self.options).conjectured_security_bits()
+    }
 
-        field_security
-            .min(fri_query_security)
-            .min(merkle_tree_security)
-            .min(public_coin_security)
+    /// Returns the proven security level of the proof in bits. See
+    /// [`SecurityParams::proven_security_bits`]
+    pub fn proven_security_level_bits(&self) -> u32 {
+        SecurityParams::new::<C>(self.trace_len, self.options).proven_security_bits()
     }
 }
@@ -1,13 +1,22 @@
+use crate::air;
 use crate::air::AirConfig;
 use crate::challenges::Challenges;
 use crate::channel::ProverChannel;
 use crate::composer::DeepPolyComposer;
+use crate::debug::ConstraintError;
+use crate::fri;
+use crate::fri::is_supported_folding_factor;
 use crate::fri::FriProver;
+use crate::merkle;
 use crate::merkle::MatrixMerkleTree;
 use crate::merkle::MerkleTree;
 use crate::random::draw_multiple;
 use crate::stark::Stark;
+use crate::telemetry::Event;
+use crate::telemetry::Phase;
+use crate::telemetry::PhaseGuard;
 use crate::trace::Queries;
+use crate::utils::reset_gpu_peak_allocated_bytes;
 use crate::utils::GpuAllocator;
 use crate::utils::GpuVec;
 use crate::Air;
@@ -17,9 +26,13 @@ use crate::ProofOptions;
 use crate::Trace;
 use alloc::vec::Vec;
 use ark_ff::Field;
+use ark_ff::UniformRand;
 use ark_poly::EvaluationDomain;
 use ministark_gpu::utils::bit_reverse;
-use std::time::Instant;
+use rand::RngCore;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use snafu::Snafu;
 
 #[allow(clippy::too_many_lines)]
 pub fn default_prove<S: Stark>(
@@ -27,55 +40,116 @@ pub fn default_prove<S: Stark>(
     options: ProofOptions,
     witness: S::Witness,
 ) -> Result<Proof<S>, ProvingError> {
-    let now = Instant::now();
+    let folding_schedule = options.fri_folding_schedule;
+    let folding_factors = folding_schedule.as_slice().iter();
+    for &folding_factor in folding_factors.chain([&options.fri_folding_factor]) {
+        if !is_supported_folding_factor(folding_factor.into()) {
+            return UnsupportedFoldingFactorSnafu { folding_factor }.fail();
+        }
+    }
+
+    let observer = this.observer();
+    reset_gpu_peak_allocated_bytes();
+
+    let phase = PhaseGuard::new(observer, Phase::TraceGeneration);
     let trace = this.generate_trace(witness);
-    println!(
-        "Generated execution trace (cols={}, rows={}) in {:.0?}",
-        trace.base_columns().num_cols(),
-        trace.base_columns().num_rows(),
-        now.elapsed(),
-    );
+    observer.on_event(&Event::TraceDimensions {
+        trace_len: trace.len(),
+        num_base_columns: trace.base_columns().num_cols(),
+        num_extension_columns: S::AirConfig::NUM_EXTENSION_COLUMNS,
+    });
+    drop(phase);
 
-    let now = Instant::now();
-    let air = Air::new(trace.len(), this.get_public_inputs(), options);
+    let phase = PhaseGuard::new(observer, Phase::AirInit);
+    let air = Air::new(trace.len(), this.get_public_inputs(), options)?;
     let public_coin = this.gen_public_coin(&air);
     let mut channel = ProverChannel::<S>::new(&air, public_coin);
-    println!("Init air: {:?}", now.elapsed());
+    observer.on_event(&Event::Constraints {
+        num_constraints: air.num_constraints(),
+        num_composition_coeffs: air.num_composition_constraint_coeffs(),
+    });
+    observer.on_event(&Event::DomainSizes {
+        ce_domain_size: air.ce_domain().size(),
+        lde_domain_size: air.lde_domain().size(),
+    });
+    drop(phase);
+
+    // randomness used to hide the witness in zero knowledge mode
+    let mut zk_rng = options.zero_knowledge.then(ChaCha20Rng::from_entropy);
 
-    let now = Instant::now();
+    let phase = PhaseGuard::new(observer, Phase::BaseTraceCommitment);
     let trace_xs = air.trace_domain();
     let lde_xs = air.lde_domain();
     let base_trace = trace.base_columns();
-    assert_eq!(S::AirConfig::NUM_BASE_COLUMNS, base_trace.num_cols());
-    let base_trace_polys = base_trace.interpolate(trace_xs);
+    if base_trace.num_cols() != S::AirConfig::NUM_BASE_COLUMNS {
+        return BaseColumnCountMismatchSnafu {
+            expected: S::AirConfig::NUM_BASE_COLUMNS,
+            actual: base_trace.num_cols(),
+        }
+        .fail();
+    }
+    let mut base_trace_polys = base_trace.interpolate(trace_xs);
+    if let Some(rng) = zk_rng.as_mut() {
+        randomize_trace_polys(&mut base_trace_polys, rng);
+    }
     let mut base_trace_lde = base_trace_polys.bit_reversed_evaluate(lde_xs);
-    let base_trace_tree = S::MerkleTree::from_matrix(&base_trace_lde);
-    println!("Base trace commitment: {:?}", now.elapsed());
-
+    let base_trace_tree: S::MerkleTree = commit_matrix(&base_trace_lde, zk_rng.as_mut());
     channel.commit_base_trace(base_trace_tree.root());
-    let num_challenges = air.num_challenges();
-    let challenges = Challenges::new(draw_multiple(&mut channel.public_coin, num_challenges));
-    let hints = air.gen_hints(&challenges);
+    drop(phase);
 
-    let now = Instant::now();
-    let extension_trace = trace.build_extension_columns(&challenges);
-    let num_extension_cols = extension_trace.as_ref().map_or(0, Matrix::num_cols);
-    assert_eq!(S::AirConfig::NUM_EXTENSION_COLUMNS, num_extension_cols);
-    let extension_trace_polys = extension_trace.as_ref().map(|t| t.interpolate(trace_xs));
-    let mut extension_trace_lde = extension_trace_polys
-        .as_ref()
-        .map(|p| p.bit_reversed_evaluate(lde_xs));
-    let extension_trace_tree = extension_trace_lde.as_ref().map(S::MerkleTree::from_matrix);
-    if let Some(t) = extension_trace_tree.as_ref() {
-        channel.commit_extension_trace(t.root());
+    // each extension trace segment is built with the challenges drawn after
+    // the previous segment is committed to
+    let mut challenges = Vec::new();
+    let mut extension_traces = Vec::new();
+    let mut extension_trace_polys = Vec::new();
+    let mut extension_trace_ldes = Vec::new();
+    let mut extension_trace_trees: Vec<S::MerkleTree> = Vec::new();
+    let phase = PhaseGuard::new(observer, Phase::ExtensionTraceCommitment);
+    for (segment, layout) in air.extension_segments().iter().enumerate() {
+        let segment_challenges = draw_multiple(&mut channel.public_coin, layout.num_challenges);
+        challenges.extend(segment_challenges);
+        let drawn_challenges = Challenges::new(challenges.clone());
+        let extension_trace =
+            trace.build_extension_segment(segment, &drawn_challenges, &extension_traces);
+        let num_columns = extension_trace.as_ref().map_or(0, Matrix::num_cols);
+        let extension_trace = match extension_trace {
+            Some(extension_trace) if num_columns == layout.num_columns => extension_trace,
+            _ => {
+                return ExtensionColumnCountMismatchSnafu {
+                    segment,
+                    expected: layout.num_columns,
+                    actual: num_columns,
+                }
+                .fail()
+            }
+        };
+        let mut polys = extension_trace.interpolate(trace_xs);
+        if let Some(rng) = zk_rng.as_mut() {
+            randomize_trace_polys(&mut polys, rng);
+        }
+        let lde = polys.bit_reversed_evaluate(lde_xs);
+        let tree: S::MerkleTree = commit_matrix(&lde, zk_rng.as_mut());
+        channel.commit_extension_trace(tree.root());
+        extension_traces.push(extension_trace);
+        extension_trace_polys.push(polys);
+        extension_trace_ldes.push(lde);
+        extension_trace_trees.push(tree);
     }
-    println!("Extension trace commitment: {:?}", now.elapsed());
+    drop(phase);
+    let num_trailing_challenges = air.num_trailing_challenges();
+    challenges.extend(draw_multiple(
+        &mut channel.public_coin,
+        num_trailing_challenges,
+    ));
+    let challenges = Challenges::new(challenges);
+    let hints = air.gen_hints(&challenges);
 
+    let extension_trace = (!extension_traces.is_empty()).then(|| Matrix::join(extension_traces));
     #[cfg(debug_assertions)]
-    this.validate_constraints(&challenges, &hints, base_trace, extension_trace.as_ref());
+    this.validate_constraints(&challenges, &hints, base_trace, extension_trace.as_ref())?;
     drop((trace, extension_trace));
 
-    let composition_trace_polys: Matrix<S::Fq>;
+    let mut composition_trace_polys: Matrix<S::Fq>;
     let composition_trace_lde: Matrix<S::Fq>;
     let composition_trace_tree: S::MerkleTree;
     {
@@ -86,58 +160,71 @@ pub fn default_prove<S: Stark>(
         let ce_lde_xs = air.ce_domain();
         let ce_domain_size = ce_lde_xs.size();
         let base_trace_ce_cols = bit_reverse_ce_trace(ce_domain_size, &mut base_trace_lde);
-        let extension_trace_ce_cols = extension_trace_lde
-            .as_mut()
-            .map(|t| bit_reverse_ce_trace(ce_domain_size, t));
+        let extension_trace_ce_cols = extension_trace_ldes
+            .iter_mut()
+            .flat_map(|t| bit_reverse_ce_trace(ce_domain_size, t))
+            .collect::<Vec<&[S::Fq]>>();
 
         let num_composition_coeffs = air.num_composition_constraint_coeffs();
         let composition_coeffs = draw_multiple(&mut channel.public_coin, num_composition_coeffs);
         let x_lde = ce_lde_xs.elements().collect::<Vec<_>>();
 
-        let now = Instant::now();
+        let phase = PhaseGuard::new(observer, Phase::ConstraintEvaluation);
         let composition_evals = S::AirConfig::eval_constraint(
             air.composition_constraint(),
             &challenges,
             &hints,
             &composition_coeffs,
-            air.ce_blowup_factor(),
+            ce_domain_size / air.trace_len(),
             x_lde.to_vec_in(GpuAllocator),
             &base_trace_ce_cols,
-            extension_trace_ce_cols.as_deref(),
+            (!extension_trace_ce_cols.is_empty()).then_some(&extension_trace_ce_cols),
         );
-        println!("Constraint eval: {:?}", now.elapsed());
+        drop(phase);
 
-        let now = Instant::now();
+        let phase = PhaseGuard::new(observer, Phase::CompositionTraceCommitment);
         let composition_poly =
             GpuVec::try_from(composition_evals.into_polynomials(air.ce_domain())).unwrap();
-        let mut composition_trace_cols = (0..air.ce_blowup_factor())
-            .map(|_| Vec::with_capacity_in(air.trace_len(), GpuAllocator))
+        let mut composition_trace_cols = composition_poly
+            .chunks(air.trace_len())
+            .map(|segment| segment.to_vec_in(GpuAllocator))
             .collect::<Vec<_>>();
-        for chunk in composition_poly.chunks(composition_trace_cols.len()) {
-            for i in 0..composition_trace_cols.len() {
-                composition_trace_cols[i].push(chunk[i]);
-            }
+        if let Some(rng) = zk_rng.as_mut() {
+            randomize_composition_segments(&mut composition_trace_cols, rng);
+            // random polynomial that masks the DEEP composition polynomial. It's
+            // committed to alongside the composition trace polynomials.
+            let mask = (0..air.padded_trace_len())
+                .map(|_| S::Fq::rand(rng))
+                .collect::<Vec<_>>()
+                .to_vec_in(GpuAllocator);
+            composition_trace_cols.push(mask);
         }
         composition_trace_polys = Matrix::new(composition_trace_cols);
         composition_trace_lde = composition_trace_polys.bit_reversed_evaluate(air.lde_domain());
-        composition_trace_tree = S::MerkleTree::from_matrix(&composition_trace_lde);
+        composition_trace_tree = commit_matrix(&composition_trace_lde, zk_rng.as_mut());
         channel.commit_composition_trace(composition_trace_tree.root());
-        println!("Composition trace commitment: {:?}", now.elapsed());
+        drop(phase);
 
         bit_reverse_ce_trace(ce_domain_size, &mut base_trace_lde);
-        extension_trace_lde
-            .as_mut()
-            .map(|t| bit_reverse_ce_trace(ce_domain_size, t));
+        for extension_trace_lde in &mut extension_trace_ldes {
+            bit_reverse_ce_trace(ce_domain_size, extension_trace_lde);
+        }
     }
 
-    let now = Instant::now();
+    let phase = PhaseGuard::new(observer, Phase::DeepComposition);
     let z = channel.get_ood_point();
+    let deep_mask = zk_rng
+        .is_some()
+        .then(|| composition_trace_polys.0.pop().unwrap());
+    let extension_trace_polys =
+        (!extension_trace_polys.is_empty()).then(|| Matrix::join(extension_trace_polys));
     let mut deep_poly_composer = DeepPolyComposer::new(
         &air,
         z,
         base_trace_polys,
         extension_trace_polys,
         composition_trace_polys,
+        deep_mask,
     );
     let (execution_trace_oods, composition_trace_oods) = deep_poly_composer.get_ood_evals();
     channel.send_ood_evals(execution_trace_oods, composition_trace_oods);
@@ -146,38 +233,120 @@ pub fn default_prove<S: Stark>(
     let deep_composition_poly = deep_poly_composer.into_deep_poly(deep_coeffs);
     // let deep_xs = Radix2EvaluationDomain::new(lde_xs.size());
     let deep_composition_lde = deep_composition_poly.into_bit_reversed_evaluations(lde_xs);
-    println!("Deep composition: {:?}", now.elapsed());
+    drop(phase);
 
-    let now = Instant::now();
+    let phase = PhaseGuard::new(observer, Phase::Fri);
     let fri_options = options.into_fri_options();
-    let mut fri_prover = FriProver::<S::Fq, S::Digest, S::MerkleTree>::new(fri_options);
-    fri_prover.build_layers(&mut channel, deep_composition_lde.try_into().unwrap());
-    println!("FRI: {:?}", now.elapsed());
+    let mut fri_prover =
+        FriProver::<S::Fq, <S::MerkleTree as MerkleTree>::Root, S::MerkleTree>::new(
+            fri_options,
+            lde_xs.coset_offset(),
+        );
+    fri_prover.build_layers(&mut channel, deep_composition_lde.try_into().unwrap())?;
+    drop(phase);
 
-    let now = Instant::now();
-    channel.grind_fri_commitments();
-    println!("Proof of work: {:?}", now.elapsed());
+    let phase = PhaseGuard::new(observer, Phase::ProofOfWork);
+    channel.grind_fri_commitments()?;
+    drop(phase);
+
+    let phase = PhaseGuard::new(observer, Phase::Queries);
 
     let query_positions = Vec::from_iter(channel.get_fri_query_positions());
-    let fri_proof = fri_prover.into_proof(&query_positions);
+    let fri_proof = fri_prover.into_proof(&query_positions)?;
 
     let queries = Queries::new(
         &base_trace_lde,
-        extension_trace_lde.as_ref(),
+        &extension_trace_ldes,
         &composition_trace_lde,
         &base_trace_tree,
-        extension_trace_tree.as_ref(),
+        &extension_trace_trees,
         &composition_trace_tree,
         &query_positions,
-    );
+    )?;
+    drop(phase);
     Ok(channel.build_proof(queries, fri_proof))
 }
 
 /// Errors that can occur during the proving stage
-#[derive(Debug)]
+#[derive(Debug, Snafu)]
 pub enum ProvingError {
-    Fail,
-    // TODO
+    #[snafu(context(false))]
+    #[snafu(display("invalid air: {source}"))]
+    InvalidAir { source: air::Error },
+    #[snafu(display("expected `{expected}` base trace columns, but `{actual}` were provided"))]
+    BaseColumnCountMismatch { expected: usize, actual: usize },
+    #[snafu(display(
+        "expected `{expected}` columns in extension trace segment `{segment}`, but `{actual}` \
+         were provided"
+    ))]
+    ExtensionColumnCountMismatch {
+        segment: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[snafu(display("fri folding factor `{folding_factor}` is not supported"))]
+    UnsupportedFoldingFactor { folding_factor: u8 },
+    #[snafu(display("no proof of work nonce found for grinding factor `{grinding_factor}`"))]
+    ProofOfWorkNotFound { grinding_factor: u8 },
+    #[snafu(context(false))]
+    #[snafu(display("execution trace does not satisfy the air: {source}"))]
+    UnsatisfiedConstraints { source: ConstraintError },
+    #[snafu(context(false))]
+    #[snafu(display("failed to open commitment: {source}"))]
+    MerkleProof { source: merkle::Error },
+    #[snafu(context(false))]
+    #[snafu(display("failed to generate fri proof: {source}"))]
+    Fri { source: fri::ProvingError },
+}
+
+/// Commits to the rows of a matrix. Leaves are salted in zero knowledge mode
+fn commit_matrix<T, M: MatrixMerkleTree<T>>(
+    matrix: &Matrix<T>,
+    rng: Option<&mut ChaCha20Rng>,
+) -> M {
+    rng.map_or_else(
+        || M::from_matrix(matrix),
+        |rng| M::from_matrix_salted(matrix, rng),
+    )
+}
+
+/// Adds a random multiple of the trace domain's vanishing polynomial to each
+/// trace polynomial i.e. `t'(x) = t(x) + (x^n - 1) * r(x)` where `r(x)` is a
+/// random polynomial of degree less than `n`. The randomized polynomials agree
+/// with the trace on the trace domain and their evaluations at up to `n` points
+/// outside the trace domain reveal nothing about the trace. [`Air::new`]
+/// checks the number of opened points is within this bound.
+fn randomize_trace_polys<F: Field>(polys: &mut Matrix<F>, rng: &mut impl RngCore) {
+    for column in &mut polys.0 {
+        let n = column.len();
+        let r = (0..n).map(|_| F::rand(rng)).collect::<Vec<F>>();
+        for (coeff, r) in column.iter_mut().zip(&r) {
+            *coeff -= r;
+        }
+        column.extend(r);
+    }
+}
+
+/// Masks the segments `h_i` of the composition polynomial with random
+/// polynomials `r_i` of degree less than `n` i.e. `h_i'(x) = h_i(x) +
+/// x^n * r_i(x) - r_{i-1}(x)` where `r_{-1}` and the last `r_i` are zero. The
+/// masks telescope so `Σ x^(i*n) * h_i'(x) = Σ x^(i*n) * h_i(x)`.
+fn randomize_composition_segments<F: Field>(segments: &mut [GpuVec<F>], rng: &mut impl RngCore) {
+    let num_segments = segments.len();
+    let mut prev_r = Vec::new();
+    for (i, segment) in segments.iter_mut().enumerate() {
+        let n = segment.len();
+        let r = if i + 1 < num_segments {
+            (0..n).map(|_| F::rand(rng)).collect::<Vec<F>>()
+        } else {
+            vec![F::zero(); n]
+        };
+        for (coeff, prev_r) in segment.iter_mut().zip(&prev_r) {
+            *coeff -= prev_r;
+        }
+        segment.extend(&r);
+        prev_r = r;
+    }
 }
 
 /// Bit reverses the first ce_domain_size many values of the matrix columns.
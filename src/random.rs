@@ -1,8 +1,13 @@
+use crate::hash::int_to_elements;
+use crate::hash::AlgebraicHashFn;
 use crate::hash::Digest;
 use crate::hash::ElementHashFn;
 use crate::hash::HashFn;
+use crate::merkle::Commitment;
 use alloc::vec::Vec;
+use ark_ff::BigInteger;
 use ark_ff::Field;
+use ark_ff::PrimeField;
 use rand::Rng;
 use rand::RngCore;
 #[cfg(feature = "parallel")]
@@ -27,11 +32,20 @@ use std::marker::PhantomData;
 pub trait PublicCoin: Sized + Send + Sync + Debug {
     type Digest: Digest;
     type Field: Field;
+    /// Hash function used to derive the coin's seed
+    type HashFn: HashFn<Digest = Self::Digest>;
 
     fn new(digest: Self::Digest) -> Self;
 
     fn reseed_with_digest(&mut self, val: &Self::Digest);
 
+    /// Reseeds with each digest of a merkle tree commitment
+    fn reseed_with_commitment(&mut self, commitment: &impl Commitment<Digest = Self::Digest>) {
+        for digest in commitment.digests() {
+            self.reseed_with_digest(digest);
+        }
+    }
+
     fn reseed_with_field_elements(&mut self, vals: &[Self::Field]);
 
     fn reseed_with_field_element_vector(&mut self, vector: &[Self::Field]) {
@@ -42,8 +56,14 @@ pub trait PublicCoin: Sized + Send + Sync + Debug {
 
     fn draw(&mut self) -> Self::Field;
 
-    /// Draws a maximum of n unique queries in the range `[0, domain_size)`
-    fn draw_queries(&mut self, max_n: usize, domain_size: usize) -> BTreeSet<usize>;
+    /// Draws exactly `n` unique queries in the range `[0, domain_size)`.
+    /// Duplicate draws are rejected and redrawn so the prover and verifier
+    /// consume the coin identically.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than `domain_size`
+    fn draw_queries(&mut self, n: usize, domain_size: usize) -> BTreeSet<usize>;
 
     fn grind_proof_of_work(&self, proof_of_work_bits: u8) -> Option<u64> {
         #[cfg(not(feature = "parallel"))]
@@ -98,6 +118,7 @@ impl<F: Field, H: HashFn> PublicCoinImpl<F, H> {
 impl<F: Field, H: ElementHashFn<F>> PublicCoin for PublicCoinImpl<F, H> {
     type Digest = H::Digest;
     type Field = F;
+    type HashFn = H;
 
     fn new(digest: H::Digest) -> Self {
         Self {
@@ -135,8 +156,16 @@ impl<F: Field, H: ElementHashFn<F>> PublicCoin for PublicCoinImpl<F, H> {
         F::rand(self)
     }
 
-    fn draw_queries(&mut self, max_n: usize, domain_size: usize) -> BTreeSet<usize> {
-        (0..max_n).map(|_| self.gen_range(0..domain_size)).collect()
+    fn draw_queries(&mut self, n: usize, domain_size: usize) -> BTreeSet<usize> {
+        assert!(
+            n <= domain_size,
+            "cannot draw {n} unique queries from {domain_size}"
+        );
+        let mut queries = BTreeSet::new();
+        while queries.len() < n {
+            queries.insert(self.gen_range(0..domain_size));
+        }
+        queries
     }
 
     fn security_level_bits() -> u32 {
@@ -149,7 +178,10 @@ impl<F: Field, H: HashFn> Iterator for PublicCoinImpl<F, H> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.bytes.is_empty() {
-            self.bytes = self.gen_next().as_bytes().to_vec();
+            // only the digest bytes are drawn from. Truncated digests are
+            // zero padded.
+            let digest = self.gen_next().as_bytes();
+            self.bytes = digest[..H::Digest::NUM_BYTES].to_vec();
         }
         self.bytes.pop()
     }
@@ -178,6 +210,143 @@ impl<F: Field, H: HashFn> RngCore for PublicCoinImpl<F, H> {
     }
 }
 
+/// Public coin that absorbs and squeezes field elements natively.
+///
+/// Uses a duplex sponge over the permutation of an algebraic hash function.
+/// Unlike [`PublicCoinImpl`] no bytes are involved which makes the coin cheap
+/// to implement inside another STARK. Elements are buffered until the rate is
+/// full or an element is drawn at which point the buffered elements overwrite
+/// the rate and the permutation is applied. Drawn elements are taken from the
+/// rate of the permuted state.
+pub struct FieldPublicCoin<F: Field, H: AlgebraicHashFn> {
+    state: Vec<H::Field>,
+    input: Vec<H::Field>,
+    output: Vec<H::Field>,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: Field, H: AlgebraicHashFn> FieldPublicCoin<F, H> {
+    fn observe(&mut self, element: H::Field) {
+        self.output.clear();
+        self.input.push(element);
+        if self.input.len() == H::RATE_RANGE.len() {
+            self.duplex();
+        }
+    }
+
+    fn sample(&mut self) -> H::Field {
+        if !self.input.is_empty() || self.output.is_empty() {
+            self.duplex();
+        }
+        self.output.pop().unwrap()
+    }
+
+    fn duplex(&mut self) {
+        let rate = &mut self.state[H::RATE_RANGE];
+        rate[..self.input.len()].copy_from_slice(&self.input);
+        self.input.clear();
+        H::permute(&mut self.state);
+        self.output = self.state[H::RATE_RANGE].to_vec();
+    }
+}
+
+impl<F: Field, H: AlgebraicHashFn> Clone for FieldPublicCoin<F, H> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            input: self.input.clone(),
+            output: self.output.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, H: AlgebraicHashFn> Debug for FieldPublicCoin<F, H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FieldPublicCoin")
+            .field("state", &self.state)
+            .field("input", &self.input)
+            .field("output", &self.output)
+            .finish()
+    }
+}
+
+impl<F: Field<BasePrimeField = H::Field>, H: AlgebraicHashFn> PublicCoin for FieldPublicCoin<F, H> {
+    type Digest = H::Digest;
+    type Field = F;
+    type HashFn = H;
+
+    fn new(digest: H::Digest) -> Self {
+        let mut coin = Self {
+            state: vec![H::Field::ZERO; H::STATE_WIDTH],
+            input: Vec::new(),
+            output: Vec::new(),
+            _phantom: PhantomData,
+        };
+        coin.reseed_with_digest(&digest);
+        coin
+    }
+
+    fn reseed_with_digest(&mut self, val: &H::Digest) {
+        for element in H::digest_elements(val) {
+            self.observe(*element);
+        }
+    }
+
+    fn reseed_with_field_elements(&mut self, vals: &[F]) {
+        for val in vals {
+            for element in val.to_base_prime_field_elements() {
+                self.observe(element);
+            }
+        }
+    }
+
+    fn reseed_with_int(&mut self, val: u64) {
+        for element in int_to_elements(val) {
+            self.observe(element);
+        }
+    }
+
+    fn draw(&mut self) -> F {
+        let elements = (0..F::extension_degree())
+            .map(|_| self.sample())
+            .collect::<Vec<H::Field>>();
+        F::from_base_prime_field_elems(&elements).unwrap()
+    }
+
+    /// Queries are drawn from the low bits of field elements. The domain size
+    /// must be a power of two.
+    fn draw_queries(&mut self, n: usize, domain_size: usize) -> BTreeSet<usize> {
+        assert!(domain_size.is_power_of_two());
+        assert!(
+            n <= domain_size,
+            "cannot draw {n} unique queries from {domain_size}"
+        );
+        let mut queries = BTreeSet::new();
+        while queries.len() < n {
+            let value = self.sample().into_bigint().as_ref()[0];
+            queries.insert(usize::try_from(value % domain_size as u64).unwrap());
+        }
+        queries
+    }
+
+    /// Proof of work is valid if the element drawn after reseeding with the
+    /// nonce has `proof_of_work_bits` trailing zeros
+    fn verify_proof_of_work(&self, proof_of_work_bits: u8, nonce: u64) -> bool {
+        let mut coin = self.clone();
+        coin.reseed_with_int(nonce);
+        let value = coin.sample().into_bigint();
+        let trailing_zeros = (0..value.num_bits())
+            .take_while(|&i| !value.get_bit(i as usize))
+            .count();
+        trailing_zeros >= usize::from(proof_of_work_bits)
+    }
+
+    fn security_level_bits() -> u32 {
+        H::COLLISION_RESISTANCE
+    }
+}
+
 pub fn leading_zeros(hash: &[u8]) -> u32 {
     let mut zeros = 0;
     for byte in hash {
@@ -194,3 +363,102 @@ pub fn leading_zeros(hash: &[u8]) -> u32 {
 pub fn draw_multiple<P: PublicCoin>(public_coin: &mut P, n: usize) -> Vec<P::Field> {
     (0..n).map(|_| public_coin.draw()).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::FieldPublicCoin;
+    use super::PublicCoin;
+    use super::PublicCoinImpl;
+    use crate::hash::poseidon2::Poseidon2GoldilocksHashFn;
+    use crate::hash::rpo::Rpo256HashFn;
+    use crate::hash::HashFn;
+    use crate::hash::MaskedKeccak256HashFn;
+    use ark_ff::Field;
+    use ark_ff::PrimeField;
+    use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+    use ministark_gpu::fields::p18446744069414584321::ark::Fq3;
+
+    type Coin = FieldPublicCoin<Fq3, Poseidon2GoldilocksHashFn>;
+
+    fn new_coin() -> Coin {
+        Coin::new(Poseidon2GoldilocksHashFn::hash([1, 2, 3]))
+    }
+
+    #[test]
+    fn draws_are_deterministic() {
+        let mut a = new_coin();
+        let mut b = new_coin();
+
+        assert_eq!(a.draw(), b.draw());
+        assert_eq!(a.draw_queries(20, 1024), b.draw_queries(20, 1024));
+    }
+
+    #[test]
+    fn draws_exact_number_of_queries() {
+        let mut coin = new_coin();
+
+        assert_eq!(20, coin.draw_queries(20, 32).len());
+        assert_eq!(16, coin.draw_queries(16, 16).len());
+    }
+
+    #[test]
+    fn reseeding_changes_draws() {
+        let mut a = new_coin();
+        let mut b = new_coin();
+
+        b.reseed_with_field_elements(&[Fq3::from(5u8)]);
+
+        assert_ne!(a.draw(), b.draw());
+    }
+
+    #[test]
+    fn consecutive_draws_differ() {
+        let mut coin = new_coin();
+
+        let draws = (0..20).map(|_| coin.draw()).collect::<Vec<Fq3>>();
+
+        for (i, a) in draws.iter().enumerate() {
+            assert!(!draws[i + 1..].contains(a));
+        }
+    }
+
+    #[test]
+    fn ground_proof_of_work_verifies() {
+        let coin = FieldPublicCoin::<Fp, Rpo256HashFn>::new(Rpo256HashFn::hash([1, 2, 3]));
+
+        let nonce = coin.grind_proof_of_work(8).unwrap();
+
+        assert!(coin.verify_proof_of_work(8, nonce));
+    }
+
+    #[test]
+    fn masked_digest_draws_use_all_digest_bytes() {
+        let seed = MaskedKeccak256HashFn::hash([1, 2, 3]);
+        let mut coin = PublicCoinImpl::<Fp, MaskedKeccak256HashFn>::new(seed);
+
+        let bytes = coin.by_ref().take(40).collect::<Vec<u8>>();
+
+        let mut first = MaskedKeccak256HashFn::merge_with_int(&seed, 1).0;
+        let mut second = MaskedKeccak256HashFn::merge_with_int(&seed, 2).0;
+        first.reverse();
+        second.reverse();
+        assert_eq!([first, second].concat(), bytes);
+    }
+
+    #[test]
+    fn masked_digest_draws_after_reseed_are_non_zero() {
+        let seed = MaskedKeccak256HashFn::hash([1, 2, 3]);
+        let mut coin = PublicCoinImpl::<Fp, MaskedKeccak256HashFn>::new(seed);
+
+        for i in 0..16 {
+            coin.reseed_with_int(i);
+            let draw = coin.draw();
+
+            assert_ne!(Fp::ZERO, draw);
+            // both 32 bit limbs are random
+            let value = draw.into_bigint().0[0];
+            assert_ne!(0, value >> 32);
+            assert_ne!(0, value & 0xFFFF_FFFF);
+        }
+    }
+}
@@ -0,0 +1,435 @@
+//! Security estimates of proofs and a tuner for [`ProofOptions`].
+//!
+//! Conjectured security assumes the ethSTARK conjecture on the soundness of
+//! FRI <https://eprint.iacr.org/2021/582>. Proven security uses the proximity
+//! gaps of Reed-Solomon codes in the list-decoding regime up to the Johnson
+//! bound <https://eprint.iacr.org/2020/654>.
+use crate::air::extension_segment_widths;
+use crate::air::AirConfig;
+use crate::constraints::Constraint;
+use crate::fri::FoldingSchedule;
+use crate::merkle::MerkleTree;
+use crate::random::PublicCoin;
+use crate::stark::Stark;
+use crate::utils::field_bits;
+use crate::ProofOptions;
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use ark_ff::FftField;
+use ark_ff::Zero;
+use ark_serialize::CanonicalSerialize;
+use core::marker::PhantomData;
+use snafu::Snafu;
+
+/// Range of the Johnson bound proximity parameter `m` searched for the best
+/// proven security
+const JOHNSON_PARAMS: core::ops::RangeInclusive<usize> = 3..=128;
+
+/// Parameters of a proof that determine its security
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityParams {
+    pub options: ProofOptions,
+    pub trace_len: usize,
+    /// Number of rows the trace polynomials are interpolated over
+    pub padded_trace_len: usize,
+    /// Degree of the composition polynomial relative to the trace polynomials
+    pub ce_blowup_factor: usize,
+    /// Number of distinct points the trace polynomials are opened at
+    pub num_trace_openings: usize,
+    /// Number of bits in the extension field
+    pub field_bits: u32,
+    /// Collision resistance of the hash function in bits
+    pub hash_bits: u32,
+}
+
+#[allow(clippy::cast_precision_loss)]
+impl SecurityParams {
+    /// Derives the parameters of a proof of `S` with the given trace length
+    /// and options
+    pub fn new<S: Stark>(trace_len: usize, options: ProofOptions) -> Self {
+        let padded_trace_len = if options.zero_knowledge {
+            trace_len * 2
+        } else {
+            trace_len
+        };
+        let constraints = S::AirConfig::constraints(trace_len);
+        let composition_constraint =
+            S::AirConfig::composition_constraint(padded_trace_len, &constraints);
+        let trace_offsets = constraints
+            .iter()
+            .flat_map(Constraint::trace_arguments)
+            .map(|(_, offset)| offset)
+            .collect::<BTreeSet<isize>>();
+        let merkle_tree_security = S::MerkleTree::security_level_bits();
+        let public_coin_security = S::PublicCoin::security_level_bits();
+        Self {
+            options,
+            trace_len,
+            padded_trace_len,
+            ce_blowup_factor: composition_constraint.blowup_factor(padded_trace_len),
+            num_trace_openings: trace_offsets.len().max(1),
+            field_bits: field_bits::<S::Fq>(),
+            hash_bits: merkle_tree_security.min(public_coin_security),
+        }
+    }
+
+    /// Returns the security level in bits assuming the ethSTARK conjecture.
+    /// This is the minimum security of the constraint batching, the DEEP
+    /// out-of-domain sampling, the FRI commit phase and the FRI queries.
+    pub fn conjectured_security_bits(&self) -> u32 {
+        let fri_blowup_factor = self.options.fri_blowup_factor() as f64;
+        let query_bits = f64::from(self.options.num_queries).mul_add(
+            fri_blowup_factor.log2(),
+            f64::from(self.options.grinding_factor),
+        );
+        let fri_commit_error = self
+            .fri_layers()
+            .map(|(folding_factor, domain_size)| ((folding_factor - 1) * domain_size) as f64)
+            .sum();
+        self.security_bits([
+            self.field_bits(1.0),
+            self.field_bits(self.deep_error()),
+            self.field_bits(fri_commit_error),
+            query_bits,
+        ])
+    }
+
+    /// Returns the proven security level in bits. Soundness is analysed in
+    /// the list-decoding regime up to the Johnson bound with the proximity
+    /// parameter `m` that gives the highest security.
+    pub fn proven_security_bits(&self) -> u32 {
+        JOHNSON_PARAMS
+            .map(|m| self.proven_security_bits_for(m))
+            .max()
+            .unwrap()
+    }
+
+    fn proven_security_bits_for(&self, m: usize) -> u32 {
+        let m = m as f64;
+        let rate = 1.0 / self.options.fri_blowup_factor() as f64;
+        // proximity parameter is `1 - alpha` for agreement `alpha`
+        let alpha = (1.0 + 0.5 / m) * rate.sqrt();
+        let list_size = (m + 0.5) / rate.sqrt();
+        let query_bits = (-f64::from(self.options.num_queries))
+            .mul_add(alpha.log2(), f64::from(self.options.grinding_factor));
+        // theorem 1.5 of eprint 2020/654 for batching with powers of a random
+        // element i.e. `(k - 1) * (m + 1/2)^7 * n^2 / (3 * rho^(3/2) * |F|)`
+        let fri_commit_error = self
+            .fri_layers()
+            .map(|(folding_factor, domain_size)| {
+                let domain_size = domain_size as f64;
+                (folding_factor - 1) as f64 * (m + 0.5).powi(7) * domain_size.powi(2)
+                    / (3.0 * rate.powf(1.5))
+            })
+            .sum();
+        self.security_bits([
+            self.field_bits(list_size),
+            self.field_bits(list_size * self.deep_error()),
+            self.field_bits(fri_commit_error),
+            query_bits,
+        ])
+    }
+
+    /// Returns the numerator of the soundness error of the DEEP out-of-domain
+    /// sampling for a list size of one
+    fn deep_error(&self) -> f64 {
+        let trace_len = self.padded_trace_len as f64;
+        let degree = self.ce_blowup_factor as f64;
+        let num_openings = self.num_trace_openings as f64;
+        degree.mul_add(trace_len + num_openings - 1.0, trace_len - 1.0)
+    }
+
+    /// Returns the bits of security of an error of `numerator / |F|`
+    fn field_bits(&self, numerator: f64) -> f64 {
+        f64::from(self.field_bits) - numerator.log2()
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn security_bits(&self, component_bits: [f64; 4]) -> u32 {
+        let bits = component_bits.into_iter().fold(f64::INFINITY, f64::min);
+        (bits.max(0.0).floor() as u32).min(self.hash_bits)
+    }
+
+    /// Returns the folding factor and domain size of each FRI layer
+    fn fri_layers(&self) -> impl Iterator<Item = (usize, usize)> {
+        let fri_options = self.options.into_fri_options();
+        let domain_size = self.trace_len * usize::from(self.options.lde_blowup_factor);
+        (0..fri_options.num_layers(domain_size)).map(move |layer| {
+            (
+                fri_options.layer_folding_factor(layer),
+                fri_options.layer_domain_size(domain_size, layer),
+            )
+        })
+    }
+
+    /// Returns an estimate of the size of a serialized proof in bytes. Merkle
+    /// proofs are assumed to share no nodes so this is an upper estimate.
+    pub fn proof_size<S: Stark>(&self) -> usize {
+        let digest_size = <S::MerkleTree as MerkleTree>::Root::default().compressed_size();
+        let base_field_size = S::Fp::zero().compressed_size();
+        let extension_field_size = S::Fq::zero().compressed_size();
+        let num_queries = usize::from(self.options.num_queries);
+        let zero_knowledge = self.options.zero_knowledge;
+        let lde_domain_size = self.trace_len * usize::from(self.options.lde_blowup_factor);
+        let num_base_columns = S::AirConfig::NUM_BASE_COLUMNS;
+        let num_extension_columns = S::AirConfig::NUM_EXTENSION_COLUMNS;
+        let num_composition_segments =
+            self.padded_trace_len * self.ce_blowup_factor / self.trace_len;
+        let num_composition_columns = num_composition_segments + usize::from(zero_knowledge);
+        let num_trees = 2 + extension_segment_widths::<S::AirConfig>().len();
+
+        let commitments = (num_trees + self.fri_layers().count() + 1) * digest_size;
+        let ood_evals = (self.num_trace_openings * (num_base_columns + num_extension_columns)
+            + num_composition_segments)
+            * extension_field_size;
+        let merkle_path_size = lde_domain_size.ilog2() as usize * digest_size;
+        let salt_size = if zero_knowledge { digest_size } else { 0 };
+        let trace_queries = num_queries
+            * (num_base_columns * base_field_size
+                + (num_extension_columns + num_composition_columns) * extension_field_size
+                + num_trees * (merkle_path_size + salt_size));
+        let fri_queries = self
+            .fri_layers()
+            .map(|(folding_factor, domain_size)| {
+                let merkle_path_size = (domain_size / folding_factor).ilog2() as usize;
+                num_queries
+                    * (folding_factor * extension_field_size + merkle_path_size * digest_size)
+            })
+            .sum::<usize>();
+        let fri_options = self.options.into_fri_options();
+        let num_remainder_coeffs =
+            fri_options.remainder_size(lde_domain_size) / fri_options.blowup_factor();
+        let pow_nonce = 8;
+
+        commitments
+            + ood_evals
+            + trace_queries
+            + fri_queries
+            + num_remainder_coeffs * extension_field_size
+            + pow_nonce
+    }
+}
+
+/// Security model options are tuned for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SecurityModel {
+    /// See [`SecurityParams::conjectured_security_bits`]
+    #[default]
+    Conjectured,
+    /// See [`SecurityParams::proven_security_bits`]
+    Proven,
+}
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("trace length `{trace_len}` is not a power of two"))]
+    InvalidTraceLength { trace_len: usize },
+    #[snafu(display("no proof options reach {target_bits} bits of security"))]
+    TargetUnreachable { target_bits: u32 },
+}
+
+/// Chooses proof options that reach a target security level.
+///
+/// The number of queries, LDE blowup factor, grinding factor and FRI folding
+/// schedule are chosen to minimize the estimated proof size. See
+/// [`SecurityParams::proof_size`].
+pub struct ProofOptionsTuner<S: Stark> {
+    trace_len: usize,
+    target_bits: u32,
+    model: SecurityModel,
+    max_grinding_factor: u8,
+    max_remainder_coeffs: u8,
+    zero_knowledge: bool,
+    _phantom: PhantomData<S>,
+}
+
+impl<S: Stark> ProofOptionsTuner<S> {
+    pub const fn new(trace_len: usize, target_bits: u32) -> Self {
+        Self {
+            trace_len,
+            target_bits,
+            model: SecurityModel::Conjectured,
+            max_grinding_factor: 16,
+            max_remainder_coeffs: 16,
+            zero_knowledge: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub const fn with_security_model(mut self, model: SecurityModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Sets the largest grinding factor the prover is willing to compute
+    /// proof of work for. Grinding doesn't change the size of a proof so the
+    /// tuner uses the smallest grinding factor needed by the chosen options.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grinding factor exceeds
+    /// [`ProofOptions::MAX_GRINDING_FACTOR`]
+    pub const fn with_max_grinding_factor(mut self, max_grinding_factor: u8) -> Self {
+        assert!(max_grinding_factor <= ProofOptions::MAX_GRINDING_FACTOR);
+        self.max_grinding_factor = max_grinding_factor;
+        self
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `max_remainder_coeffs` is zero
+    pub const fn with_max_remainder_coeffs(mut self, max_remainder_coeffs: u8) -> Self {
+        assert!(max_remainder_coeffs != 0);
+        self.max_remainder_coeffs = max_remainder_coeffs;
+        self
+    }
+
+    pub const fn with_zero_knowledge(mut self, zero_knowledge: bool) -> Self {
+        self.zero_knowledge = zero_knowledge;
+        self
+    }
+
+    /// Returns the options with the smallest estimated proof size that reach
+    /// the target security level
+    pub fn tune(&self) -> Result<ProofOptions, Error> {
+        let trace_len = self.trace_len;
+        if !trace_len.is_power_of_two() {
+            return InvalidTraceLengthSnafu { trace_len }.fail();
+        }
+
+        let mut best: Option<(usize, ProofOptions)> = None;
+        let max_blowup_bits = ProofOptions::MAX_BLOWUP_FACTOR.ilog2();
+        for lde_blowup_factor in (0..=max_blowup_bits).map(|i| 1 << i) {
+            let lde_domain_size = trace_len * usize::from(lde_blowup_factor);
+            if lde_domain_size.ilog2() > S::Fp::TWO_ADICITY {
+                break;
+            }
+            let options = ProofOptions::new(
+                1,
+                lde_blowup_factor,
+                self.max_grinding_factor,
+                2,
+                self.max_remainder_coeffs,
+            )
+            .with_zero_knowledge(self.zero_knowledge);
+            let params = SecurityParams::new::<S>(trace_len, options);
+            // FRI queries are only sound if the codeword has redundancy
+            let ce_domain_size = params.padded_trace_len * params.ce_blowup_factor;
+            if options.fri_blowup_factor() < 2 || ce_domain_size > lde_domain_size {
+                continue;
+            }
+
+            // the options fold by two so each FRI layer is a single fold
+            let num_layers = options.into_fri_options().num_layers(lde_domain_size);
+            let num_folds = u32::try_from(num_layers).unwrap();
+            for schedule in folding_schedules(num_folds) {
+                if schedule.len() > FoldingSchedule::MAX_LEN {
+                    continue;
+                }
+                let options = ProofOptions {
+                    fri_folding_factor: schedule.last().copied().unwrap_or(2),
+                    ..options.with_fri_folding_schedule(&schedule)
+                };
+                debug_assert_eq!(
+                    options.into_fri_options().num_layers(lde_domain_size),
+                    schedule.len()
+                );
+                let params = SecurityParams { options, ..params };
+                let Some(params) = self.min_num_queries(params, lde_domain_size) else {
+                    continue;
+                };
+                let proof_size = params.proof_size::<S>();
+                // `Option::is_none_or` isn't available on older toolchains
+                #[allow(clippy::unnecessary_map_or)]
+                let is_smaller = best.map_or(true, |(best_size, _)| proof_size < best_size);
+                if is_smaller {
+                    best = Some((proof_size, params.options));
+                }
+            }
+        }
+
+        let (_, options) = best.ok_or(Error::TargetUnreachable {
+            target_bits: self.target_bits,
+        })?;
+        let params = SecurityParams::new::<S>(trace_len, options);
+        Ok(self.min_grinding_factor(params).options)
+    }
+
+    fn security_bits(&self, params: &SecurityParams) -> u32 {
+        match self.model {
+            SecurityModel::Conjectured => params.conjectured_security_bits(),
+            SecurityModel::Proven => params.proven_security_bits(),
+        }
+    }
+
+    /// Returns the parameters with the fewest queries that reach the target
+    /// security level. Security increases with the number of queries.
+    #[allow(clippy::cast_possible_truncation)]
+    fn min_num_queries(
+        &self,
+        mut params: SecurityParams,
+        lde_domain_size: usize,
+    ) -> Option<SecurityParams> {
+        let mut max_num_queries = lde_domain_size.min(ProofOptions::MAX_NUM_QUERIES.into());
+        if params.options.zero_knowledge {
+            // randomizers only hide up to `trace_len` openings. See `Air::new`
+            let max_hidden_queries = params.trace_len.saturating_sub(params.num_trace_openings);
+            max_num_queries = max_num_queries.min(max_hidden_queries);
+        }
+        let max_num_queries = max_num_queries as u8;
+        if max_num_queries < ProofOptions::MIN_NUM_QUERIES {
+            return None;
+        }
+        let (mut lo, mut hi) = (ProofOptions::MIN_NUM_QUERIES, max_num_queries);
+        params.options.num_queries = hi;
+        if self.security_bits(&params) < self.target_bits {
+            return None;
+        }
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            params.options.num_queries = mid;
+            if self.security_bits(&params) < self.target_bits {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        params.options.num_queries = hi;
+        Some(params)
+    }
+
+    /// Returns the parameters with the smallest grinding factor that still
+    /// reaches the target security level
+    fn min_grinding_factor(&self, mut params: SecurityParams) -> SecurityParams {
+        while params.options.grinding_factor > 0 {
+            let mut reduced = params;
+            reduced.options.grinding_factor -= 1;
+            if self.security_bits(&reduced) < self.target_bits {
+                break;
+            }
+            params = reduced;
+        }
+        params
+    }
+}
+
+/// Returns all folding schedules with non-increasing folding factors that
+/// fold a domain by `2^num_folds`
+fn folding_schedules(num_folds: u32) -> Vec<Vec<u8>> {
+    fn extend(schedule: &mut Vec<u8>, num_folds: u32, max_bits: u32, out: &mut Vec<Vec<u8>>) {
+        if num_folds == 0 {
+            out.push(schedule.clone());
+            return;
+        }
+        for bits in (1..=max_bits.min(num_folds)).rev() {
+            schedule.push(1 << bits);
+            extend(schedule, num_folds - bits, bits, out);
+            schedule.pop();
+        }
+    }
+
+    let mut schedules = Vec::new();
+    // the largest supported folding factor is 16
+    extend(&mut Vec::new(), num_folds, 4, &mut schedules);
+    schedules
+}
@@ -0,0 +1,73 @@
+//! Row selectors that restrict constraints to rows of the execution trace
+//!
+//! A constraint that must hold at a set of rows is divided by the vanishing
+//! polynomial of those rows. Each selector is that quotient so constraints are
+//! built by multiplying e.g. `(Cycle.curr() - one) * first_row()`.
+//!
+//! Rows are points of the trace domain where row `i` is `g^i`.
+use crate::constraints::AlgebraicItem;
+use crate::expression::Expr;
+use crate::utils::FieldVariant;
+use ark_ff::FftField;
+use ark_ff::Field;
+use ark_poly::EvaluationDomain;
+use ark_poly::Radix2EvaluationDomain;
+use num_traits::Pow;
+
+/// Selects the first row i.e. `1 / (x - 1)`
+pub fn first_row<Fp: FftField, Fq: Field>() -> Expr<AlgebraicItem<FieldVariant<Fp, Fq>>> {
+    let one = AlgebraicItem::Constant(FieldVariant::Fp(Fp::one()));
+    Expr::from(one) / (AlgebraicItem::X - one)
+}
+
+/// Selects the last row i.e. `1 / (x - g^(n-1))`
+pub fn last_row<Fp: FftField, Fq: Field>(
+    trace_len: usize,
+) -> Expr<AlgebraicItem<FieldVariant<Fp, Fq>>> {
+    let one = AlgebraicItem::Constant(FieldVariant::Fp(Fp::one()));
+    Expr::from(one) / (AlgebraicItem::X - last_trace_x(trace_len))
+}
+
+/// Selects every row i.e. `1 / (x^n - 1)`
+pub fn every_row<Fp: FftField, Fq: Field>(
+    trace_len: usize,
+) -> Expr<AlgebraicItem<FieldVariant<Fp, Fq>>> {
+    periodic_rows(trace_len, 1, 0)
+}
+
+/// Selects every row except the last i.e. `(x - g^(n-1)) / (x^n - 1)`.
+/// Used for transition constraints that reference the next row.
+pub fn every_row_except_last<Fp: FftField, Fq: Field>(
+    trace_len: usize,
+) -> Expr<AlgebraicItem<FieldVariant<Fp, Fq>>> {
+    (AlgebraicItem::X - last_trace_x(trace_len)) * every_row(trace_len)
+}
+
+/// Selects every `interval_size`-th row starting from row `offset` i.e. the
+/// rows `offset + k*interval_size`. These rows are the roots of
+/// `x^(n/interval_size) - g^(offset*n/interval_size)`.
+///
+/// # Panics
+///
+/// Panics if `interval_size` is not a power of two, `interval_size` is larger
+/// than the trace or `offset` is not less than `interval_size`
+pub fn periodic_rows<Fp: FftField, Fq: Field>(
+    trace_len: usize,
+    interval_size: usize,
+    offset: usize,
+) -> Expr<AlgebraicItem<FieldVariant<Fp, Fq>>> {
+    use AlgebraicItem::*;
+    assert!(interval_size.is_power_of_two());
+    assert!(interval_size <= trace_len);
+    assert!(offset < interval_size);
+    let trace_xs = Radix2EvaluationDomain::<Fp>::new(trace_len).unwrap();
+    let num_rows = trace_len / interval_size;
+    let shift = Constant(FieldVariant::Fp(trace_xs.element(offset * num_rows)));
+    let one = Constant(FieldVariant::Fp(Fp::one()));
+    Expr::from(one) / (X.pow(num_rows) - shift)
+}
+
+fn last_trace_x<Fp: FftField, Fq: Field>(trace_len: usize) -> AlgebraicItem<FieldVariant<Fp, Fq>> {
+    let trace_xs = Radix2EvaluationDomain::<Fp>::new(trace_len).unwrap();
+    AlgebraicItem::Constant(FieldVariant::Fp(trace_xs.element(trace_len - 1)))
+}
@@ -1,16 +1,22 @@
+use crate::air::extension_segment_widths;
 use crate::air::AirConfig;
 use crate::challenges::Challenges;
 use crate::channel::VerifierChannelArtifacts;
 use crate::composer::DeepCompositionCoeffs;
 use crate::debug::default_validate_constraints;
+use crate::debug::ConstraintError;
 use crate::hash::Digest;
+use crate::hash::HashFn;
 use crate::hints::Hints;
+use crate::merkle::Commitment;
 use crate::merkle::MatrixMerkleTree;
 use crate::merkle::MerkleTree;
 use crate::prover::default_prove;
 use crate::prover::ProvingError;
 use crate::random::draw_multiple;
 use crate::random::PublicCoin;
+use crate::telemetry::NoopObserver;
+use crate::telemetry::ProverObserver;
 use crate::verifier::default_verify;
 use crate::verifier::VerificationError;
 use crate::Air;
@@ -20,14 +26,18 @@ use crate::ProofOptions;
 use crate::StarkExtensionOf;
 use crate::Trace;
 use ark_ff::FftField;
+use ark_serialize::CanonicalSerialize;
 use ministark_gpu::GpuFftField;
 
+/// Domain separator for the seed of the default public coin
+const PUBLIC_COIN_DOMAIN_SEPARATOR: &[u8] = b"ministark-public-coin-v1";
+
 pub trait Stark: Sized + Send + Sync {
     type Fp: GpuFftField + FftField;
     type Fq: StarkExtensionOf<Self::Fp>;
     type AirConfig: AirConfig<Fp = Self::Fp, Fq = Self::Fq>;
     type PublicCoin: PublicCoin<Digest = Self::Digest, Field = Self::Fq>;
-    type MerkleTree: MerkleTree<Root = Self::Digest>
+    type MerkleTree: MerkleTree<Root: Commitment<Digest = Self::Digest>>
         + MatrixMerkleTree<Self::Fp>
         + MatrixMerkleTree<Self::Fq>;
     type Trace: Trace<Fp = Self::Fp, Fq = Self::Fq>;
@@ -36,7 +46,32 @@ pub trait Stark: Sized + Send + Sync {
 
     fn get_public_inputs(&self) -> <Self::AirConfig as AirConfig>::PublicInputs;
 
-    fn gen_public_coin(&self, air: &Air<Self::AirConfig>) -> Self::PublicCoin;
+    /// Seeds the public coin with everything the proof is about. The public
+    /// inputs, proof options, trace dimensions and constraint system are all
+    /// bound to the Fiat-Shamir transcript so a proof can't be replayed
+    /// against a different statement.
+    fn gen_public_coin(&self, air: &Air<Self::AirConfig>) -> Self::PublicCoin {
+        let mut seed = PUBLIC_COIN_DOMAIN_SEPARATOR.to_vec();
+        air.public_inputs().serialize_compressed(&mut seed).unwrap();
+        air.options().serialize_compressed(&mut seed).unwrap();
+        air.trace_len().serialize_compressed(&mut seed).unwrap();
+        let num_base_columns = <Self::AirConfig as AirConfig>::NUM_BASE_COLUMNS;
+        let num_extension_columns = <Self::AirConfig as AirConfig>::NUM_EXTENSION_COLUMNS;
+        num_base_columns.serialize_compressed(&mut seed).unwrap();
+        num_extension_columns
+            .serialize_compressed(&mut seed)
+            .unwrap();
+        let extension_segment_widths = extension_segment_widths::<Self::AirConfig>();
+        extension_segment_widths
+            .serialize_compressed(&mut seed)
+            .unwrap();
+        let constraints_hash = air
+            .composition_constraint()
+            .canonical_hash::<<Self::PublicCoin as PublicCoin>::HashFn>();
+        constraints_hash.serialize_compressed(&mut seed).unwrap();
+        let digest = <Self::PublicCoin as PublicCoin>::HashFn::hash_chunks([&*seed]);
+        Self::PublicCoin::new(digest)
+    }
 
     fn gen_deep_coeffs(
         &self,
@@ -44,7 +79,7 @@ pub trait Stark: Sized + Send + Sync {
         air: &Air<Self::AirConfig>,
     ) -> DeepCompositionCoeffs<Self::Fq> {
         let num_execution_trace = air.trace_arguments().len();
-        let num_composition_trace = air.ce_blowup_factor();
+        let num_composition_trace = air.num_composition_segments();
         DeepCompositionCoeffs {
             execution_trace: draw_multiple(public_coin, num_execution_trace),
             composition_trace: draw_multiple(public_coin, num_composition_trace),
@@ -54,6 +89,12 @@ pub trait Stark: Sized + Send + Sync {
 
     fn generate_trace(&self, witness: Self::Witness) -> Self::Trace;
 
+    /// Returns the observer that receives events during proof generation.
+    /// Defaults to an observer that discards all events.
+    fn observer(&self) -> &dyn ProverObserver {
+        &NoopObserver
+    }
+
     async fn prove(
         &self,
         options: ProofOptions,
@@ -69,9 +110,8 @@ pub trait Stark: Sized + Send + Sync {
         hints: &Hints<Self::Fq>,
         base_trace: &Matrix<Self::Fp>,
         extension_trace: Option<&Matrix<Self::Fq>>,
-    ) {
-        // TODO: this is unfinished
-        default_validate_constraints(self, challenges, hints, base_trace, extension_trace);
+    ) -> Result<(), ConstraintError> {
+        default_validate_constraints(self, challenges, hints, base_trace, extension_trace)
     }
 
     #[allow(clippy::too_many_lines)]
@@ -0,0 +1,393 @@
+//! Tampers with valid proofs to check every part of a proof is verified
+//!
+//! Enabled with the `test-utils` feature. The fib and brainfuck examples run
+//! [`check_tampered_proofs`] in their tests. The rescue example isn't covered
+//! yet: it doesn't define AIR constraints so it can't generate proofs.
+
+use crate::air::AirConfig;
+use crate::fri::FoldingSchedule;
+use crate::fri::RemainderCommitment;
+use crate::merkle::CompactMerkleProof;
+use crate::merkle::MatrixMerkleProof;
+use crate::merkle::MerkleTree;
+use crate::merkle::MerkleView;
+use crate::stark::Stark;
+use crate::verifier::VerificationError;
+use crate::Air;
+use crate::Proof;
+use crate::ProofOptions;
+use alloc::vec::Vec;
+use ark_ff::Field;
+use core::fmt::Display;
+
+/// Merkle proofs whose digests can be tampered with
+pub trait MerkleProofDigests {
+    type Digest: crate::hash::Digest;
+
+    /// Returns all digests in the proof
+    fn digests_mut(&mut self) -> Vec<&mut Self::Digest>;
+}
+
+impl<D: crate::hash::Digest> MerkleProofDigests for MerkleView<D, D> {
+    type Digest = D;
+
+    fn digests_mut(&mut self) -> Vec<&mut D> {
+        let leaves = self
+            .initial_leaves
+            .iter_mut()
+            .chain(&mut self.sibling_leaves);
+        leaves.chain(&mut self.nodes).collect()
+    }
+}
+
+impl<D: crate::hash::Digest> MerkleProofDigests for MatrixMerkleProof<D> {
+    type Digest = D;
+
+    fn digests_mut(&mut self) -> Vec<&mut D> {
+        let mut digests = self.view.digests_mut();
+        digests.extend(&mut self.salts);
+        digests
+    }
+}
+
+impl<D: crate::hash::Digest> MerkleProofDigests for CompactMerkleProof<D> {
+    type Digest = D;
+
+    fn digests_mut(&mut self) -> Vec<&mut D> {
+        let digests = self.leaves.iter_mut().chain(&mut self.nodes);
+        digests.chain(&mut self.salts).collect()
+    }
+}
+
+/// Tampers with each component of a valid proof one at a time and checks that
+/// [`Stark::verify`] rejects every tampered proof with the expected error.
+///
+/// Covers the commitments, out-of-domain evaluations, queried trace values,
+/// every digest of every merkle proof, FRI layer values and remainder
+/// coefficients, the proof of work nonce, the trace length and the options.
+/// The nonce is only checked if the proof has a non-zero grinding factor.
+///
+/// # Panics
+///
+/// Panics if the proof is invalid or if a tampered proof is accepted or
+/// rejected with an unexpected error.
+#[allow(clippy::too_many_lines)]
+pub fn check_tampered_proofs<S: Stark>(stark: &S, proof: &Proof<S>)
+where
+    <S::MerkleTree as MerkleTree>::Proof: MerkleProofDigests,
+{
+    use VerificationError::*;
+
+    if let Err(err) = stark.verify(proof.clone(), 0) {
+        panic!("proof must be valid but verification failed: {err}");
+    }
+
+    let tamperer = Tamperer { stark, proof };
+
+    // commitments change all verifier randomness
+    tamperer.check(
+        "base trace commitment",
+        || vec![InconsistentOodConstraintEvaluations],
+        |p| {
+            p.base_trace_commitment = Default::default();
+        },
+    );
+    for i in 0..proof.extension_trace_commitments.len() {
+        tamperer.check(
+            format_args!("extension trace commitment {i}"),
+            || vec![InconsistentOodConstraintEvaluations],
+            |p| p.extension_trace_commitments[i] = Default::default(),
+        );
+    }
+    tamperer.check(
+        "composition trace commitment",
+        || vec![InconsistentOodConstraintEvaluations],
+        |p| p.composition_trace_commitment = Default::default(),
+    );
+
+    for i in 0..proof.execution_trace_ood_evals.len() {
+        tamperer.check(
+            format_args!("execution trace ood evaluation {i}"),
+            || vec![InconsistentOodConstraintEvaluations],
+            |p| p.execution_trace_ood_evals[i] += S::Fq::ONE,
+        );
+    }
+    for i in 0..proof.composition_trace_ood_evals.len() {
+        tamperer.check(
+            format_args!("composition trace ood evaluation {i}"),
+            || vec![InconsistentOodConstraintEvaluations],
+            |p| p.composition_trace_ood_evals[i] += S::Fq::ONE,
+        );
+    }
+
+    // changes to the transcript after the out-of-domain point is drawn change the
+    // query positions unless they are caught by the proof of work
+    let query_errors = || {
+        let mut errors = vec![BaseTraceQueryDoesNotMatchCommitment];
+        if proof.options.grinding_factor != 0 {
+            errors.push(FriProofOfWork);
+        }
+        errors
+    };
+    for (layer, layer_proof) in proof.fri_proof.layers.iter().enumerate() {
+        let layer_error =
+            || vec![crate::fri::VerificationError::LayerCommitmentInvalid { layer }.into()];
+        tamperer.check(
+            format_args!("fri layer {layer} commitment"),
+            query_errors,
+            |p| {
+                p.fri_proof.layers[layer].commitment = Default::default();
+            },
+        );
+        for i in 0..layer_proof.flattenend_rows.len() {
+            tamperer.check(
+                format_args!("fri layer {layer} value {i}"),
+                layer_error,
+                |p| {
+                    p.fri_proof.layers[layer].flattenend_rows[i] += S::Fq::ONE;
+                },
+            );
+        }
+        for i in 0..num_digests(&layer_proof.merkle_proof) {
+            tamperer.check(
+                format_args!("fri layer {layer} merkle digest {i}"),
+                layer_error,
+                |p| {
+                    tamper_digest(&mut p.fri_proof.layers[layer].merkle_proof, i);
+                },
+            );
+        }
+    }
+    // a committed remainder codeword keeps the coefficients out of the transcript
+    let remainder_errors = || match proof.fri_proof.remainder_commitment {
+        Some(_) => vec![crate::fri::VerificationError::RemainderCommitmentInvalid.into()],
+        None => query_errors(),
+    };
+    if proof.fri_proof.remainder_commitment.is_some() {
+        tamperer.check("fri remainder commitment", query_errors, |p| {
+            p.fri_proof.remainder_commitment = Some(Default::default());
+        });
+    }
+    for i in 0..proof.fri_proof.remainder_coeffs.len() {
+        tamperer.check(
+            format_args!("fri remainder coefficient {i}"),
+            remainder_errors,
+            |p| {
+                p.fri_proof.remainder_coeffs[i] += S::Fq::ONE;
+            },
+        );
+    }
+    if proof.options.grinding_factor != 0 {
+        tamperer.check("proof of work nonce", query_errors, |p| {
+            p.pow_nonce = p.pow_nonce.wrapping_add(1);
+        });
+    }
+
+    let queries = &proof.trace_queries;
+    for i in 0..queries.base_trace_values.len() {
+        tamperer.check(
+            format_args!("base trace value {i}"),
+            || vec![BaseTraceQueryDoesNotMatchCommitment],
+            |p| p.trace_queries.base_trace_values[i] += S::Fp::ONE,
+        );
+    }
+    for (segment, values) in queries.extension_trace_values.iter().enumerate() {
+        for i in 0..values.len() {
+            tamperer.check(
+                format_args!("extension trace segment {segment} value {i}"),
+                || vec![ExtensionTraceQueryDoesNotMatchCommitment],
+                |p| p.trace_queries.extension_trace_values[segment][i] += S::Fq::ONE,
+            );
+        }
+    }
+    for i in 0..queries.composition_trace_values.len() {
+        tamperer.check(
+            format_args!("composition trace value {i}"),
+            || vec![CompositionTraceQueryDoesNotMatchCommitment],
+            |p| p.trace_queries.composition_trace_values[i] += S::Fq::ONE,
+        );
+    }
+    for i in 0..num_digests(&queries.base_trace_proof) {
+        tamperer.check(
+            format_args!("base trace merkle digest {i}"),
+            || vec![BaseTraceQueryDoesNotMatchCommitment],
+            |p| tamper_digest(&mut p.trace_queries.base_trace_proof, i),
+        );
+    }
+    for (segment, merkle_proof) in queries.extension_trace_proofs.iter().enumerate() {
+        for i in 0..num_digests(merkle_proof) {
+            tamperer.check(
+                format_args!("extension trace segment {segment} merkle digest {i}"),
+                || vec![ExtensionTraceQueryDoesNotMatchCommitment],
+                |p| tamper_digest(&mut p.trace_queries.extension_trace_proofs[segment], i),
+            );
+        }
+    }
+    for i in 0..num_digests(&queries.composition_trace_proof) {
+        tamperer.check(
+            format_args!("composition trace merkle digest {i}"),
+            || vec![CompositionTraceQueryDoesNotMatchCommitment],
+            |p| tamper_digest(&mut p.trace_queries.composition_trace_proof, i),
+        );
+    }
+
+    // the trace length and options are part of the statement
+    for trace_len in [proof.trace_len * 2, proof.trace_len / 2] {
+        tamperer.check_statement(format_args!("trace length {trace_len}"), |p| {
+            p.trace_len = trace_len;
+        });
+    }
+    for (name, options) in tampered_options(proof.options) {
+        tamperer.check_statement(format_args!("option {name}"), |p| p.options = options);
+    }
+}
+
+/// Returns options that each differ from `options` in a single field
+fn tampered_options(options: ProofOptions) -> Vec<(&'static str, ProofOptions)> {
+    let ProofOptions {
+        num_queries,
+        lde_blowup_factor,
+        grinding_factor,
+        fri_folding_factor,
+        fri_folding_schedule,
+        fri_max_remainder_coeffs,
+        fri_remainder_commitment,
+        zero_knowledge,
+    } = options;
+    vec![
+        (
+            "num_queries",
+            ProofOptions {
+                num_queries: num_queries.saturating_add(1),
+                ..options
+            },
+        ),
+        (
+            "lde_blowup_factor",
+            ProofOptions {
+                lde_blowup_factor: lde_blowup_factor.saturating_mul(2),
+                ..options
+            },
+        ),
+        (
+            "grinding_factor",
+            ProofOptions {
+                grinding_factor: grinding_factor.saturating_add(1),
+                ..options
+            },
+        ),
+        (
+            "fri_folding_factor",
+            ProofOptions {
+                fri_folding_factor: if fri_folding_factor == 2 { 4 } else { 2 },
+                ..options
+            },
+        ),
+        (
+            "fri_folding_schedule",
+            ProofOptions {
+                fri_folding_schedule: if fri_folding_schedule.is_empty() {
+                    FoldingSchedule::new(&[2])
+                } else {
+                    FoldingSchedule::default()
+                },
+                ..options
+            },
+        ),
+        (
+            "fri_max_remainder_coeffs",
+            ProofOptions {
+                fri_max_remainder_coeffs: fri_max_remainder_coeffs.saturating_mul(2),
+                ..options
+            },
+        ),
+        (
+            "fri_remainder_commitment",
+            ProofOptions {
+                fri_remainder_commitment: match fri_remainder_commitment {
+                    RemainderCommitment::Coefficients => RemainderCommitment::Codeword,
+                    RemainderCommitment::Codeword => RemainderCommitment::Coefficients,
+                },
+                ..options
+            },
+        ),
+        (
+            "zero_knowledge",
+            ProofOptions {
+                zero_knowledge: !zero_knowledge,
+                ..options
+            },
+        ),
+    ]
+}
+
+fn num_digests<P: MerkleProofDigests + Clone>(merkle_proof: &P) -> usize {
+    merkle_proof.clone().digests_mut().len()
+}
+
+/// Replaces the `i`th digest of a merkle proof with the default digest
+fn tamper_digest<P: MerkleProofDigests>(merkle_proof: &mut P, i: usize) {
+    let digest = merkle_proof.digests_mut().swap_remove(i);
+    let tampered = P::Digest::default();
+    assert!(
+        *digest != tampered,
+        "merkle proof contains the default digest"
+    );
+    *digest = tampered;
+}
+
+struct Tamperer<'a, S: Stark> {
+    stark: &'a S,
+    proof: &'a Proof<S>,
+}
+
+impl<S: Stark> Tamperer<'_, S> {
+    /// Tampers with a copy of the proof and checks the verifier rejects it with
+    /// one of the expected errors
+    fn check(
+        &self,
+        component: impl Display,
+        expected: impl FnOnce() -> Vec<VerificationError>,
+        tamper: impl FnOnce(&mut Proof<S>),
+    ) {
+        let mut proof = self.proof.clone();
+        tamper(&mut proof);
+        self.check_rejected(component, &expected(), proof);
+    }
+
+    /// Changes the statement the proof is for. Statements that describe
+    /// differently shaped proofs are rejected as malformed. Otherwise the
+    /// verifier's randomness changes which is caught out of domain.
+    fn check_statement(&self, component: impl Display, tamper: impl FnOnce(&mut Proof<S>)) {
+        let mut proof = self.proof.clone();
+        tamper(&mut proof);
+        let expected = proof
+            .validate()
+            .map_err(VerificationError::from)
+            .and_then(|()| {
+                let public_inputs = self.stark.get_public_inputs();
+                let air = Air::<S::AirConfig>::new(proof.trace_len, public_inputs, proof.options)?;
+                Ok(proof.validate_with_air(&air)?)
+            });
+        let expected = expected
+            .err()
+            .unwrap_or(VerificationError::InconsistentOodConstraintEvaluations);
+        self.check_rejected(component, &[expected], proof);
+    }
+
+    fn check_rejected(
+        &self,
+        component: impl Display,
+        expected: &[VerificationError],
+        proof: Proof<S>,
+    ) {
+        let air = <S::AirConfig as AirConfig>::NAME;
+        match self.stark.verify(proof, 0) {
+            Ok(_) => panic!("{air} verifier accepted a proof with a tampered {component}"),
+            Err(err) => assert!(
+                expected.contains(&err),
+                "{air} verifier rejected a proof with a tampered {component} with {err:?} but expected one of {expected:?}"
+            ),
+        }
+    }
+}
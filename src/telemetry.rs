@@ -0,0 +1,226 @@
+//! Hooks for observing proof generation
+
+use crate::utils::gpu_peak_allocated_bytes;
+use core::time::Duration;
+use std::time::Instant;
+
+/// Phases of proof generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    TraceGeneration,
+    AirInit,
+    BaseTraceCommitment,
+    ExtensionTraceCommitment,
+    ConstraintEvaluation,
+    CompositionTraceCommitment,
+    DeepComposition,
+    Fri,
+    ProofOfWork,
+    Queries,
+}
+
+impl Phase {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::TraceGeneration => "trace_generation",
+            Self::AirInit => "air_init",
+            Self::BaseTraceCommitment => "base_trace_commitment",
+            Self::ExtensionTraceCommitment => "extension_trace_commitment",
+            Self::ConstraintEvaluation => "constraint_evaluation",
+            Self::CompositionTraceCommitment => "composition_trace_commitment",
+            Self::DeepComposition => "deep_composition",
+            Self::Fri => "fri",
+            Self::ProofOfWork => "proof_of_work",
+            Self::Queries => "queries",
+        }
+    }
+}
+
+/// Events emitted by the prover
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    PhaseStart(Phase),
+    PhaseEnd {
+        phase: Phase,
+        elapsed: Duration,
+    },
+    /// Dimensions of the execution trace
+    TraceDimensions {
+        trace_len: usize,
+        num_base_columns: usize,
+        num_extension_columns: usize,
+    },
+    /// Sizes of the constraint evaluation and low degree extension domains
+    DomainSizes {
+        ce_domain_size: usize,
+        lde_domain_size: usize,
+    },
+    /// Number of AIR constraints and the number of composition coefficients
+    Constraints {
+        num_constraints: usize,
+        num_composition_coeffs: usize,
+    },
+    /// Peak number of bytes held by [`GpuAllocator`] since proving started.
+    /// Emitted at the end of every phase. Note that allocations are tracked
+    /// process wide so concurrent provers will observe each other's usage.
+    ///
+    /// [`GpuAllocator`]: crate::utils::GpuAllocator
+    MemoryHighWaterMark {
+        phase: Phase,
+        bytes: usize,
+    },
+}
+
+/// Receives events during proof generation
+pub trait ProverObserver: Send + Sync {
+    fn on_event(&self, event: &Event);
+}
+
+/// Observer that discards all events
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopObserver;
+
+impl ProverObserver for NoopObserver {
+    fn on_event(&self, _: &Event) {}
+}
+
+/// Emits [`Event::PhaseStart`] when created and [`Event::PhaseEnd`] followed by
+/// [`Event::MemoryHighWaterMark`] when dropped
+pub(crate) struct PhaseGuard<'a> {
+    observer: &'a dyn ProverObserver,
+    phase: Phase,
+    start: Instant,
+}
+
+impl<'a> PhaseGuard<'a> {
+    pub fn new(observer: &'a dyn ProverObserver, phase: Phase) -> Self {
+        observer.on_event(&Event::PhaseStart(phase));
+        Self {
+            observer,
+            phase,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for PhaseGuard<'_> {
+    fn drop(&mut self) {
+        let phase = self.phase;
+        let elapsed = self.start.elapsed();
+        self.observer.on_event(&Event::PhaseEnd { phase, elapsed });
+        let bytes = gpu_peak_allocated_bytes();
+        self.observer
+            .on_event(&Event::MemoryHighWaterMark { phase, bytes });
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub use tracing_observer::TracingObserver;
+
+#[cfg(feature = "tracing")]
+mod tracing_observer {
+    use super::Event;
+    use super::Phase;
+    use super::ProverObserver;
+    use alloc::vec::Vec;
+    use std::sync::Mutex;
+    use tracing::Span;
+
+    /// Observer that forwards events to [`tracing`]. Each phase is recorded as
+    /// a span and all other events are recorded as events within the current
+    /// phase's span.
+    #[derive(Debug)]
+    pub struct TracingObserver {
+        spans: Mutex<Vec<(Phase, Span)>>,
+        parent: Span,
+    }
+
+    impl TracingObserver {
+        /// Creates an observer that records phase spans as children of the
+        /// current span
+        pub fn new() -> Self {
+            Self {
+                spans: Mutex::new(Vec::new()),
+                parent: Span::current(),
+            }
+        }
+
+        fn current_span(&self) -> Span {
+            let spans = self.spans.lock().unwrap();
+            spans
+                .last()
+                .map_or_else(|| self.parent.clone(), |(_, span)| span.clone())
+        }
+    }
+
+    impl Default for TracingObserver {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl ProverObserver for TracingObserver {
+        fn on_event(&self, event: &Event) {
+            match *event {
+                Event::PhaseStart(phase) => {
+                    let parent = self.current_span();
+                    let span = tracing::info_span!(parent: &parent, "phase", name = phase.name());
+                    self.spans.lock().unwrap().push((phase, span));
+                }
+                Event::PhaseEnd { phase, elapsed } => {
+                    let mut spans = self.spans.lock().unwrap();
+                    let i = spans.iter().rposition(|(p, _)| *p == phase);
+                    let span = i.map(|i| spans.remove(i).1);
+                    drop(spans);
+                    if let Some(span) = span {
+                        let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+                        tracing::info!(parent: &span, elapsed_ms, "phase complete");
+                    }
+                }
+                Event::TraceDimensions {
+                    trace_len,
+                    num_base_columns,
+                    num_extension_columns,
+                } => {
+                    tracing::info!(
+                        parent: &self.current_span(),
+                        trace_len,
+                        num_base_columns,
+                        num_extension_columns,
+                        "trace dimensions"
+                    );
+                }
+                Event::DomainSizes {
+                    ce_domain_size,
+                    lde_domain_size,
+                } => {
+                    tracing::info!(
+                        parent: &self.current_span(),
+                        ce_domain_size,
+                        lde_domain_size,
+                        "domain sizes"
+                    );
+                }
+                Event::Constraints {
+                    num_constraints,
+                    num_composition_coeffs,
+                } => {
+                    tracing::info!(
+                        parent: &self.current_span(),
+                        num_constraints,
+                        num_composition_coeffs,
+                        "constraints"
+                    );
+                }
+                Event::MemoryHighWaterMark { phase, bytes } => {
+                    tracing::info!(
+                        parent: &self.current_span(),
+                        phase = phase.name(),
+                        bytes,
+                        "memory high-water mark"
+                    );
+                }
+            }
+        }
+    }
+}
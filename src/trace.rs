@@ -1,4 +1,7 @@
+use crate::air::extension_segment_widths;
+use crate::air::AirConfig;
 use crate::challenges::Challenges;
+use crate::merkle;
 use crate::merkle::MatrixMerkleTree;
 use crate::merkle::MerkleTree;
 use crate::stark::Stark;
@@ -9,6 +12,7 @@ use ark_ff::Field;
 use ark_serialize::CanonicalDeserialize;
 use ark_serialize::CanonicalSerialize;
 use ark_serialize::Valid;
+use core::iter::zip;
 
 /// STARK execution trace
 #[allow(clippy::len_without_is_empty)]
@@ -33,14 +37,32 @@ pub trait Trace: Send + Sync {
     ) -> Option<Matrix<Self::Fq>> {
         None
     }
+
+    /// Builds and returns the columns of the extension trace segment at index
+    /// `segment`. `challenges` are all challenges drawn so far and `segments`
+    /// are the previously built segments.
+    /// Defaults to [`Trace::build_extension_columns`] for the first segment.
+    fn build_extension_segment(
+        &self,
+        segment: usize,
+        challenges: &Challenges<Self::Fq>,
+        _segments: &[Matrix<Self::Fq>],
+    ) -> Option<Matrix<Self::Fq>> {
+        if segment == 0 {
+            self.build_extension_columns(challenges)
+        } else {
+            None
+        }
+    }
 }
 
 pub struct Queries<C: Stark> {
     pub base_trace_values: Vec<C::Fp>,
-    pub extension_trace_values: Vec<C::Fq>,
+    /// Queried rows of each extension trace segment
+    pub extension_trace_values: Vec<Vec<C::Fq>>,
     pub composition_trace_values: Vec<C::Fq>,
     pub base_trace_proof: <C::MerkleTree as MerkleTree>::Proof,
-    pub extension_trace_proof: Option<<C::MerkleTree as MerkleTree>::Proof>,
+    pub extension_trace_proofs: Vec<<C::MerkleTree as MerkleTree>::Proof>,
     pub composition_trace_proof: <C::MerkleTree as MerkleTree>::Proof,
 }
 
@@ -58,7 +80,7 @@ impl<C: Stark> CanonicalSerialize for Queries<C> {
             .serialize_with_mode(&mut writer, compress)?;
         self.base_trace_proof
             .serialize_with_mode(&mut writer, compress)?;
-        self.extension_trace_proof
+        self.extension_trace_proofs
             .serialize_with_mode(&mut writer, compress)?;
         self.composition_trace_proof
             .serialize_with_mode(&mut writer, compress)?;
@@ -70,14 +92,29 @@ impl<C: Stark> CanonicalSerialize for Queries<C> {
             + self.extension_trace_values.serialized_size(compress)
             + self.composition_trace_values.serialized_size(compress)
             + self.base_trace_proof.serialized_size(compress)
-            + self.extension_trace_proof.serialized_size(compress)
+            + self.extension_trace_proofs.serialized_size(compress)
             + self.composition_trace_proof.serialized_size(compress)
     }
 }
 
 impl<C: Stark> Valid for Queries<C> {
     fn check(&self) -> Result<(), ark_serialize::SerializationError> {
-        Ok(())
+        let num_base_columns = C::AirConfig::NUM_BASE_COLUMNS;
+        let segment_widths = extension_segment_widths::<C::AirConfig>();
+        let is_rows = |len: usize, num_columns: usize| {
+            len.checked_rem(num_columns)
+                .map_or(len == 0, |remainder| remainder == 0)
+        };
+        if is_rows(self.base_trace_values.len(), num_base_columns)
+            && self.extension_trace_values.len() == segment_widths.len()
+            && zip(&self.extension_trace_values, &segment_widths)
+                .all(|(values, &num_columns)| is_rows(values.len(), num_columns))
+            && self.extension_trace_proofs.len() == segment_widths.len()
+        {
+            Ok(())
+        } else {
+            Err(ark_serialize::SerializationError::InvalidData)
+        }
     }
 }
 
@@ -92,7 +129,7 @@ impl<C: Stark> CanonicalDeserialize for Queries<C> {
             extension_trace_values: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
             composition_trace_values: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
             base_trace_proof: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
-            extension_trace_proof: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
+            extension_trace_proofs: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
             composition_trace_proof: <_>::deserialize_with_mode(&mut reader, compress, validate)?,
         })
     }
@@ -105,54 +142,56 @@ impl<C: Stark> Clone for Queries<C> {
             extension_trace_values: self.extension_trace_values.clone(),
             composition_trace_values: self.composition_trace_values.clone(),
             base_trace_proof: self.base_trace_proof.clone(),
-            extension_trace_proof: self.extension_trace_proof.clone(),
+            extension_trace_proofs: self.extension_trace_proofs.clone(),
             composition_trace_proof: self.composition_trace_proof.clone(),
         }
     }
 }
 
 impl<C: Stark> Queries<C> {
+    /// `extension_trace_ldes` and `extension_trees` hold the LDE and merkle
+    /// tree of each extension trace segment
     pub fn new(
         base_trace_lde: &Matrix<C::Fp>,
-        extension_trace_lde: Option<&Matrix<C::Fq>>,
+        extension_trace_ldes: &[Matrix<C::Fq>],
         composition_trace_lde: &Matrix<C::Fq>,
         base_tree: &C::MerkleTree,
-        extension_tree: Option<&C::MerkleTree>,
+        extension_trees: &[C::MerkleTree],
         composition_tree: &C::MerkleTree,
         positions: &[usize],
-    ) -> Self {
-        let base_trace_proof = MatrixMerkleTree::<C::Fp>::prove_rows(base_tree, positions).unwrap();
-        let extension_trace_proof = extension_tree.map(|extension_tree| {
-            MatrixMerkleTree::<C::Fq>::prove_rows(extension_tree, positions).unwrap()
-        });
+    ) -> Result<Self, merkle::Error> {
+        let base_trace_proof = MatrixMerkleTree::<C::Fp>::prove_rows(base_tree, positions)?;
+        let extension_trace_proofs = extension_trees
+            .iter()
+            .map(|extension_tree| MatrixMerkleTree::<C::Fq>::prove_rows(extension_tree, positions))
+            .collect::<Result<Vec<_>, _>>()?;
         let composition_trace_proof =
-            MatrixMerkleTree::<C::Fq>::prove_rows(composition_tree, positions).unwrap();
+            MatrixMerkleTree::<C::Fq>::prove_rows(composition_tree, positions)?;
 
         let mut base_trace_values = Vec::new();
-        let mut extension_trace_values = Vec::new();
+        let mut extension_trace_values = vec![Vec::new(); extension_trace_ldes.len()];
         let mut composition_trace_values = Vec::new();
         for &position in positions {
             // execution trace
             let base_trace_row = base_trace_lde.get_row(position).unwrap();
             base_trace_values.extend(base_trace_row);
 
-            if let Some(extension_trace_lde) = extension_trace_lde {
+            for (values, lde) in zip(&mut extension_trace_values, extension_trace_ldes) {
                 // TODO: suport ark DomainCoeff on evaluate_at
-                let extension_trace_row = extension_trace_lde.get_row(position).unwrap();
-                extension_trace_values.extend(extension_trace_row);
+                values.extend(lde.get_row(position).unwrap());
             }
 
             // composition trace
             let composition_trace_row = composition_trace_lde.get_row(position).unwrap();
             composition_trace_values.extend(composition_trace_row);
         }
-        Self {
+        Ok(Self {
             base_trace_values,
             extension_trace_values,
             composition_trace_values,
             base_trace_proof,
-            extension_trace_proof,
+            extension_trace_proofs,
             composition_trace_proof,
-        }
+        })
     }
 }
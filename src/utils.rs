@@ -21,6 +21,8 @@ use core::ops::Div;
 use core::ops::Mul;
 use core::ops::Neg;
 use core::ptr::NonNull;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
 use num_traits::Pow;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
@@ -203,6 +205,41 @@ pub fn reduce_lde_blowup_factor<T: Copy>(
     lde.truncate(lde.len() / reduction_factor);
 }
 
+/// Deserializes an array of `N` elements. Unlike the [`CanonicalDeserialize`]
+/// implementation for `[T; N]` this returns an error rather than panicking if
+/// the reader has too few bytes.
+pub fn deserialize_array<T: CanonicalDeserialize, const N: usize>(
+    mut reader: impl ark_serialize::Read,
+    compress: ark_serialize::Compress,
+    validate: ark_serialize::Validate,
+) -> Result<[T; N], ark_serialize::SerializationError> {
+    let mut values = Vec::with_capacity(N);
+    for _ in 0..N {
+        values.push(T::deserialize_with_mode(&mut reader, compress, validate)?);
+    }
+    Ok(values.try_into().unwrap_or_else(|_| unreachable!()))
+}
+
+/// Deserializes a length prefixed vector that has at most `max_len` elements.
+/// The length is checked before anything is allocated.
+pub fn deserialize_vec<T: CanonicalDeserialize>(
+    mut reader: impl ark_serialize::Read,
+    compress: ark_serialize::Compress,
+    validate: ark_serialize::Validate,
+    max_len: usize,
+) -> Result<Vec<T>, ark_serialize::SerializationError> {
+    let len = u64::deserialize_with_mode(&mut reader, compress, validate)?;
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= max_len)
+        .ok_or(ark_serialize::SerializationError::InvalidData)?;
+    let mut values = Vec::with_capacity(len);
+    for _ in 0..len {
+        values.push(T::deserialize_with_mode(&mut reader, compress, validate)?);
+    }
+    Ok(values)
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum FieldType {
     Fp,
@@ -241,7 +278,7 @@ impl CanonicalDeserialize for FieldType {
         } else if x == Self::Fq as u8 {
             Self::Fq
         } else {
-            unreachable!()
+            return Err(ark_serialize::SerializationError::InvalidData);
         })
     }
 }
@@ -443,8 +480,12 @@ pub type GpuVec<T> = Vec<T, GpuAllocator>;
 /// Uses global allocator on all other platforms.
 pub struct GpuAllocator;
 
+static GPU_ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static GPU_PEAK_ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
 unsafe impl Allocator for GpuAllocator {
     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        record_gpu_allocation(layout.size());
         #[cfg(all(target_arch = "aarch64", target_os = "macos"))]
         return page_aligned_allocator::PageAlignedAllocator.allocate(layout);
         #[cfg(not(all(target_arch = "aarch64", target_os = "macos")))]
@@ -452,6 +493,7 @@ unsafe impl Allocator for GpuAllocator {
     }
 
     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        record_gpu_deallocation(layout.size());
         #[cfg(all(target_arch = "aarch64", target_os = "macos"))]
         return page_aligned_allocator::PageAlignedAllocator.deallocate(ptr, layout);
         #[cfg(not(all(target_arch = "aarch64", target_os = "macos")))]
@@ -459,12 +501,47 @@ unsafe impl Allocator for GpuAllocator {
     }
 }
 
+/// Returns the number of bytes currently held by [`GpuAllocator`]
+pub fn gpu_allocated_bytes() -> usize {
+    GPU_ALLOCATED_BYTES.load(Ordering::Relaxed)
+}
+
+/// Returns the peak number of bytes held by [`GpuAllocator`] since the last
+/// call to [`reset_gpu_peak_allocated_bytes`]
+pub fn gpu_peak_allocated_bytes() -> usize {
+    GPU_PEAK_ALLOCATED_BYTES.load(Ordering::Relaxed)
+}
+
+/// Resets the peak number of allocated bytes to the number of bytes currently
+/// held by [`GpuAllocator`]
+pub fn reset_gpu_peak_allocated_bytes() {
+    GPU_PEAK_ALLOCATED_BYTES.store(gpu_allocated_bytes(), Ordering::Relaxed);
+}
+
+fn record_gpu_allocation(bytes: usize) {
+    let allocated = GPU_ALLOCATED_BYTES.fetch_add(bytes, Ordering::Relaxed) + bytes;
+    // avoid a second read-modify-write unless there is a new peak
+    if allocated > GPU_PEAK_ALLOCATED_BYTES.load(Ordering::Relaxed) {
+        GPU_PEAK_ALLOCATED_BYTES.fetch_max(allocated, Ordering::Relaxed);
+    }
+}
+
+fn record_gpu_deallocation(bytes: usize) {
+    GPU_ALLOCATED_BYTES.fetch_sub(bytes, Ordering::Relaxed);
+}
+
+/// Moves a vec out of [`GpuAllocator`]. The vec's memory is no longer counted
+/// as held by [`GpuAllocator`].
 pub fn gpu_vec_to_vec<T>(v: GpuVec<T>) -> Vec<T> {
+    record_gpu_deallocation(v.capacity() * core::mem::size_of::<T>());
     let (ptr, length, capacity) = v.into_raw_parts();
     unsafe { Vec::from_raw_parts(ptr, length, capacity) }
 }
 
+/// Moves a vec into [`GpuAllocator`]. The vec's memory is counted as held by
+/// [`GpuAllocator`] from here on.
 pub fn vec_to_gpu_vec<T>(v: Vec<T>) -> GpuVec<T> {
+    record_gpu_allocation(v.capacity() * core::mem::size_of::<T>());
     let (ptr, length, capacity) = v.into_raw_parts();
     unsafe { Vec::from_raw_parts_in(ptr, length, capacity, GpuAllocator) }
 }
@@ -576,7 +653,10 @@ impl<D: digest::Digest> CanonicalDeserialize for SerdeOutput<D> {
         compress: ark_serialize::Compress,
         validate: ark_serialize::Validate,
     ) -> Result<Self, ark_serialize::SerializationError> {
-        let bytes = Vec::deserialize_with_mode(reader, compress, validate)?;
+        let bytes = Vec::<u8>::deserialize_with_mode(reader, compress, validate)?;
+        if bytes.len() != <D as digest::Digest>::output_size() {
+            return Err(ark_serialize::SerializationError::InvalidData);
+        }
         Ok(Self(digest::Output::<D>::from_iter(bytes)))
     }
 }
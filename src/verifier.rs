@@ -1,3 +1,4 @@
+use crate::air;
 use crate::air::AirConfig;
 use crate::challenges::Challenges;
 use crate::channel::VerifierChannelArtifacts;
@@ -8,6 +9,8 @@ use crate::fri;
 use crate::fri::FriVerifier;
 use crate::hints::Hints;
 use crate::merkle::MatrixMerkleTree;
+use crate::merkle::MerkleTree;
+use crate::proof;
 use crate::random::draw_multiple;
 use crate::random::PublicCoin;
 use crate::stark::Stark;
@@ -20,6 +23,7 @@ use alloc::vec::Vec;
 use ark_ff::Field;
 use ark_ff::Zero;
 use ark_poly::EvaluationDomain;
+use core::iter::zip;
 use ministark_gpu::utils::bit_reverse_index;
 use snafu::Snafu;
 
@@ -31,40 +35,43 @@ pub fn default_verify<S: Stark>(
 ) -> Result<VerifierChannelArtifacts<S::Fq>, VerificationError> {
     use VerificationError::*;
 
+    proof.validate()?;
     if proof.security_level_bits() < required_security_bits {
         return Err(InvalidProofSecurity);
     }
 
+    let air = Air::new(proof.trace_len, this.get_public_inputs(), proof.options)?;
+    proof.validate_with_air(&air)?;
+
     let Proof {
         options,
         base_trace_commitment,
-        extension_trace_commitment,
+        extension_trace_commitments,
         composition_trace_commitment,
         execution_trace_ood_evals,
         composition_trace_ood_evals,
         trace_queries,
-        trace_len,
         fri_proof,
         pow_nonce,
         ..
     } = proof;
 
-    let air = Air::new(trace_len, this.get_public_inputs(), options);
     let mut public_coin = this.gen_public_coin(&air);
 
-    public_coin.reseed_with_digest(&base_trace_commitment);
-    let num_challenges = air.num_challenges();
-    let air_challenges = Challenges::new(draw_multiple(&mut public_coin, num_challenges));
+    public_coin.reseed_with_commitment(&base_trace_commitment);
+    let mut air_challenges = Vec::new();
+    for (segment, commitment) in zip(air.extension_segments(), &extension_trace_commitments) {
+        air_challenges.extend(draw_multiple(&mut public_coin, segment.num_challenges));
+        public_coin.reseed_with_commitment(commitment);
+    }
+    let num_trailing_challenges = air.num_trailing_challenges();
+    air_challenges.extend(draw_multiple(&mut public_coin, num_trailing_challenges));
+    let air_challenges = Challenges::new(air_challenges);
     let air_hints = air.gen_hints(&air_challenges);
 
-    let extension_trace_commitment = extension_trace_commitment.map(|commitment| {
-        public_coin.reseed_with_digest(&commitment);
-        commitment
-    });
-
     let num_composition_coeffs = air.num_composition_constraint_coeffs();
     let composition_coeffs = draw_multiple(&mut public_coin, num_composition_coeffs);
-    public_coin.reseed_with_digest(&composition_trace_commitment);
+    public_coin.reseed_with_commitment(&composition_trace_commitment);
 
     let z = public_coin.draw();
     let ood_evals = [
@@ -88,19 +95,23 @@ pub fn default_verify<S: Stark>(
         z,
     );
 
-    let provided_ood_constraint_evaluation = horner_evaluate(&composition_trace_ood_evals, &z);
+    // composition polynomial segments hold `trace_len` coefficients each
+    let z_n = z.pow([air.trace_len() as u64]);
+    let provided_ood_constraint_evaluation = horner_evaluate(&composition_trace_ood_evals, &z_n);
 
     if calculated_ood_constraint_evaluation != provided_ood_constraint_evaluation {
         return Err(InconsistentOodConstraintEvaluations);
     }
 
     let deep_coeffs = this.gen_deep_coeffs(&mut public_coin, &air);
-    let fri_verifier = FriVerifier::<S::Fq, S::Digest, S::MerkleTree>::new(
-        &mut public_coin,
-        options.into_fri_options(),
-        fri_proof,
-        trace_len - 1,
-    )?;
+    let fri_verifier =
+        FriVerifier::<S::Fq, <S::MerkleTree as MerkleTree>::Root, S::MerkleTree>::new(
+            &mut public_coin,
+            options.into_fri_options(),
+            fri_proof,
+            air.padded_trace_len() - 1,
+            air.lde_domain().coset_offset(),
+        )?;
 
     if options.grinding_factor != 0 {
         if !public_coin.verify_proof_of_work(options.grinding_factor, pow_nonce) {
@@ -117,18 +128,27 @@ pub fn default_verify<S: Stark>(
         .base_trace_values
         .chunks(S::AirConfig::NUM_BASE_COLUMNS)
         .collect::<Vec<_>>();
-    let extension_trace_rows = if S::AirConfig::NUM_EXTENSION_COLUMNS == 0 {
-        Vec::new()
-    } else {
-        trace_queries
-            .extension_trace_values
-            .chunks(S::AirConfig::NUM_EXTENSION_COLUMNS)
-            .collect::<Vec<_>>()
-    };
+    let extension_segment_rows = zip(
+        air.extension_segments(),
+        &trace_queries.extension_trace_values,
+    )
+    .map(|(segment, values)| values.chunks(segment.num_columns).collect::<Vec<_>>())
+    .collect::<Vec<Vec<&[S::Fq]>>>();
+    // rows of all extension trace segments joined together
+    let extension_trace_rows = (0..query_positions.len())
+        .map(|i| {
+            let segment_rows = extension_segment_rows.iter().map(|rows| rows[i]);
+            segment_rows.flatten().copied().collect()
+        })
+        .collect::<Vec<Vec<S::Fq>>>();
+    let extension_trace_rows = extension_trace_rows
+        .iter()
+        .map(Vec::as_slice)
+        .collect::<Vec<&[S::Fq]>>();
 
     let composition_trace_rows = trace_queries
         .composition_trace_values
-        .chunks(air.ce_blowup_factor())
+        .chunks(air.num_composition_trace_cols())
         .collect::<Vec<&[S::Fq]>>();
 
     // base trace positions
@@ -140,14 +160,13 @@ pub fn default_verify<S: Stark>(
     )
     .map_err(|_| BaseTraceQueryDoesNotMatchCommitment)?;
 
-    if let Some(extension_trace_commitment) = extension_trace_commitment {
-        S::MerkleTree::verify_rows(
-            &extension_trace_commitment,
-            &query_positions,
-            &extension_trace_rows,
-            trace_queries.extension_trace_proof.unwrap(),
-        )
-        .map_err(|_| ExtensionTraceQueryDoesNotMatchCommitment)?;
+    let extension_trace_proofs = trace_queries.extension_trace_proofs;
+    for ((commitment, rows), proof) in zip(
+        zip(&extension_trace_commitments, &extension_segment_rows),
+        extension_trace_proofs,
+    ) {
+        S::MerkleTree::verify_rows(commitment, &query_positions, rows, proof)
+            .map_err(|_| ExtensionTraceQueryDoesNotMatchCommitment)?;
     }
 
     // composition trace positions
@@ -183,7 +202,7 @@ pub fn default_verify<S: Stark>(
 }
 
 /// Errors that are returned during verification of a STARK proof
-#[derive(Debug, Snafu)]
+#[derive(Debug, PartialEq, Eq, Snafu)]
 pub enum VerificationError {
     #[snafu(display("proof params do not satisfy security requirements"))]
     InvalidProofSecurity,
@@ -200,6 +219,12 @@ pub enum VerificationError {
     CompositionTraceQueryDoesNotMatchCommitment,
     #[snafu(display("insufficient proof of work on fri commitments"))]
     FriProofOfWork,
+    #[snafu(context(false))]
+    #[snafu(display("proof does not describe a valid air: {source}"))]
+    InvalidAir { source: air::Error },
+    #[snafu(context(false))]
+    #[snafu(display("proof is malformed: {source}"))]
+    MalformedProof { source: proof::Error },
 }
 
 pub fn ood_constraint_evaluation<A: AirConfig>(
@@ -249,7 +274,6 @@ pub fn deep_composition_evaluations<A: AirConfig>(
     let trace_domain = air.trace_domain();
     let g = trace_domain.group_gen();
     let g_inv = trace_domain.group_gen_inv();
-    let z_n = z.pow([air.ce_blowup_factor() as u64]);
     let lde_domain = air.lde_domain();
     let lde_domain_size = lde_domain.size();
     let xs = query_positions
@@ -280,10 +304,12 @@ pub fn deep_composition_evaluations<A: AirConfig>(
         }
 
         // composition trace
-        for (j, value) in composition_trace_rows[i].iter().enumerate() {
+        let num_segments = air.num_composition_segments();
+        let composition_trace_row = &composition_trace_rows[i][..num_segments];
+        for (j, value) in composition_trace_row.iter().enumerate() {
             let alpha = composition_coeffs.composition_trace[j];
             let ood_eval = composition_trace_ood_evals[j];
-            *eval += alpha * (*value - ood_eval) / (A::Fq::from(x) - z_n);
+            *eval += alpha * (*value - ood_eval) / (A::Fq::from(x) - z);
         }
     }
 
@@ -293,5 +319,13 @@ pub fn deep_composition_evaluations<A: AirConfig>(
         *eval *= alpha + beta * x;
     }
 
+    // apply the zero knowledge mask
+    if air.options().zero_knowledge {
+        let mask_column = air.num_composition_segments();
+        for (row, eval) in composition_trace_rows.iter().zip(&mut evals) {
+            *eval += row[mask_column];
+        }
+    }
+
     evals
 }
@@ -0,0 +1,270 @@
+#![feature(allocator_api)]
+mod common;
+
+use ark_ff::Field;
+use ark_ff::One;
+use ark_ff::Zero;
+use common::OPTIONS;
+use ministark::air::AirConfig;
+use ministark::arguments::EvaluationArgument;
+use ministark::arguments::PermutationArgument;
+use ministark::challenges::Challenges;
+use ministark::constraints::AlgebraicItem;
+use ministark::constraints::Constraint;
+use ministark::constraints::ExecutionTraceColumn;
+use ministark::hash::Sha256HashFn;
+use ministark::hints::Hints;
+use ministark::merkle::MatrixMerkleTreeImpl;
+use ministark::prover::ProvingError;
+use ministark::random::PublicCoinImpl;
+use ministark::stark::Stark;
+use ministark::tamper::check_tampered_proofs;
+use ministark::utils::FieldVariant;
+use ministark::utils::GpuAllocator;
+use ministark::utils::GpuVec;
+use ministark::utils::SerdeOutput;
+use ministark::Matrix;
+use ministark::ProofOptions;
+use ministark::Trace;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+use num_traits::Pow;
+use sha2::Sha256;
+
+const TRACE_LEN: usize = 64;
+
+fn column(values: impl IntoIterator<Item = Fp>) -> GpuVec<Fp> {
+    let mut column = Vec::new_in(GpuAllocator);
+    column.extend(values);
+    column
+}
+
+/// Constraints that a column is zero or one in every row
+fn is_binary(columns: &[usize], trace_len: usize) -> Vec<Constraint<FieldVariant<Fp, Fp>>> {
+    use AlgebraicItem::*;
+    let one = Constant(FieldVariant::Fp(Fp::one()));
+    let every_row = X.pow(trace_len) - one;
+    (columns.iter())
+        .map(|column| Constraint::new((column.curr() - one) * column.curr() / &every_row))
+        .collect()
+}
+
+/// Table `(a0, a1)` holds the same selected rows as table `(b0, b1)`
+fn permutation_arguments() -> [PermutationArgument<Fp, Fp>; 2] {
+    [
+        PermutationArgument::new(0, 1, 6, [0.curr(), 1.curr()]).with_selector(2.curr()),
+        PermutationArgument::new(0, 1, 7, [3.curr(), 4.curr()]).with_selector(5.curr()),
+    ]
+}
+
+struct PermutationTrace(Matrix<Fp>);
+
+impl Trace for PermutationTrace {
+    type Fp = Fp;
+    type Fq = Fp;
+
+    fn base_columns(&self) -> &Matrix<Fp> {
+        &self.0
+    }
+
+    fn build_extension_columns(&self, challenges: &Challenges<Fp>) -> Option<Matrix<Fp>> {
+        let [a, b] = permutation_arguments();
+        Some(Matrix::join(vec![
+            a.build_column(self, challenges, &[]),
+            b.build_column(self, challenges, &[]),
+        ]))
+    }
+}
+
+/// Rows `(i, i^2)` for `i` in `0..num_rows` followed by junk rows that aren't
+/// selected. Table `b` holds the rows in reverse.
+fn gen_permutation_trace(num_rows: usize) -> PermutationTrace {
+    let table = |rows: &mut dyn Iterator<Item = u64>| {
+        let rows = Vec::from_iter(rows.map(Fp::from));
+        let junk = (rows.len()..TRACE_LEN).map(|i| Fp::from(i as u64 * 17 + 5));
+        let selector = (0..TRACE_LEN).map(|i| Fp::from(u64::from(i < rows.len())));
+        [
+            column(rows.iter().copied().chain(junk.clone())),
+            column(rows.iter().map(Fp::square).chain(junk)),
+            column(selector),
+        ]
+    };
+    let [a0, a1, a_selector] = table(&mut (0..num_rows as u64));
+    let [b0, b1, b_selector] = table(&mut (0..num_rows as u64).rev());
+    PermutationTrace(Matrix::new(vec![a0, a1, a_selector, b0, b1, b_selector]))
+}
+
+struct PermutationAirConfig;
+
+impl AirConfig for PermutationAirConfig {
+    const NUM_BASE_COLUMNS: usize = 6;
+    const NUM_EXTENSION_COLUMNS: usize = 2;
+    const NAME: &'static str = "permutation";
+    type Fp = Fp;
+    type Fq = Fp;
+    type PublicInputs = ();
+
+    fn constraints(trace_len: usize) -> Vec<Constraint<FieldVariant<Fp, Fp>>> {
+        let [a, b] = permutation_arguments();
+        let permutation = a.permutation_constraint(trace_len, &b);
+        (is_binary(&[2, 5], trace_len).into_iter())
+            .chain(a.constraints(trace_len))
+            .chain(b.constraints(trace_len))
+            .chain([permutation])
+            .collect()
+    }
+}
+
+struct PermutationClaim;
+
+impl Stark for PermutationClaim {
+    type Fp = Fp;
+    type Fq = Fp;
+    type AirConfig = PermutationAirConfig;
+    type Digest = SerdeOutput<Sha256>;
+    type PublicCoin = PublicCoinImpl<Fp, Sha256HashFn>;
+    type MerkleTree = MatrixMerkleTreeImpl<Sha256HashFn>;
+    type Witness = PermutationTrace;
+    type Trace = PermutationTrace;
+
+    fn get_public_inputs(&self) {}
+
+    fn generate_trace(&self, witness: PermutationTrace) -> PermutationTrace {
+        witness
+    }
+}
+
+#[test]
+fn permutation_proof_verifies() {
+    // selected running products have degree 3 transition constraints
+    let options = ProofOptions::new(32, 8, 0, 2, 4);
+    for options in [options, options.with_zero_knowledge(true)] {
+        let trace = gen_permutation_trace(40);
+
+        let proof = pollster::block_on(PermutationClaim.prove(options, trace)).unwrap();
+
+        PermutationClaim.verify(proof.clone(), 0).unwrap();
+        check_tampered_proofs(&PermutationClaim, &proof);
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn prove_returns_error_for_tables_that_are_not_permutations() {
+    let mut trace = gen_permutation_trace(40);
+    trace.0 .0[3][0] += Fp::one();
+
+    let options = ProofOptions::new(32, 8, 0, 2, 4);
+    let res = pollster::block_on(PermutationClaim.prove(options, trace));
+
+    assert!(matches!(
+        res,
+        Err(ProvingError::UnsatisfiedConstraints { .. })
+    ));
+}
+
+#[test]
+fn build_column_ends_with_the_terminal() {
+    let trace = gen_permutation_trace(TRACE_LEN);
+    let challenges = Challenges::new(vec![Fp::from(1234u64), Fp::from(5678u64)]);
+    let rows = Vec::from_iter((0..TRACE_LEN).map(|i| [trace.0 .0[0][i], trace.0 .0[1][i]]));
+    let permutation = PermutationArgument::new(0, 1, 6, [0.curr(), 1.curr()]);
+    let evaluation = EvaluationArgument::new(0, 1, 6, [0.curr(), 1.curr()]);
+
+    let permutation_column = permutation.build_column(&trace, &challenges, &[]);
+    let evaluation_column = evaluation.build_column(&trace, &challenges, &[]);
+
+    assert_eq!(
+        permutation.terminal(&challenges, rows.iter().map(|row| row.as_slice())),
+        permutation_column.0[0][TRACE_LEN - 1]
+    );
+    assert_eq!(
+        evaluation.terminal(&challenges, rows.iter().map(|row| row.as_slice())),
+        evaluation_column.0[0][TRACE_LEN - 1]
+    );
+}
+
+/// Selected rows of column 0 are the public sequence of values
+fn evaluation_argument() -> EvaluationArgument<Fp, Fp> {
+    EvaluationArgument::new(0, 1, 2, [0.curr()]).with_selector(1.curr())
+}
+
+struct SequenceTrace(Matrix<Fp>);
+
+impl Trace for SequenceTrace {
+    type Fp = Fp;
+    type Fq = Fp;
+
+    fn base_columns(&self) -> &Matrix<Fp> {
+        &self.0
+    }
+
+    fn build_extension_columns(&self, challenges: &Challenges<Fp>) -> Option<Matrix<Fp>> {
+        Some(evaluation_argument().build_column(self, challenges, &[]))
+    }
+}
+
+struct SequenceAirConfig;
+
+impl AirConfig for SequenceAirConfig {
+    const NUM_BASE_COLUMNS: usize = 2;
+    const NUM_EXTENSION_COLUMNS: usize = 1;
+    const NAME: &'static str = "public_sequence";
+    type Fp = Fp;
+    type Fq = Fp;
+    type PublicInputs = Vec<Fp>;
+
+    fn gen_hints(_: usize, sequence: &Vec<Fp>, challenges: &Challenges<Fp>) -> Hints<Fp> {
+        let rows = sequence.iter().map(core::slice::from_ref);
+        Hints::new(vec![(0, evaluation_argument().terminal(challenges, rows))])
+    }
+
+    fn constraints(trace_len: usize) -> Vec<Constraint<FieldVariant<Fp, Fp>>> {
+        let evaluation = evaluation_argument();
+        let terminal = evaluation.terminal_constraint(trace_len, 0);
+        (is_binary(&[1], trace_len).into_iter())
+            .chain(evaluation.constraints(trace_len))
+            .chain([terminal])
+            .collect()
+    }
+}
+
+struct SequenceClaim(Vec<Fp>);
+
+impl Stark for SequenceClaim {
+    type Fp = Fp;
+    type Fq = Fp;
+    type AirConfig = SequenceAirConfig;
+    type Digest = SerdeOutput<Sha256>;
+    type PublicCoin = PublicCoinImpl<Fp, Sha256HashFn>;
+    type MerkleTree = MatrixMerkleTreeImpl<Sha256HashFn>;
+    type Witness = ();
+    type Trace = SequenceTrace;
+
+    fn get_public_inputs(&self) -> Vec<Fp> {
+        self.0.clone()
+    }
+
+    /// Spreads the sequence over every other row
+    fn generate_trace(&self, (): ()) -> SequenceTrace {
+        let mut values = vec![Fp::zero(); TRACE_LEN];
+        let mut selector = vec![Fp::zero(); TRACE_LEN];
+        for (i, &value) in self.0.iter().enumerate() {
+            values[2 * i + 1] = value;
+            selector[2 * i + 1] = Fp::one();
+        }
+        SequenceTrace(Matrix::new(vec![column(values), column(selector)]))
+    }
+}
+
+#[test]
+fn evaluation_of_public_sequence_verifies() {
+    let sequence = Vec::from_iter((0..20u64).map(|i| Fp::from(i * i + 7)));
+    let claim = SequenceClaim(sequence.clone());
+
+    let proof = pollster::block_on(claim.prove(OPTIONS, ())).unwrap();
+    claim.verify(proof.clone(), 0).unwrap();
+
+    let mut reordered = sequence;
+    reordered.swap(0, 1);
+    assert!(SequenceClaim(reordered).verify(proof, 0).is_err());
+}
@@ -0,0 +1,174 @@
+#![allow(dead_code)]
+use ark_ff::One;
+use ark_poly::EvaluationDomain;
+use ark_poly::Radix2EvaluationDomain;
+use ministark::air::AirConfig;
+use ministark::challenges::Challenges;
+use ministark::constraints::AlgebraicItem;
+use ministark::constraints::Constraint;
+use ministark::constraints::ExecutionTraceColumn;
+use ministark::hash::HashFn;
+use ministark::hash::Sha256HashFn;
+use ministark::hints::Hints;
+use ministark::merkle::Commitment;
+use ministark::merkle::MatrixMerkleTree;
+use ministark::merkle::MatrixMerkleTreeImpl;
+use ministark::merkle::MerkleTree;
+use ministark::random::PublicCoin;
+use ministark::random::PublicCoinImpl;
+use ministark::stark::Stark;
+use ministark::telemetry::NoopObserver;
+use ministark::telemetry::ProverObserver;
+use ministark::utils::FieldVariant;
+use ministark::utils::GpuAllocator;
+use ministark::Matrix;
+use ministark::ProofOptions;
+use ministark::Trace;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+use num_traits::Pow;
+use std::marker::PhantomData;
+
+pub const OPTIONS: ProofOptions = ProofOptions::new(32, 4, 0, 2, 4);
+
+/// Two column fibonacci trace i.e. `col0[i+1] = col1[i]` and
+/// `col1[i+1] = col0[i] + col1[i]`
+pub struct FibTrace(pub Matrix<Fp>);
+
+impl Trace for FibTrace {
+    type Fp = Fp;
+    type Fq = Fp;
+
+    fn len(&self) -> usize {
+        self.0.num_rows()
+    }
+
+    fn base_columns(&self) -> &Matrix<Self::Fp> {
+        &self.0
+    }
+}
+
+pub struct FibAirConfig;
+
+impl AirConfig for FibAirConfig {
+    const NUM_BASE_COLUMNS: usize = 2;
+    const NAME: &'static str = "fibonacci";
+    type Fp = Fp;
+    type Fq = Fp;
+    type PublicInputs = ();
+
+    fn gen_hints(_: usize, _: &(), _: &Challenges<Self::Fq>) -> Hints<Self::Fq> {
+        Hints::new(vec![])
+    }
+
+    fn constraints(trace_len: usize) -> Vec<Constraint<FieldVariant<Self::Fp, Self::Fq>>> {
+        use AlgebraicItem::*;
+        let trace_xs = Radix2EvaluationDomain::<Fp>::new(trace_len).unwrap();
+        let first_trace_x = Constant(FieldVariant::Fp(trace_xs.element(0)));
+        let last_trace_x = Constant(FieldVariant::Fp(trace_xs.element(trace_len - 1)));
+        let one = Constant(FieldVariant::Fp(Fp::one()));
+
+        let every_row_except_last = (X - last_trace_x) / (X.pow(trace_len) - one);
+        vec![
+            (0.curr() - one) / (X - first_trace_x),
+            (0.next() - 1.curr()) * &every_row_except_last,
+            (1.next() - 0.curr() - 1.curr()) * &every_row_except_last,
+        ]
+        .into_iter()
+        .map(Constraint::new)
+        .collect()
+    }
+}
+
+/// Fibonacci claim that is generic over the hash function `H`, merkle tree
+/// `M` and public coin `P`. The AIR `A` and trace `T` can be swapped out to
+/// prove related statements.
+pub struct FibClaim<
+    H = Sha256HashFn,
+    M = MatrixMerkleTreeImpl<H>,
+    P = PublicCoinImpl<Fp, H>,
+    A: AirConfig = FibAirConfig,
+    T = FibTrace,
+    O = NoopObserver,
+> {
+    public_inputs: A::PublicInputs,
+    pub observer: O,
+    _phantom: PhantomData<(H, M, P, T)>,
+}
+
+impl FibClaim {
+    pub const fn new() -> Self {
+        Self::with_public_inputs(())
+    }
+}
+
+impl<H, M, P, A: AirConfig, T> FibClaim<H, M, P, A, T> {
+    pub const fn with_public_inputs(public_inputs: A::PublicInputs) -> Self {
+        Self {
+            public_inputs,
+            observer: NoopObserver,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<H, M, P, A: AirConfig, T> Default for FibClaim<H, M, P, A, T>
+where
+    A::PublicInputs: Default,
+{
+    fn default() -> Self {
+        Self::with_public_inputs(Default::default())
+    }
+}
+
+impl<H, M, P, A: AirConfig, T, O> FibClaim<H, M, P, A, T, O> {
+    pub fn with_observer<O2>(self, observer: O2) -> FibClaim<H, M, P, A, T, O2> {
+        FibClaim {
+            public_inputs: self.public_inputs,
+            observer,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<H, M, P, A, T, O> Stark for FibClaim<H, M, P, A, T, O>
+where
+    H: HashFn,
+    M: MerkleTree<Root: Commitment<Digest = H::Digest>> + MatrixMerkleTree<Fp>,
+    P: PublicCoin<Digest = H::Digest, Field = Fp>,
+    A: AirConfig<Fp = Fp, Fq = Fp, PublicInputs: Send + Sync>,
+    T: Trace<Fp = Fp, Fq = Fp>,
+    O: ProverObserver,
+{
+    type Fp = Fp;
+    type Fq = Fp;
+    type AirConfig = A;
+    type Digest = H::Digest;
+    type PublicCoin = P;
+    type MerkleTree = M;
+    type Witness = T;
+    type Trace = T;
+
+    fn get_public_inputs(&self) -> A::PublicInputs {
+        self.public_inputs.clone()
+    }
+
+    fn generate_trace(&self, witness: T) -> T {
+        witness
+    }
+
+    fn observer(&self) -> &dyn ProverObserver {
+        &self.observer
+    }
+}
+
+pub fn gen_fib_trace(n: usize) -> FibTrace {
+    let mut col0 = Vec::with_capacity_in(n, GpuAllocator);
+    let mut col1 = Vec::with_capacity_in(n, GpuAllocator);
+    let (mut a, mut b) = (Fp::one(), Fp::one());
+    for _ in 0..n {
+        col0.push(a);
+        col1.push(b);
+        (a, b) = (b, a + b);
+    }
+    FibTrace(Matrix::new(vec![col0, col1]))
+}
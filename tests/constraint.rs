@@ -15,6 +15,11 @@ use ministark::constraints::Constraint;
 use ministark::constraints::ExecutionTraceColumn;
 use ministark::constraints::VerifierChallenge;
 use ministark::expression::Expr;
+use ministark::selectors::every_row;
+use ministark::selectors::every_row_except_last;
+use ministark::selectors::first_row;
+use ministark::selectors::last_row;
+use ministark::selectors::periodic_rows;
 use ministark::utils;
 use ministark::utils::tests::gen_binary_valued_matrix;
 use ministark::utils::tests::gen_fib_matrix;
@@ -329,6 +334,79 @@ fn evaluate_zerofier_constraint() {
     assert_valid_over_transition_domain(trace_domain, constraint_eval_poly);
 }
 
+#[test]
+fn selector_constraints_have_minimal_degree() {
+    use AlgebraicItem::*;
+    let n = 64;
+    let one = Constant(FieldVariant::Fp(Fp::one()));
+    let mut rng = ark_std::test_rng();
+    let fib_matrix = gen_fib_matrix::<Fp>(n);
+    let last_value = Constant(FieldVariant::Fp(fib_matrix.0[0][n - 1]));
+    let binary_matrix = gen_binary_valued_matrix(n, Fp::zero(), Fp::one());
+    // zero in rows `1 + 4k`
+    let periodic_column = (0..n)
+        .map(|i| {
+            if i % 4 == 1 {
+                Fp::zero()
+            } else {
+                Fp::rand(&mut rng)
+            }
+        })
+        .collect::<Vec<Fp>>()
+        .to_vec_in(GpuAllocator);
+    let matrix = Matrix::join(vec![
+        fib_matrix,
+        binary_matrix,
+        Matrix::new(vec![periodic_column]),
+    ]);
+    let constraints = [
+        (0.curr() - one) * first_row(),
+        (0.curr() - last_value) * last_row(n),
+        (0.next() - (0.curr() + 1.curr())) * every_row_except_last(n),
+        2.curr() * (2.curr() - one) * every_row(n),
+        3.curr() * periodic_rows(n, 4, 1),
+    ];
+    let trace_domain = Radix2EvaluationDomain::<Fp>::new(n).unwrap();
+    let lde_domain = Radix2EvaluationDomain::<Fp>::new_coset(n * 4, Fp::GENERATOR).unwrap();
+    let lde_matrix = matrix.interpolate(trace_domain).evaluate(lde_domain);
+
+    for constraint in constraints.into_iter().map(Constraint::new) {
+        let (numerator_degree, denominator_degree) = constraint.degree(n - 1);
+        let eval = evaluate_symbolic(lde_domain, 4, &[], &[], &constraint, &lde_matrix);
+        let eval_poly = eval.interpolate(lde_domain);
+
+        let poly = DensePolynomial::from_coefficients_slice(&eval_poly.0[0]);
+        assert_eq!(numerator_degree - denominator_degree, poly.degree());
+    }
+}
+
+#[test]
+fn periodic_rows_selects_every_nth_row() {
+    use AlgebraicItem::*;
+    let n = 64;
+    let trace_domain = Radix2EvaluationDomain::<Fp>::new(n).unwrap();
+    let one = FieldVariant::<Fp, Fp>::Fp(Fp::one());
+    for (interval_size, offset) in [(1, 0), (4, 1), (8, 7), (64, 5)] {
+        let constraint = Constraint::new(0.curr() * periodic_rows(n, interval_size, offset));
+
+        for (i, x) in trace_domain.elements().enumerate() {
+            let eval = constraint.check(&mut |leaf| match leaf {
+                X => FieldVariant::Fp(x),
+                &Constant(v) => v,
+                Trace(..) => one,
+                _ => unreachable!(),
+            });
+
+            let is_selected = i % interval_size == offset;
+            assert_eq!(
+                is_selected,
+                eval.is_none(),
+                "row {i} of every {interval_size}"
+            );
+        }
+    }
+}
+
 fn assert_valid_over_transition_domain<F: GpuField + Field>(
     domain: Radix2EvaluationDomain<F::FftField>,
     poly_matrix: Matrix<F>,
@@ -0,0 +1,78 @@
+#![feature(allocator_api)]
+mod common;
+
+use ark_ff::One;
+use common::gen_fib_trace;
+use common::FibClaim;
+use common::FibTrace;
+use ministark::challenges::Challenges;
+use ministark::debug::ConstraintError;
+use ministark::debug::Leaf;
+use ministark::hints::Hints;
+use ministark::stark::Stark;
+use ministark::Trace;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+
+#[test]
+fn valid_trace_passes_validation() {
+    let trace = gen_fib_trace(16);
+
+    let res = validate(&trace);
+
+    assert!(res.is_ok());
+}
+
+#[test]
+fn invalid_trace_reports_constraint_and_row() {
+    let mut trace = gen_fib_trace(16);
+    trace.0 .0[0][5] += Fp::one();
+
+    let res = validate(&trace);
+
+    match res {
+        Err(ConstraintError::UnsatisfiedConstraint {
+            constraint,
+            row,
+            values,
+        }) => {
+            // column 0 at row 5 is first referenced by the transition from row 4
+            assert_eq!(1, constraint);
+            assert_eq!(4, row);
+            let col0_next = values
+                .iter()
+                .find(|v| {
+                    v.leaf
+                        == Leaf::Trace {
+                            column: 0,
+                            offset: 1,
+                        }
+                })
+                .unwrap();
+            assert_eq!(trace.0 .0[0][5].to_string(), col0_next.value);
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
+}
+
+#[test]
+fn invalid_boundary_reports_first_row() {
+    let mut trace = gen_fib_trace(16);
+    trace.0 .0[0][0] += Fp::one();
+
+    let res = validate(&trace);
+
+    assert!(matches!(
+        res,
+        Err(ConstraintError::UnsatisfiedConstraint {
+            constraint: 0,
+            row: 0,
+            ..
+        })
+    ));
+}
+
+fn validate(trace: &FibTrace) -> Result<(), ConstraintError> {
+    let challenges = Challenges::new(vec![]);
+    let hints = Hints::new(vec![]);
+    FibClaim::new().validate_constraints(&challenges, &hints, trace.base_columns(), None)
+}
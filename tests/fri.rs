@@ -0,0 +1,338 @@
+#![feature(allocator_api)]
+
+use ark_ff::FftField;
+use ark_ff::Field;
+use ark_ff::One;
+use ark_ff::UniformRand;
+use ark_ff::Zero;
+use ark_poly::EvaluationDomain;
+use ark_poly::Radix2EvaluationDomain;
+use ministark::fri;
+use ministark::fri::fold_positions;
+use ministark::fri::BatchedCodeword;
+use ministark::fri::FoldingSchedule;
+use ministark::fri::FriOptions;
+use ministark::fri::FriProof;
+use ministark::fri::FriProver;
+use ministark::fri::FriVerifier;
+use ministark::fri::RemainderCommitment;
+use ministark::hash::HashFn;
+use ministark::hash::Sha256HashFn;
+use ministark::merkle::MatrixMerkleTreeImpl;
+use ministark::merkle::MerkleTree;
+use ministark::random::PublicCoin;
+use ministark::random::PublicCoinImpl;
+use ministark::utils::GpuAllocator;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+use ministark_gpu::utils::bit_reverse;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+type Tree = MatrixMerkleTreeImpl<Sha256HashFn>;
+type Root = <Tree as MerkleTree>::Root;
+type Coin = PublicCoinImpl<Fp, Sha256HashFn>;
+type Proof = FriProof<Fp, Root, Tree>;
+
+const NUM_QUERIES: usize = 20;
+const DOMAIN_OFFSET: Fp = Fp::GENERATOR;
+
+struct Channel(Coin);
+
+impl fri::ProverChannel for Channel {
+    type Commitment = Root;
+    type Field = Fp;
+
+    fn commit_fri_layer(&mut self, layer_root: Root) {
+        self.0.reseed_with_commitment(&layer_root);
+    }
+
+    fn commit_remainder(&mut self, remainder_coeffs: &[Fp]) {
+        self.0.reseed_with_field_element_vector(remainder_coeffs);
+    }
+
+    fn draw_fri_alpha(&mut self) -> Fp {
+        self.0.draw()
+    }
+}
+
+/// Returns the coset offset of the domain of `layer`
+fn layer_domain_offset(options: FriOptions, domain_size: usize, layer: usize) -> Fp {
+    let layer_domain_size = options.layer_domain_size(domain_size, layer);
+    DOMAIN_OFFSET.pow([(domain_size / layer_domain_size) as u64])
+}
+
+fn public_coin() -> Coin {
+    PublicCoinImpl::new(Sha256HashFn::hash(*b"fri"))
+}
+
+/// Returns the bit-reversed codewords of random polynomials with the given
+/// degree bounds. Each codeword is over the domain of its layer.
+fn gen_codewords(options: FriOptions, degree_bounds: &[usize]) -> Vec<Vec<Fp>> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let max_degree_bound = *degree_bounds.iter().max().unwrap();
+    let domain_size = options.batch_domain_size(max_degree_bound);
+    degree_bounds
+        .iter()
+        .map(|&degree_bound| {
+            let layer = options.batch_layer(domain_size, degree_bound);
+            let layer_domain_size = options.layer_domain_size(domain_size, layer);
+            let offset = layer_domain_offset(options, domain_size, layer);
+            let domain = Radix2EvaluationDomain::new_coset(layer_domain_size, offset);
+            let coeffs = (0..degree_bound)
+                .map(|_| Fp::rand(&mut rng))
+                .collect::<Vec<Fp>>();
+            let mut evaluations = domain.unwrap().fft(&coeffs);
+            bit_reverse(&mut evaluations);
+            evaluations
+        })
+        .collect()
+}
+
+/// Returns the proof and query positions
+fn prove(
+    options: FriOptions,
+    degree_bounds: &[usize],
+    codewords: &[Vec<Fp>],
+) -> (Proof, Vec<usize>) {
+    let mut channel = Channel(public_coin());
+    let batch = degree_bounds
+        .iter()
+        .zip(codewords)
+        .map(|(&degree_bound, evaluations)| {
+            BatchedCodeword::new(evaluations.to_vec_in(GpuAllocator), degree_bound)
+        })
+        .collect();
+    let mut prover = FriProver::<Fp, Root, Tree>::new(options, DOMAIN_OFFSET);
+    prover.build_batched_layers(&mut channel, batch).unwrap();
+
+    let max_degree_bound = *degree_bounds.iter().max().unwrap();
+    let domain_size = options.batch_domain_size(max_degree_bound);
+    let positions = Vec::from_iter(channel.0.draw_queries(NUM_QUERIES, domain_size));
+    (prover.into_proof(&positions).unwrap(), positions)
+}
+
+fn verify(
+    options: FriOptions,
+    degree_bounds: &[usize],
+    codewords: &[Vec<Fp>],
+    proof: Proof,
+) -> Result<(), fri::VerificationError> {
+    verify_over_coset(options, degree_bounds, codewords, proof, DOMAIN_OFFSET)
+}
+
+fn verify_over_coset(
+    options: FriOptions,
+    degree_bounds: &[usize],
+    codewords: &[Vec<Fp>],
+    proof: Proof,
+    domain_offset: Fp,
+) -> Result<(), fri::VerificationError> {
+    let mut public_coin = public_coin();
+    let verifier = FriVerifier::new_batched(
+        &mut public_coin,
+        options,
+        proof,
+        degree_bounds,
+        domain_offset,
+    )?;
+
+    let max_degree_bound = *degree_bounds.iter().max().unwrap();
+    let domain_size = options.batch_domain_size(max_degree_bound);
+    let positions = Vec::from_iter(public_coin.draw_queries(NUM_QUERIES, domain_size));
+    let evaluations = degree_bounds
+        .iter()
+        .zip(codewords)
+        .map(|(&degree_bound, codeword)| {
+            let layer = options.batch_layer(domain_size, degree_bound);
+            let mut layer_positions = positions.clone();
+            for i in 0..layer {
+                let folding_factor = options.layer_folding_factor(i);
+                layer_positions = fold_positions(&layer_positions, folding_factor);
+            }
+            layer_positions.iter().map(|&p| codeword[p]).collect()
+        })
+        .collect::<Vec<Vec<Fp>>>();
+    verifier.verify_batched(&positions, &evaluations)
+}
+
+#[test]
+fn batched_polynomials_of_different_degrees_verify() {
+    let degree_bounds = [256, 100, 64, 16, 3, 1];
+
+    for folding_factor in [2, 4, 8, 16] {
+        let options = FriOptions::new(4, folding_factor, 4);
+        let codewords = gen_codewords(options, &degree_bounds);
+        let (proof, _) = prove(options, &degree_bounds, &codewords);
+
+        let res = verify(options, &degree_bounds, &codewords, proof);
+
+        assert!(res.is_ok(), "folding factor {folding_factor}: {res:?}");
+    }
+}
+
+#[test]
+fn batched_polynomials_verify_with_folding_schedule() {
+    let degree_bounds = [256, 100, 64, 16, 3, 1];
+    let options = FriOptions::new(4, 2, 4).with_folding_schedule(FoldingSchedule::new(&[8, 4]));
+    let codewords = gen_codewords(options, &degree_bounds);
+    let (proof, _) = prove(options, &degree_bounds, &codewords);
+
+    verify(options, &degree_bounds, &codewords, proof).unwrap();
+}
+
+#[test]
+fn batched_polynomials_verify_with_committed_remainder() {
+    let degree_bounds = [256, 64, 3];
+    let options = FriOptions::new(4, 4, 4).with_remainder_commitment(RemainderCommitment::Codeword);
+    let codewords = gen_codewords(options, &degree_bounds);
+    let (proof, _) = prove(options, &degree_bounds, &codewords);
+    assert!(proof.remainder_commitment.is_some());
+
+    verify(options, &degree_bounds, &codewords, proof.clone()).unwrap();
+
+    let mut tampered = proof.clone();
+    tampered.remainder_coeffs[0] += Fp::one();
+    assert!(matches!(
+        verify(options, &degree_bounds, &codewords, tampered),
+        Err(fri::VerificationError::RemainderCommitmentInvalid)
+    ));
+    assert!(matches!(
+        verify(FriOptions::new(4, 4, 4), &degree_bounds, &codewords, proof),
+        Err(fri::VerificationError::RemainderCommitmentMismatch)
+    ));
+}
+
+#[test]
+fn apply_drp_matches_folded_coefficients() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let domain = Radix2EvaluationDomain::new_coset(64, DOMAIN_OFFSET).unwrap();
+    let coeffs = (0..64).map(|_| Fp::rand(&mut rng)).collect::<Vec<Fp>>();
+    let alpha = Fp::rand(&mut rng);
+    let mut evaluations = domain.fft(&coeffs);
+    bit_reverse(&mut evaluations);
+
+    for folding_factor in [2, 4, 8, 16] {
+        let drp = fri::apply_drp(&evaluations, DOMAIN_OFFSET, alpha, folding_factor).unwrap();
+
+        // f'(x) = folding_factor * (f_0(x) + α * f_1(x) + α^2 * f_2(x) + ...)
+        let drp_coeffs = coeffs
+            .chunks(folding_factor)
+            .map(|chunk| {
+                let combined = chunk.iter().rfold(Fp::zero(), |acc, c| acc * alpha + c);
+                combined * Fp::from(folding_factor as u64)
+            })
+            .collect::<Vec<Fp>>();
+        let drp_offset = DOMAIN_OFFSET.pow([folding_factor as u64]);
+        let drp_domain = Radix2EvaluationDomain::new_coset(64 / folding_factor, drp_offset);
+        let mut expected = drp_domain.unwrap().fft(&drp_coeffs);
+        bit_reverse(&mut expected);
+        assert_eq!(expected, *drp, "folding factor {folding_factor}");
+    }
+}
+
+#[test]
+fn apply_drp_rejects_unsupported_folding_factor() {
+    let evaluations = vec![Fp::one(); 64];
+
+    let res = fri::apply_drp(&evaluations, DOMAIN_OFFSET, Fp::one(), 32);
+
+    assert!(matches!(
+        res,
+        Err(fri::ProvingError::UnsupportedFoldingFactor { folding_factor: 32 })
+    ));
+}
+
+#[test]
+fn batched_polynomials_are_injected_into_matching_layers() {
+    let options = FriOptions::new(4, 4, 4);
+    // layers have degree bounds 256, 64 and 16 followed by a remainder of 4
+    let layers =
+        [256, 100, 64, 16, 8, 3, 1].map(|degree_bound| options.batch_layer(1024, degree_bound));
+
+    assert_eq!([0, 0, 1, 2, 2, 3, 3], layers);
+}
+
+#[test]
+fn verify_rejects_lower_degree_bound() {
+    let options = FriOptions::new(4, 4, 4);
+    let degree_bounds = [256, 16];
+    let codewords = gen_codewords(options, &degree_bounds);
+    let (proof, _) = prove(options, &degree_bounds, &codewords);
+
+    // both degree bounds share a layer but require different degree corrections
+    let res = verify(options, &[256, 8], &codewords, proof);
+
+    assert!(res.is_err());
+}
+
+#[test]
+fn verify_rejects_tampered_evaluations() {
+    let options = FriOptions::new(4, 2, 4);
+    let degree_bounds = [256, 64, 2];
+    let mut codewords = gen_codewords(options, &degree_bounds);
+    let (proof, positions) = prove(options, &degree_bounds, &codewords);
+
+    // the smallest codeword is queried at positions folded down to the remainder
+    let layer = options.batch_layer(1024, 2);
+    codewords[2][positions[0] >> layer] += Fp::one();
+    let res = verify(options, &degree_bounds, &codewords, proof);
+
+    assert!(matches!(
+        res,
+        Err(fri::VerificationError::RemainderCommitmentInvalid)
+    ));
+}
+
+#[test]
+fn prover_rejects_unsupported_folding_factor() {
+    let options = FriOptions::new(4, 32, 4);
+    let codewords = gen_codewords(options, &[256]);
+    let codeword = BatchedCodeword::new(codewords[0].to_vec_in(GpuAllocator), 256);
+    let mut prover = FriProver::<Fp, Root, Tree>::new(options, DOMAIN_OFFSET);
+
+    let res = prover.build_batched_layers(&mut Channel(public_coin()), vec![codeword]);
+
+    assert!(matches!(
+        res,
+        Err(fri::ProvingError::UnsupportedFoldingFactor { folding_factor: 32 })
+    ));
+}
+
+#[test]
+fn verify_rejects_mismatched_domain_offset() {
+    let options = FriOptions::new(4, 4, 4);
+    let degree_bounds = [256, 16];
+    let codewords = gen_codewords(options, &degree_bounds);
+    let (proof, _) = prove(options, &degree_bounds, &codewords);
+
+    let res = verify_over_coset(options, &degree_bounds, &codewords, proof, Fp::one());
+
+    assert!(res.is_err());
+}
+
+#[test]
+fn verify_rejects_invalid_batches() {
+    let options = FriOptions::new(4, 2, 4);
+    let degree_bounds = [256, 64];
+    let codewords = gen_codewords(options, &degree_bounds);
+    let (proof, _) = prove(options, &degree_bounds, &codewords);
+
+    assert!(matches!(
+        verify(options, &[256, 0], &codewords, proof.clone()),
+        Err(fri::VerificationError::InvalidBatchDegreeBound)
+    ));
+    assert!(matches!(
+        verify(options, &degree_bounds, &codewords[..1], proof),
+        Err(fri::VerificationError::BatchSizeMismatch { .. })
+    ));
+    assert!(matches!(
+        FriVerifier::<Fp, Root, Tree>::new_batched(
+            &mut public_coin(),
+            options,
+            Proof::new(Vec::new(), Vec::new()),
+            &[],
+            DOMAIN_OFFSET,
+        ),
+        Err(fri::VerificationError::EmptyBatch)
+    ));
+}
@@ -0,0 +1,123 @@
+//! Feeds randomly mutated fibonacci proofs to the verifier. Mutated proofs may
+//! be rejected at any point but must never cause the verifier to panic.
+//! Set `FUZZ_ITERATIONS` to change the number of mutated proofs per test.
+#![feature(allocator_api)]
+mod common;
+
+use ark_ff::UniformRand;
+use ark_serialize::CanonicalDeserialize;
+use ark_serialize::CanonicalSerialize;
+use common::gen_fib_trace;
+use common::FibClaim;
+use common::OPTIONS;
+use ministark::stark::Stark;
+use ministark::Proof;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+
+fn num_iterations() -> u64 {
+    std::env::var("FUZZ_ITERATIONS").map_or(256, |n| n.parse().unwrap())
+}
+
+fn gen_proof() -> Proof<FibClaim> {
+    pollster::block_on(FibClaim::new().prove(OPTIONS, gen_fib_trace(16))).unwrap()
+}
+
+/// Verifies the proof and panics with `description` if the verifier panics
+fn verify_without_panic(proof: Proof<FibClaim>, description: &str) {
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        // the result is irrelevant since some mutations don't invalidate the proof
+        let _ = FibClaim::new().verify(proof, 0);
+    }));
+    assert!(res.is_ok(), "verifier panicked on {description}");
+}
+
+#[test]
+fn verifier_does_not_panic_on_mutated_bytes() {
+    let proof = gen_proof();
+    let envelope = proof.to_bytes();
+    let mut payload = Vec::new();
+    proof.serialize_compressed(&mut payload).unwrap();
+
+    for seed in 0..num_iterations() {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let description = format!("mutated bytes (seed {seed})");
+
+        let envelope = mutate_bytes(&mut rng, envelope.clone());
+        let res = catch_unwind(|| Proof::<FibClaim>::from_bytes(&envelope));
+        assert!(res.is_ok(), "from_bytes panicked on {description}");
+
+        // deserialize without validation so malformed proofs reach the verifier
+        let payload = mutate_bytes(&mut rng, payload.clone());
+        let res = catch_unwind(|| Proof::<FibClaim>::deserialize_compressed_unchecked(&*payload));
+        let res = res.unwrap_or_else(|_| panic!("deserialization panicked on {description}"));
+        if let Ok(proof) = res {
+            verify_without_panic(proof, &description);
+        }
+    }
+}
+
+#[test]
+fn verifier_does_not_panic_on_mutated_proofs() {
+    let proof = gen_proof();
+
+    for seed in 0..num_iterations() {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let mut proof = proof.clone();
+        let mutation = rng.gen_range(0..10);
+        match mutation {
+            0 => proof.trace_len = 1 << rng.gen_range(0..usize::BITS),
+            1 => proof.options.num_queries = rng.gen(),
+            2 => proof.options.lde_blowup_factor = rng.gen(),
+            3 => proof.options.fri_folding_factor = rng.gen(),
+            4 => proof.options.fri_max_remainder_coeffs = rng.gen(),
+            5 => mutate_vec(&mut rng, &mut proof.trace_queries.base_trace_values),
+            6 => mutate_vec(&mut rng, &mut proof.trace_queries.composition_trace_values),
+            7 => mutate_vec(&mut rng, &mut proof.execution_trace_ood_evals),
+            8 => mutate_vec(&mut rng, &mut proof.fri_proof.remainder_coeffs),
+            _ => {
+                let layers = &mut proof.fri_proof.layers;
+                if rng.gen() {
+                    layers.shuffle(&mut rng);
+                    layers.truncate(rng.gen_range(0..=layers.len()));
+                } else if let Some(layer) = layers.choose_mut(&mut rng) {
+                    mutate_vec(&mut rng, &mut layer.flattenend_rows);
+                }
+            }
+        }
+
+        verify_without_panic(proof, &format!("mutation {mutation} (seed {seed})"));
+    }
+}
+
+/// Flips bits, overwrites bytes, truncates or inserts bytes
+fn mutate_bytes(rng: &mut impl Rng, mut bytes: Vec<u8>) -> Vec<u8> {
+    for _ in 0..rng.gen_range(1..4) {
+        if bytes.is_empty() {
+            break;
+        }
+        let i = rng.gen_range(0..bytes.len());
+        match rng.gen_range(0..4) {
+            0 => bytes[i] ^= 1 << rng.gen_range(0..8),
+            1 => bytes[i] = rng.gen(),
+            2 => bytes.truncate(i),
+            _ => bytes.insert(i, rng.gen()),
+        }
+    }
+    bytes
+}
+
+/// Randomly changes, removes or inserts an element
+fn mutate_vec(rng: &mut impl Rng, values: &mut Vec<Fp>) {
+    let len = values.len();
+    match rng.gen_range(0..3) {
+        0 if len != 0 => values[rng.gen_range(0..len)] = Fp::rand(rng),
+        1 if len != 0 => drop(values.remove(rng.gen_range(0..len))),
+        _ => values.insert(rng.gen_range(0..=len), Fp::rand(rng)),
+    }
+}
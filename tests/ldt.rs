@@ -0,0 +1,205 @@
+#![feature(allocator_api)]
+
+use ark_ff::FftField;
+use ark_ff::One;
+use ark_ff::UniformRand;
+use ark_ff::Zero;
+use ark_poly::EvaluationDomain;
+use ark_poly::Radix2EvaluationDomain;
+use ark_serialize::CanonicalDeserialize;
+use ark_serialize::CanonicalSerialize;
+use ministark::fri;
+use ministark::hash::HashFn;
+use ministark::hash::Sha256HashFn;
+use ministark::ldt;
+use ministark::ldt::BatchCommitment;
+use ministark::ldt::LdtOptions;
+use ministark::ldt::LdtProof;
+use ministark::ldt::ProvingError;
+use ministark::ldt::VerificationError;
+use ministark::merkle::MatrixMerkleTreeImpl;
+use ministark::random::PublicCoin;
+use ministark::random::PublicCoinImpl;
+use ministark::utils::GpuAllocator;
+use ministark::Matrix;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+type MerkleTree = MatrixMerkleTreeImpl<Sha256HashFn>;
+type Commitment = BatchCommitment<Fp, MerkleTree>;
+type Proof = LdtProof<Fp, MerkleTree>;
+
+const OPTIONS: LdtOptions = LdtOptions::new(16, 4, 8, 4);
+const DEGREE_BOUND: usize = 256;
+const DOMAIN_SIZE: usize = 2048;
+
+fn public_coin() -> PublicCoinImpl<Fp, Sha256HashFn> {
+    PublicCoinImpl::new(Sha256HashFn::hash(*b"ldt"))
+}
+
+/// Coset of the evaluation domain offset by a power of the generator
+fn domain() -> Radix2EvaluationDomain<Fp> {
+    let offset = Fp::GENERATOR * Fp::GENERATOR;
+    Radix2EvaluationDomain::new_coset(DOMAIN_SIZE, offset).unwrap()
+}
+
+/// Returns random polynomials with the given number of coefficients. Columns
+/// are padded with zero coefficients to the same length.
+fn gen_polynomials(num_coeffs: &[usize]) -> Matrix<Fp> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let num_rows = *num_coeffs.iter().max().unwrap();
+    Matrix::new(
+        num_coeffs
+            .iter()
+            .map(|&n| {
+                let mut column = Vec::new_in(GpuAllocator);
+                column.extend((0..n).map(|_| Fp::rand(&mut rng)));
+                column.resize(num_rows, Fp::zero());
+                column
+            })
+            .collect(),
+    )
+}
+
+fn prove(commitment: &Commitment) -> Result<Proof, ProvingError> {
+    ldt::prove(OPTIONS, commitment, DEGREE_BOUND, &mut public_coin())
+}
+
+fn verify(commitment: &Commitment, proof: Proof) -> Result<Vec<usize>, VerificationError> {
+    let num_columns = commitment.evaluations().num_cols();
+    let root = commitment.root();
+    let (domain, mut coin) = (commitment.domain(), public_coin());
+    ldt::verify(
+        OPTIONS,
+        &root,
+        num_columns,
+        domain,
+        DEGREE_BOUND,
+        proof,
+        &mut coin,
+    )
+}
+
+#[test]
+fn verifies_low_degree_batch() {
+    let polynomials = gen_polynomials(&[DEGREE_BOUND, DEGREE_BOUND / 2, 1]);
+    let commitment = Commitment::from_polynomials(polynomials, domain()).unwrap();
+
+    let proof = prove(&commitment).unwrap();
+    let positions = verify(&commitment, proof.clone()).unwrap();
+
+    assert_eq!(OPTIONS.num_queries, positions.len());
+    let evaluations = commitment.evaluations();
+    let query_values = positions
+        .iter()
+        .flat_map(|&i| evaluations.get_row(i).unwrap());
+    assert!(query_values.eq(proof.query_values));
+}
+
+#[test]
+fn commitment_from_evaluations_matches_polynomials() {
+    let polynomials = gen_polynomials(&[DEGREE_BOUND, 3]);
+    let evaluations = polynomials.evaluate(domain());
+
+    let from_polynomials = Commitment::from_polynomials(polynomials, domain()).unwrap();
+    let from_evaluations = Commitment::from_evaluations(evaluations, domain()).unwrap();
+
+    assert_eq!(from_polynomials.root(), from_evaluations.root());
+    let proof = prove(&from_evaluations).unwrap();
+    verify(&from_polynomials, proof).unwrap();
+}
+
+#[test]
+fn proof_serialization_roundtrip() {
+    let polynomials = gen_polynomials(&[DEGREE_BOUND]);
+    let commitment = Commitment::from_polynomials(polynomials, domain()).unwrap();
+    let proof = prove(&commitment).unwrap();
+    let mut bytes = Vec::new();
+    proof.serialize_compressed(&mut bytes).unwrap();
+
+    let proof = Proof::deserialize_compressed(&*bytes).unwrap();
+
+    verify(&commitment, proof).unwrap();
+}
+
+#[test]
+fn prove_rejects_polynomials_exceeding_degree_bound() {
+    let polynomials = gen_polynomials(&[DEGREE_BOUND, DEGREE_BOUND + 1]);
+    let commitment = Commitment::from_polynomials(polynomials, domain()).unwrap();
+
+    let res = prove(&commitment);
+
+    assert!(matches!(res, Err(ProvingError::DegreeBoundExceeded { .. })));
+}
+
+#[test]
+fn verify_rejects_smaller_degree_bound() {
+    let polynomials = gen_polynomials(&[DEGREE_BOUND]);
+    let commitment = Commitment::from_polynomials(polynomials, domain()).unwrap();
+    let proof = prove(&commitment).unwrap();
+    let root = commitment.root();
+
+    let res = ldt::verify(
+        OPTIONS,
+        &root,
+        1,
+        domain(),
+        DEGREE_BOUND / 2,
+        proof,
+        &mut public_coin(),
+    );
+
+    assert!(matches!(
+        res,
+        Err(VerificationError::FriVerification {
+            source: fri::VerificationError::LayerCountMismatch { .. }
+        })
+    ));
+}
+
+#[test]
+fn verify_rejects_tampered_proofs() {
+    let polynomials = gen_polynomials(&[DEGREE_BOUND, DEGREE_BOUND]);
+    let commitment = Commitment::from_polynomials(polynomials, domain()).unwrap();
+    let proof = prove(&commitment).unwrap();
+
+    let mut tampered = proof.clone();
+    tampered.query_values[0] += Fp::one();
+    assert!(matches!(
+        verify(&commitment, tampered),
+        Err(VerificationError::QueryDoesNotMatchCommitment)
+    ));
+
+    let mut tampered = proof.clone();
+    tampered.query_values.pop();
+    assert!(matches!(
+        verify(&commitment, tampered),
+        Err(VerificationError::QueryValueCountMismatch { .. })
+    ));
+
+    let mut tampered = proof.clone();
+    tampered.fri_proof.remainder_coeffs[0] += Fp::one();
+    assert!(verify(&commitment, tampered).is_err());
+
+    let mut tampered = proof;
+    tampered.pow_nonce = tampered.pow_nonce.wrapping_add(1);
+    assert!(verify(&commitment, tampered).is_err());
+}
+
+#[test]
+fn options_reject_invalid_parameters() {
+    let invalid = [
+        (OPTIONS, DEGREE_BOUND + 1),
+        (OPTIONS, DOMAIN_SIZE),
+        (LdtOptions::new(0, 4, 8, 0), DEGREE_BOUND),
+        (LdtOptions::new(16, 3, 8, 0), DEGREE_BOUND),
+        (LdtOptions::new(16, 4, 0, 0), DEGREE_BOUND),
+        (LdtOptions::new(16, 4, 8, 51), DEGREE_BOUND),
+    ];
+
+    for (options, degree_bound) in invalid {
+        assert!(options.fri_options(degree_bound, DOMAIN_SIZE).is_err());
+    }
+    assert!(OPTIONS.fri_options(DEGREE_BOUND, DOMAIN_SIZE).is_ok());
+}
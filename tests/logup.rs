@@ -0,0 +1,252 @@
+#![feature(allocator_api)]
+mod common;
+
+use ark_ff::Field;
+use ark_ff::One;
+use ark_ff::Zero;
+use ark_poly::EvaluationDomain;
+use ark_poly::Radix2EvaluationDomain;
+use common::OPTIONS;
+use ministark::air::AirConfig;
+use ministark::challenges::Challenges;
+use ministark::constraints::AlgebraicItem;
+use ministark::constraints::Constraint;
+use ministark::constraints::ExecutionTraceColumn;
+use ministark::hash::Sha256HashFn;
+use ministark::hints::Hints;
+use ministark::logup::LogUp;
+use ministark::merkle::MatrixMerkleTreeImpl;
+use ministark::prover::ProvingError;
+use ministark::random::PublicCoinImpl;
+use ministark::stark::Stark;
+use ministark::tamper::check_tampered_proofs;
+use ministark::utils::FieldVariant;
+use ministark::utils::GpuAllocator;
+use ministark::utils::SerdeOutput;
+use ministark::Matrix;
+use ministark::Trace;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+use num_traits::Pow;
+use sha2::Sha256;
+
+const TRACE_LEN: usize = 64;
+
+/// Looks up `(a, a^2)` in a table of squares `(i, i^2)` for `i` in `0..64`
+fn squares_logup() -> LogUp<Fp, Fp> {
+    LogUp::new(0, 1, 5, 0)
+        .lookup([0.curr(), 1.curr()])
+        .table([2.curr(), 3.curr()], 4)
+}
+
+struct SquaresTrace(Matrix<Fp>);
+
+impl Trace for SquaresTrace {
+    type Fp = Fp;
+    type Fq = Fp;
+
+    fn base_columns(&self) -> &Matrix<Fp> {
+        &self.0
+    }
+
+    fn build_extension_columns(&self, challenges: &Challenges<Fp>) -> Option<Matrix<Fp>> {
+        Some(squares_logup().build_columns(self, challenges, &[]))
+    }
+}
+
+fn gen_squares_trace(values: impl IntoIterator<Item = u64>) -> SquaresTrace {
+    let values = Vec::from_iter(values);
+    assert_eq!(TRACE_LEN, values.len());
+    let mut multiplicities = vec![0; TRACE_LEN];
+    for &v in &values {
+        if let Some(m) = multiplicities.get_mut(v as usize) {
+            *m += 1;
+        }
+    }
+
+    let column = |values: &mut dyn Iterator<Item = Fp>| {
+        let mut column = Vec::new_in(GpuAllocator);
+        column.extend(values);
+        column
+    };
+    let table = (0..TRACE_LEN as u64).map(Fp::from);
+    SquaresTrace(Matrix::new(vec![
+        column(&mut values.iter().map(|&v| Fp::from(v))),
+        column(&mut values.iter().map(|&v| Fp::from(v).square())),
+        column(&mut table.clone()),
+        column(&mut table.map(|v| v.square())),
+        column(&mut multiplicities.into_iter().map(Fp::from)),
+    ]))
+}
+
+struct SquaresAirConfig;
+
+impl AirConfig for SquaresAirConfig {
+    const NUM_BASE_COLUMNS: usize = 5;
+    const NUM_EXTENSION_COLUMNS: usize = 3;
+    const NAME: &'static str = "squares_lookup";
+    type Fp = Fp;
+    type Fq = Fp;
+    type PublicInputs = ();
+
+    fn gen_hints(_: usize, _: &(), _: &Challenges<Fp>) -> Hints<Fp> {
+        // every lookup is into a table in the trace
+        Hints::new(vec![squares_logup().final_sum_hint(Fp::zero())])
+    }
+
+    fn constraints(trace_len: usize) -> Vec<Constraint<FieldVariant<Fp, Fp>>> {
+        use AlgebraicItem::*;
+        let trace_xs = Radix2EvaluationDomain::<Fp>::new(trace_len).unwrap();
+        let first_trace_x = Constant(FieldVariant::Fp(trace_xs.element(0)));
+        let last_trace_x = Constant(FieldVariant::Fp(trace_xs.element(trace_len - 1)));
+        let one = Constant(FieldVariant::Fp(Fp::one()));
+
+        let every_row = X.pow(trace_len) - one;
+        let every_row_except_last = (X - last_trace_x) / &every_row;
+        let table_constraints = [
+            (0.curr().pow(2) - 1.curr()) / &every_row,
+            2.curr() / (X - first_trace_x),
+            (2.next() - 2.curr() - one) * &every_row_except_last,
+            (2.curr().pow(2) - 3.curr()) / &every_row,
+        ];
+        (table_constraints.into_iter().map(Constraint::new))
+            .chain(squares_logup().constraints(trace_len))
+            .collect()
+    }
+}
+
+struct SquaresClaim;
+
+impl Stark for SquaresClaim {
+    type Fp = Fp;
+    type Fq = Fp;
+    type AirConfig = SquaresAirConfig;
+    type Digest = SerdeOutput<Sha256>;
+    type PublicCoin = PublicCoinImpl<Fp, Sha256HashFn>;
+    type MerkleTree = MatrixMerkleTreeImpl<Sha256HashFn>;
+    type Witness = SquaresTrace;
+    type Trace = SquaresTrace;
+
+    fn get_public_inputs(&self) {}
+
+    fn generate_trace(&self, witness: SquaresTrace) -> SquaresTrace {
+        witness
+    }
+}
+
+#[test]
+fn lookup_proof_verifies() {
+    for options in [OPTIONS, OPTIONS.with_zero_knowledge(true)] {
+        let trace = gen_squares_trace((0..TRACE_LEN as u64).map(|i| i * 7 % 13));
+
+        let proof = pollster::block_on(SquaresClaim.prove(options, trace)).unwrap();
+
+        SquaresClaim.verify(proof.clone(), 0).unwrap();
+        check_tampered_proofs(&SquaresClaim, &proof);
+    }
+}
+
+#[test]
+fn build_columns_ends_with_the_final_sum() {
+    let trace = gen_squares_trace((0..TRACE_LEN as u64).map(|i| i / 2));
+    let challenges = Challenges::new(vec![Fp::from(1234u64), Fp::from(5678u64)]);
+
+    let columns = squares_logup().build_columns(&trace, &challenges, &[]);
+
+    assert_eq!(squares_logup().num_columns(), columns.num_cols());
+    let running_sum = &columns.0[squares_logup().running_sum_column() - 5];
+    assert_eq!(Fp::zero(), running_sum[TRACE_LEN - 1]);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn prove_returns_error_for_value_missing_from_table() {
+    let trace = gen_squares_trace((0..TRACE_LEN as u64).map(|i| if i == 5 { 100 } else { i }));
+
+    let res = pollster::block_on(SquaresClaim.prove(OPTIONS, trace));
+
+    assert!(matches!(
+        res,
+        Err(ProvingError::UnsatisfiedConstraints { .. })
+    ));
+}
+
+/// Looks up every value of column 0 in a public table
+fn public_logup() -> LogUp<Fp, Fp> {
+    LogUp::new(0, 1, 1, 0).lookup([0.curr()])
+}
+
+struct PublicTableTrace(Matrix<Fp>);
+
+impl Trace for PublicTableTrace {
+    type Fp = Fp;
+    type Fq = Fp;
+
+    fn base_columns(&self) -> &Matrix<Fp> {
+        &self.0
+    }
+
+    fn build_extension_columns(&self, challenges: &Challenges<Fp>) -> Option<Matrix<Fp>> {
+        Some(public_logup().build_columns(self, challenges, &[]))
+    }
+}
+
+struct PublicTableAirConfig;
+
+impl AirConfig for PublicTableAirConfig {
+    const NUM_BASE_COLUMNS: usize = 1;
+    const NUM_EXTENSION_COLUMNS: usize = 2;
+    const NAME: &'static str = "public_table_lookup";
+    type Fp = Fp;
+    type Fq = Fp;
+    type PublicInputs = Vec<Fp>;
+
+    fn gen_hints(_: usize, table: &Vec<Fp>, challenges: &Challenges<Fp>) -> Hints<Fp> {
+        let logup = public_logup();
+        let final_sum = (table.iter())
+            .map(|&v| logup.fraction(challenges, &[v], Fp::one()))
+            .sum();
+        Hints::new(vec![logup.final_sum_hint(final_sum)])
+    }
+
+    fn constraints(trace_len: usize) -> Vec<Constraint<FieldVariant<Fp, Fp>>> {
+        public_logup().constraints(trace_len)
+    }
+}
+
+struct PublicTableClaim(Vec<Fp>);
+
+impl Stark for PublicTableClaim {
+    type Fp = Fp;
+    type Fq = Fp;
+    type AirConfig = PublicTableAirConfig;
+    type Digest = SerdeOutput<Sha256>;
+    type PublicCoin = PublicCoinImpl<Fp, Sha256HashFn>;
+    type MerkleTree = MatrixMerkleTreeImpl<Sha256HashFn>;
+    type Witness = Vec<Fp>;
+    type Trace = PublicTableTrace;
+
+    fn get_public_inputs(&self) -> Vec<Fp> {
+        self.0.clone()
+    }
+
+    fn generate_trace(&self, witness: Vec<Fp>) -> PublicTableTrace {
+        let mut column = Vec::new_in(GpuAllocator);
+        column.extend(witness);
+        PublicTableTrace(Matrix::new(vec![column]))
+    }
+}
+
+#[test]
+fn lookup_into_public_table_verifies() {
+    let values = Vec::from_iter((0..TRACE_LEN as u64).map(|i| Fp::from(i * i + 3)));
+    let mut table = values.clone();
+    table.reverse();
+    let claim = PublicTableClaim(table);
+
+    let proof = pollster::block_on(claim.prove(OPTIONS, values.clone())).unwrap();
+    claim.verify(proof.clone(), 0).unwrap();
+
+    let mut wrong_table = values;
+    wrong_table[0] += Fp::one();
+    assert!(PublicTableClaim(wrong_table).verify(proof, 0).is_err());
+}
@@ -0,0 +1,221 @@
+#![feature(allocator_api)]
+mod common;
+
+use ark_ff::One;
+use common::gen_fib_trace;
+use common::FibClaim;
+use common::OPTIONS;
+use ministark::fri::FoldingSchedule;
+use ministark::fri::RemainderCommitment;
+use ministark::proof::Error;
+use ministark::proof::ProofHeader;
+use ministark::stark::Stark;
+use ministark::verifier::VerificationError;
+use ministark::Proof;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+
+fn gen_proof() -> Proof<FibClaim> {
+    pollster::block_on(FibClaim::new().prove(OPTIONS, gen_fib_trace(16))).unwrap()
+}
+
+#[test]
+fn proof_bytes_roundtrip() {
+    let bytes = gen_proof().to_bytes();
+
+    let proof = Proof::<FibClaim>::from_bytes(&bytes).unwrap();
+
+    assert_eq!(bytes, proof.to_bytes());
+    FibClaim::new().verify(proof, 0).unwrap();
+}
+
+#[test]
+fn proof_bytes_roundtrip_with_folding_schedule() {
+    let options = OPTIONS.with_fri_folding_schedule(&[4]);
+    let proof = pollster::block_on(FibClaim::new().prove(options, gen_fib_trace(16)));
+    let bytes = proof.unwrap().to_bytes();
+
+    let proof = Proof::<FibClaim>::from_bytes(&bytes).unwrap();
+
+    assert_eq!(&[4], proof.options.fri_folding_schedule.as_slice());
+    FibClaim::new().verify(proof, 0).unwrap();
+}
+
+#[test]
+fn proof_bytes_roundtrip_with_committed_remainder() {
+    let options = OPTIONS.with_fri_remainder_commitment(RemainderCommitment::Codeword);
+    let proof = pollster::block_on(FibClaim::new().prove(options, gen_fib_trace(16)));
+    let bytes = proof.unwrap().to_bytes();
+
+    let proof = Proof::<FibClaim>::from_bytes(&bytes).unwrap();
+
+    assert!(proof.fri_proof.remainder_commitment.is_some());
+    assert_eq!(bytes, proof.to_bytes());
+    FibClaim::new().verify(proof, 0).unwrap();
+}
+
+#[test]
+fn from_bytes_rejects_invalid_magic() {
+    let mut bytes = gen_proof().to_bytes();
+    bytes[0] ^= 1;
+
+    let res = Proof::<FibClaim>::from_bytes(&bytes);
+
+    assert!(matches!(res, Err(Error::InvalidMagic)));
+}
+
+#[test]
+fn from_bytes_rejects_truncated_payload() {
+    let mut bytes = gen_proof().to_bytes();
+    bytes.pop();
+
+    let res = Proof::<FibClaim>::from_bytes(&bytes);
+
+    assert!(matches!(res, Err(Error::PayloadLengthMismatch { .. })));
+}
+
+#[test]
+fn from_bytes_rejects_lengths_exceeding_options() {
+    let mut proof = gen_proof();
+    // each fri layer opens at most `num_queries` rows
+    let num_values = OPTIONS.num_queries as usize * OPTIONS.fri_folding_factor as usize;
+    proof.fri_proof.layers[0].flattenend_rows = vec![Fp::one(); num_values + 2];
+
+    let res = Proof::<FibClaim>::from_bytes(&proof.to_bytes());
+
+    assert!(matches!(res, Err(Error::MalformedPayload)));
+}
+
+#[test]
+fn from_bytes_rejects_oversized_merkle_proofs() {
+    let mut proof = gen_proof();
+    // a proof opens at most `num_queries` leaves
+    let leaves = &mut proof.trace_queries.base_trace_proof.view.initial_leaves;
+    leaves.extend(vec![leaves[0].clone(); OPTIONS.num_queries as usize + 1]);
+
+    let res = Proof::<FibClaim>::from_bytes(&proof.to_bytes());
+
+    assert!(matches!(res, Err(Error::MalformedPayload)));
+}
+
+#[test]
+fn from_bytes_rejects_excess_ood_evals() {
+    let mut proof = gen_proof();
+    let num_evals = proof.execution_trace_ood_evals.len();
+    proof.execution_trace_ood_evals = vec![Fp::one(); num_evals + 1];
+
+    let res = Proof::<FibClaim>::from_bytes(&proof.to_bytes());
+
+    assert!(matches!(res, Err(Error::MalformedPayload)));
+}
+
+#[test]
+fn header_check_rejects_mismatches() {
+    let header = ProofHeader::new::<FibClaim>(0);
+    header.check::<FibClaim>().unwrap();
+
+    let field = ProofHeader {
+        field: "p3618502788666131213697322783095070105623107215331596699973092056135872020481_fp"
+            .to_string(),
+        ..header.clone()
+    };
+    let hash = ProofHeader {
+        hash: "blake3".to_string(),
+        ..header.clone()
+    };
+    let merkle_tree = ProofHeader {
+        merkle_tree: "sha256_cap2".to_string(),
+        ..header.clone()
+    };
+    let air = ProofHeader {
+        air: "brainfuck".to_string(),
+        ..header.clone()
+    };
+    let version = ProofHeader {
+        version: header.version + 1,
+        ..header
+    };
+
+    assert!(matches!(
+        field.check::<FibClaim>(),
+        Err(Error::FieldMismatch { .. })
+    ));
+    assert!(matches!(
+        hash.check::<FibClaim>(),
+        Err(Error::HashMismatch { .. })
+    ));
+    assert!(matches!(
+        merkle_tree.check::<FibClaim>(),
+        Err(Error::MerkleTreeMismatch { .. })
+    ));
+    assert!(matches!(
+        air.check::<FibClaim>(),
+        Err(Error::AirMismatch { .. })
+    ));
+    assert!(matches!(
+        version.check::<FibClaim>(),
+        Err(Error::UnsupportedVersion { .. })
+    ));
+}
+
+#[test]
+fn verify_rejects_malformed_proofs() {
+    let verify = |mutate: fn(&mut Proof<FibClaim>)| {
+        let mut proof = gen_proof();
+        mutate(&mut proof);
+        match FibClaim::new().verify(proof, 0) {
+            Err(VerificationError::MalformedProof { source }) => source,
+            res => panic!("unexpected result: {res:?}"),
+        }
+    };
+
+    assert!(matches!(
+        verify(|proof| proof.trace_len = usize::MAX),
+        Error::InvalidTraceLength { .. }
+    ));
+    assert!(matches!(
+        verify(|proof| proof.options.fri_folding_factor = 3),
+        Error::InvalidOptions
+    ));
+    assert!(matches!(
+        verify(|proof| {
+            proof.trace_queries.base_trace_values.pop();
+        }),
+        Error::InvalidLength {
+            name: "base_trace_values",
+            ..
+        }
+    ));
+    assert!(matches!(
+        verify(|proof| proof
+            .extension_trace_commitments
+            .push(proof.base_trace_commitment.clone())),
+        Error::ExtensionTraceMismatch
+    ));
+    assert!(matches!(
+        verify(|proof| proof.options.fri_folding_schedule = FoldingSchedule::new(&[16])),
+        Error::InvalidLength {
+            name: "fri_layers",
+            ..
+        }
+    ));
+    assert!(matches!(
+        verify(|proof| proof.fri_proof.remainder_commitment = Some(Default::default())),
+        Error::FriRemainderCommitmentMismatch
+    ));
+    assert!(matches!(
+        verify(|proof| proof.fri_proof.remainder_coeffs.push(Fp::one())),
+        Error::InvalidLength {
+            name: "fri_remainder_coeffs",
+            ..
+        }
+    ));
+    assert!(matches!(
+        verify(|proof| {
+            proof.execution_trace_ood_evals.pop();
+        }),
+        Error::InvalidLength {
+            name: "execution_trace_ood_evals",
+            ..
+        }
+    ));
+}
@@ -0,0 +1,571 @@
+#![feature(allocator_api)]
+mod common;
+
+use ark_ff::FftField;
+use ark_ff::Field;
+use ark_ff::One;
+use ark_poly::EvaluationDomain;
+use ark_poly::Radix2EvaluationDomain;
+use common::gen_fib_trace;
+use common::FibAirConfig;
+use common::FibClaim;
+use common::FibTrace;
+use common::OPTIONS;
+use ministark::air;
+use ministark::air::AirConfig;
+use ministark::air::TraceSegment;
+use ministark::challenges::Challenges;
+use ministark::constraints::AlgebraicItem;
+use ministark::constraints::Constraint;
+use ministark::constraints::ExecutionTraceColumn;
+use ministark::debug::ConstraintError;
+use ministark::fri::RemainderCommitment;
+use ministark::hash::poseidon2::Poseidon2GoldilocksHashFn;
+use ministark::hash::rpo::Rpo256HashFn;
+use ministark::hash::MaskedKeccak256HashFn;
+use ministark::hash::Sha256HashFn;
+use ministark::merkle::MatrixMerkleCapTreeImpl;
+use ministark::merkle::MatrixMerkleTreeImpl;
+use ministark::merkle::MerkleTree;
+use ministark::prover::ProvingError;
+use ministark::random::FieldPublicCoin;
+use ministark::random::PublicCoin;
+use ministark::random::PublicCoinImpl;
+use ministark::stark::Stark;
+use ministark::tamper::check_tampered_proofs;
+use ministark::telemetry::Event;
+use ministark::telemetry::Phase;
+use ministark::telemetry::ProverObserver;
+use ministark::utils::FieldVariant;
+use ministark::utils::GpuAllocator;
+use ministark::verifier::VerificationError;
+use ministark::Air;
+use ministark::Matrix;
+use ministark::ProofOptions;
+use ministark::Trace;
+use ministark_gpu::fields::p18446744069414584321::ark::Fp;
+use num_traits::Pow;
+use std::sync::Mutex;
+
+#[test]
+fn prove_returns_error_for_too_many_queries() {
+    // lde domain has `16 * 4 = 64` positions
+    let options = ProofOptions::new(65, 4, 0, 2, 4);
+
+    let res = pollster::block_on(FibClaim::new().prove(options, gen_fib_trace(16)));
+
+    assert!(matches!(
+        res,
+        Err(ProvingError::InvalidAir {
+            source: air::Error::TooManyQueries {
+                num_queries: 65,
+                lde_domain_size: 64,
+            }
+        })
+    ));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn prove_returns_error_for_invalid_trace() {
+    let mut trace = gen_fib_trace(16);
+    trace.0 .0[1][9] += Fp::one();
+
+    let res = pollster::block_on(FibClaim::new().prove(OPTIONS, trace));
+
+    assert!(matches!(
+        res,
+        Err(ProvingError::UnsatisfiedConstraints {
+            source: ConstraintError::UnsatisfiedConstraint {
+                constraint: 1,
+                row: 9,
+                ..
+            }
+        })
+    ));
+}
+
+#[test]
+fn prove_returns_error_for_non_power_of_two_trace() {
+    let mut trace = gen_fib_trace(16);
+    for column in trace.0.iter_mut() {
+        column.pop();
+    }
+
+    let res = pollster::block_on(FibClaim::new().prove(OPTIONS, trace));
+
+    assert!(matches!(
+        res,
+        Err(ProvingError::InvalidAir {
+            source: air::Error::TraceLengthNotPowerOfTwo { trace_len: 15 }
+        })
+    ));
+}
+
+#[test]
+fn prove_returns_error_for_unsupported_folding_factor() {
+    let options = ProofOptions::new(32, 4, 0, 3, 4);
+
+    let res = pollster::block_on(FibClaim::new().prove(options, gen_fib_trace(16)));
+
+    assert!(matches!(
+        res,
+        Err(ProvingError::UnsupportedFoldingFactor { folding_factor: 3 })
+    ));
+}
+
+#[test]
+fn proof_opens_exactly_num_queries_positions() {
+    // every position in the lde domain must be queried
+    let options = ProofOptions::new(64, 4, 0, 2, 4);
+    let claim = FibClaim::new();
+
+    let proof = pollster::block_on(claim.prove(options, gen_fib_trace(16))).unwrap();
+
+    assert_eq!(64 * 2, proof.trace_queries.base_trace_values.len());
+    claim.verify(proof, 0).unwrap();
+}
+
+#[derive(Default)]
+struct RecordingObserver(Mutex<Vec<Event>>);
+
+impl ProverObserver for RecordingObserver {
+    fn on_event(&self, event: &Event) {
+        self.0.lock().unwrap().push(*event);
+    }
+}
+
+#[test]
+fn observer_receives_each_phase_in_order() {
+    let claim = FibClaim::new().with_observer(RecordingObserver::default());
+
+    pollster::block_on(claim.prove(OPTIONS, gen_fib_trace(16))).unwrap();
+
+    let events = claim.observer.0.into_inner().unwrap();
+    let started = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::PhaseStart(phase) => Some(*phase),
+            _ => None,
+        })
+        .collect::<Vec<Phase>>();
+    let ended = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::PhaseEnd { phase, .. } => Some(*phase),
+            _ => None,
+        })
+        .collect::<Vec<Phase>>();
+    assert_eq!(
+        vec![
+            Phase::TraceGeneration,
+            Phase::AirInit,
+            Phase::BaseTraceCommitment,
+            Phase::ExtensionTraceCommitment,
+            Phase::ConstraintEvaluation,
+            Phase::CompositionTraceCommitment,
+            Phase::DeepComposition,
+            Phase::Fri,
+            Phase::ProofOfWork,
+            Phase::Queries,
+        ],
+        started
+    );
+    assert_eq!(started, ended);
+    assert!(events.contains(&Event::TraceDimensions {
+        trace_len: 16,
+        num_base_columns: 2,
+        num_extension_columns: 0,
+    }));
+    assert!(events.contains(&Event::Constraints {
+        num_constraints: 3,
+        num_composition_coeffs: 6,
+    }));
+}
+
+#[test]
+fn zero_knowledge_proof_verifies() {
+    let options = OPTIONS.with_zero_knowledge(true);
+    let claim = FibClaim::new();
+
+    let proof = pollster::block_on(claim.prove(options, gen_fib_trace(64))).unwrap();
+
+    assert!(proof.options.zero_knowledge);
+    claim.verify(proof, 0).unwrap();
+}
+
+#[test]
+fn zero_knowledge_requires_fri_blowup_factor_of_two() {
+    let options = ProofOptions::new(8, 2, 0, 2, 4).with_zero_knowledge(true);
+
+    let res = pollster::block_on(FibClaim::new().prove(options, gen_fib_trace(64)));
+
+    assert!(matches!(
+        res,
+        Err(ProvingError::InvalidAir {
+            source: air::Error::ZeroKnowledgeBlowupFactorTooSmall {
+                fri_blowup_factor: 1
+            }
+        })
+    ));
+}
+
+#[test]
+fn zero_knowledge_limits_number_of_openings() {
+    // 32 queries and the ood evaluations at offsets 0 and 1 exceed 32 rows
+    let options = OPTIONS.with_zero_knowledge(true);
+
+    let res = pollster::block_on(FibClaim::new().prove(options, gen_fib_trace(32)));
+
+    assert!(matches!(
+        res,
+        Err(ProvingError::InvalidAir {
+            source: air::Error::TooManyZeroKnowledgeOpenings {
+                num_openings: 34,
+                trace_len: 32,
+            }
+        })
+    ));
+}
+
+#[test]
+fn zero_knowledge_proofs_are_randomized() {
+    let options = OPTIONS.with_zero_knowledge(true);
+    let claim = FibClaim::new();
+
+    let proof1 = pollster::block_on(claim.prove(options, gen_fib_trace(64))).unwrap();
+    let proof2 = pollster::block_on(claim.prove(options, gen_fib_trace(64))).unwrap();
+
+    assert_ne!(proof1.base_trace_commitment, proof2.base_trace_commitment);
+    assert_ne!(
+        proof1.execution_trace_ood_evals,
+        proof2.execution_trace_ood_evals
+    );
+}
+
+#[test]
+fn folding_schedule_proof_verifies() {
+    // lde domain of size 1024 folds to 128, 32 and then 16
+    let options = OPTIONS.with_fri_folding_schedule(&[8, 4]);
+    let claim = FibClaim::new();
+
+    let proof = pollster::block_on(claim.prove(options, gen_fib_trace(256))).unwrap();
+
+    assert_eq!(3, proof.fri_proof.layers.len());
+    claim.verify(proof, 0).unwrap();
+}
+
+#[test]
+fn committed_remainder_proof_verifies() {
+    let options = OPTIONS.with_fri_remainder_commitment(RemainderCommitment::Codeword);
+    let claim = FibStatementClaim::<FibStatementAirConfig>::with_public_inputs(1);
+
+    let proof = pollster::block_on(claim.prove(options, gen_fib_trace(64))).unwrap();
+
+    assert!(proof.fri_proof.remainder_commitment.is_some());
+    claim.verify(proof.clone(), 0).unwrap();
+    check_tampered_proofs(&claim, &proof);
+}
+
+#[test]
+#[should_panic]
+fn folding_schedule_rejects_unsupported_folding_factor() {
+    let _ = OPTIONS.with_fri_folding_schedule(&[8, 3]);
+}
+
+/// Fibonacci claim that commits to merkle caps
+type FibCapClaim = FibClaim<Sha256HashFn, MatrixMerkleCapTreeImpl<Sha256HashFn, 2>>;
+
+#[test]
+fn merkle_cap_proof_verifies() {
+    let claim = FibCapClaim::default();
+    let proof = pollster::block_on(claim.prove(OPTIONS, gen_fib_trace(16))).unwrap();
+
+    assert_eq!(4, proof.base_trace_commitment.0.len());
+    claim.verify(proof, 0).unwrap();
+}
+
+/// Fibonacci claim that uses an algebraic hash function
+type FibRpoClaim = FibClaim<Rpo256HashFn>;
+
+#[test]
+fn rpo_proof_verifies() {
+    let claim = FibRpoClaim::default();
+    let proof = pollster::block_on(claim.prove(OPTIONS, gen_fib_trace(16))).unwrap();
+
+    claim.verify(proof, 0).unwrap();
+}
+
+/// Fibonacci claim that uses Poseidon2 and a field-native public coin
+type FibPoseidon2Claim = FibClaim<
+    Poseidon2GoldilocksHashFn,
+    MatrixMerkleTreeImpl<Poseidon2GoldilocksHashFn>,
+    FieldPublicCoin<Fp, Poseidon2GoldilocksHashFn>,
+>;
+
+#[test]
+fn poseidon2_proof_verifies() {
+    let options = ProofOptions::new(16, 4, 8, 2, 4);
+    let claim = FibPoseidon2Claim::default();
+    let proof = pollster::block_on(claim.prove(options, gen_fib_trace(16))).unwrap();
+
+    claim.verify(proof, 0).unwrap();
+}
+
+/// Fibonacci claim that uses Keccak-256 truncated to 160 bits
+type FibMaskedKeccakClaim = FibClaim<MaskedKeccak256HashFn>;
+
+#[test]
+fn masked_keccak_proof_verifies() {
+    let claim = FibMaskedKeccakClaim::default();
+    let proof = pollster::block_on(claim.prove(OPTIONS, gen_fib_trace(16))).unwrap();
+
+    let merkle_tree_security = <FibMaskedKeccakClaim as Stark>::MerkleTree::security_level_bits();
+    let public_coin_security = <FibMaskedKeccakClaim as Stark>::PublicCoin::security_level_bits();
+    assert_eq!(80, merkle_tree_security);
+    assert_eq!(80, public_coin_security);
+    claim.verify(proof, 0).unwrap();
+}
+
+/// Fibonacci AIR with a public input that the constraints don't reference.
+/// Each constraint is scaled by `SCALE`.
+struct FibStatementAirConfig<const NUM_BASE_COLUMNS: usize = 2, const SCALE: u64 = 1>;
+
+impl<const NUM_BASE_COLUMNS: usize, const SCALE: u64> AirConfig
+    for FibStatementAirConfig<NUM_BASE_COLUMNS, SCALE>
+{
+    const NUM_BASE_COLUMNS: usize = NUM_BASE_COLUMNS;
+    const NAME: &'static str = "fibonacci_statement";
+    type Fp = Fp;
+    type Fq = Fp;
+    type PublicInputs = u64;
+
+    fn constraints(trace_len: usize) -> Vec<Constraint<FieldVariant<Fp, Fp>>> {
+        let scale = AlgebraicItem::Constant(FieldVariant::Fp(Fp::from(SCALE)));
+        FibAirConfig::constraints(trace_len)
+            .into_iter()
+            .map(|constraint| Constraint::new((*constraint).clone() * scale))
+            .collect()
+    }
+}
+
+/// Fibonacci AIR evaluated over a coset other than the default
+struct FibOffsetAirConfig;
+
+impl AirConfig for FibOffsetAirConfig {
+    const NUM_BASE_COLUMNS: usize = 2;
+    const NAME: &'static str = "fibonacci_offset";
+    type Fp = Fp;
+    type Fq = Fp;
+    type PublicInputs = u64;
+
+    fn constraints(trace_len: usize) -> Vec<Constraint<FieldVariant<Fp, Fp>>> {
+        FibAirConfig::constraints(trace_len)
+    }
+
+    fn domain_offset() -> Fp {
+        Fp::GENERATOR.pow([3])
+    }
+}
+
+type FibStatementClaim<A = FibStatementAirConfig> =
+    FibClaim<Sha256HashFn, MatrixMerkleTreeImpl<Sha256HashFn>, PublicCoinImpl<Fp, Sha256HashFn>, A>;
+
+#[test]
+fn custom_domain_offset_proof_verifies() {
+    let claim = FibStatementClaim::<FibOffsetAirConfig>::with_public_inputs(1);
+
+    for options in [
+        OPTIONS,
+        OPTIONS.with_zero_knowledge(true),
+        OPTIONS.with_fri_folding_schedule(&[4]),
+    ] {
+        let proof = pollster::block_on(claim.prove(options, gen_fib_trace(64))).unwrap();
+
+        claim.verify(proof.clone(), 0).unwrap();
+        check_tampered_proofs(&claim, &proof);
+    }
+}
+
+#[test]
+fn proof_is_bound_to_public_inputs() {
+    let claim = FibStatementClaim::<FibStatementAirConfig>::with_public_inputs(1);
+    let proof = pollster::block_on(claim.prove(OPTIONS, gen_fib_trace(16))).unwrap();
+
+    claim.verify(proof.clone(), 0).unwrap();
+    let res = FibStatementClaim::<FibStatementAirConfig>::with_public_inputs(2).verify(proof, 0);
+
+    assert!(res.is_err());
+}
+
+#[test]
+fn proof_is_bound_to_options() {
+    let claim = FibStatementClaim::<FibStatementAirConfig>::with_public_inputs(1);
+    let mut proof = pollster::block_on(claim.prove(OPTIONS, gen_fib_trace(16))).unwrap();
+
+    // doesn't change the number of fri layers or the proof of work so the
+    // proof is only rejected because the options are absorbed into the seed
+    proof.options.fri_max_remainder_coeffs = 7;
+
+    assert!(matches!(
+        claim.verify(proof, 0),
+        Err(VerificationError::InconsistentOodConstraintEvaluations)
+    ));
+}
+
+#[test]
+fn public_coin_is_bound_to_statement() {
+    fn first_draw<A: AirConfig<Fp = Fp, Fq = Fp, PublicInputs = u64>>(
+        public_input: u64,
+        trace_len: usize,
+        options: ProofOptions,
+    ) -> Fp {
+        let air = Air::new(trace_len, public_input, options).unwrap();
+        let claim = FibStatementClaim::<A>::with_public_inputs(public_input);
+        claim.gen_public_coin(&air).draw()
+    }
+
+    let draw = first_draw::<FibStatementAirConfig>(1, 16, OPTIONS);
+
+    assert_eq!(draw, first_draw::<FibStatementAirConfig>(1, 16, OPTIONS));
+    // public inputs
+    assert_ne!(draw, first_draw::<FibStatementAirConfig>(2, 16, OPTIONS));
+    // proof options
+    let options = OPTIONS.with_zero_knowledge(true);
+    let draw_64 = first_draw::<FibStatementAirConfig>(1, 64, OPTIONS);
+    assert_ne!(draw_64, first_draw::<FibStatementAirConfig>(1, 64, options));
+    // trace length
+    assert_ne!(draw, first_draw::<FibStatementAirConfig>(1, 32, OPTIONS));
+    // number of columns
+    assert_ne!(draw, first_draw::<FibStatementAirConfig<3>>(1, 16, OPTIONS));
+    // constraints
+    assert_ne!(
+        draw,
+        first_draw::<FibStatementAirConfig<2, 2>>(1, 16, OPTIONS)
+    );
+}
+
+/// Fibonacci trace with two extension segments. The first segment shifts the
+/// first column by a challenge and the second segment is a running product of
+/// the shifted column offset by a challenge drawn after the first segment is
+/// committed to.
+struct TwoRoundTrace {
+    base: FibTrace,
+    num_product_columns: usize,
+}
+
+impl Trace for TwoRoundTrace {
+    type Fp = Fp;
+    type Fq = Fp;
+
+    fn base_columns(&self) -> &Matrix<Fp> {
+        &self.base.0
+    }
+
+    fn build_extension_segment(
+        &self,
+        segment: usize,
+        challenges: &Challenges<Fp>,
+        segments: &[Matrix<Fp>],
+    ) -> Option<Matrix<Fp>> {
+        match segment {
+            0 => {
+                assert_eq!(1, challenges.len());
+                let mut shifted = Vec::new_in(GpuAllocator);
+                shifted.extend(self.base.0[0].iter().map(|v| *v + challenges[0]));
+                Some(Matrix::new(vec![shifted]))
+            }
+            1 => {
+                assert_eq!(2, challenges.len());
+                let mut product = Vec::new_in(GpuAllocator);
+                let mut acc = Fp::one();
+                for shifted in &segments[0].0[0] {
+                    product.push(acc);
+                    acc *= *shifted + challenges[1];
+                }
+                let columns =
+                    (0..self.num_product_columns).map(|_| product.to_vec_in(GpuAllocator));
+                Some(Matrix::new(columns.collect()))
+            }
+            _ => None,
+        }
+    }
+}
+
+struct TwoRoundAirConfig;
+
+impl AirConfig for TwoRoundAirConfig {
+    const NUM_BASE_COLUMNS: usize = 2;
+    const NUM_EXTENSION_COLUMNS: usize = 2;
+    const EXTENSION_SEGMENTS: &'static [TraceSegment] =
+        &[TraceSegment::new(1, 1), TraceSegment::new(1, 1)];
+    const NAME: &'static str = "fibonacci_two_round";
+    type Fp = Fp;
+    type Fq = Fp;
+    type PublicInputs = ();
+
+    fn constraints(trace_len: usize) -> Vec<Constraint<FieldVariant<Fp, Fp>>> {
+        use AlgebraicItem::*;
+        let trace_xs = Radix2EvaluationDomain::<Fp>::new(trace_len).unwrap();
+        let first_trace_x = Constant(FieldVariant::Fp(trace_xs.element(0)));
+        let last_trace_x = Constant(FieldVariant::Fp(trace_xs.element(trace_len - 1)));
+        let one = Constant(FieldVariant::Fp(Fp::one()));
+
+        let every_row_except_last = (X - last_trace_x) / (X.pow(trace_len) - one);
+        let extension_constraints = [
+            (2.curr() - 0.curr() - Challenge(0)) * &every_row_except_last,
+            (3.curr() - one) / (X - first_trace_x),
+            (3.next() - 3.curr() * (2.curr() + Challenge(1))) * &every_row_except_last,
+        ];
+        let fib_constraints = FibAirConfig::constraints(trace_len);
+        fib_constraints
+            .into_iter()
+            .chain(extension_constraints.map(Constraint::new))
+            .collect()
+    }
+}
+
+type TwoRoundClaim = FibClaim<
+    Sha256HashFn,
+    MatrixMerkleTreeImpl<Sha256HashFn>,
+    PublicCoinImpl<Fp, Sha256HashFn>,
+    TwoRoundAirConfig,
+    TwoRoundTrace,
+>;
+
+#[test]
+fn multiple_extension_segments_proof_verifies() {
+    for options in [OPTIONS, OPTIONS.with_zero_knowledge(true)] {
+        let trace = TwoRoundTrace {
+            base: gen_fib_trace(64),
+            num_product_columns: 1,
+        };
+        let claim = TwoRoundClaim::default();
+        let proof = pollster::block_on(claim.prove(options, trace)).unwrap();
+        assert_eq!(2, proof.extension_trace_commitments.len());
+        assert_eq!(2, proof.trace_queries.extension_trace_proofs.len());
+
+        let artifacts = claim.verify(proof.clone(), 0).unwrap();
+        assert_eq!(2, artifacts.air_challenges.len());
+        check_tampered_proofs(&claim, &proof);
+    }
+}
+
+#[test]
+fn prove_returns_error_for_extension_segment_column_mismatch() {
+    let trace = TwoRoundTrace {
+        base: gen_fib_trace(64),
+        num_product_columns: 2,
+    };
+
+    let res = pollster::block_on(TwoRoundClaim::default().prove(OPTIONS, trace));
+
+    assert!(matches!(
+        res,
+        Err(ProvingError::ExtensionColumnCountMismatch {
+            segment: 1,
+            expected: 1,
+            actual: 2,
+        })
+    ));
+}
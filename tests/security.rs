@@ -0,0 +1,118 @@
+#![feature(allocator_api)]
+mod common;
+
+use common::gen_fib_trace;
+use common::FibClaim;
+use common::OPTIONS;
+use ministark::security::Error;
+use ministark::security::ProofOptionsTuner;
+use ministark::security::SecurityModel;
+use ministark::security::SecurityParams;
+use ministark::stark::Stark;
+use ministark::ProofOptions;
+
+const TRACE_LEN: usize = 256;
+
+fn params(options: ProofOptions) -> SecurityParams {
+    SecurityParams::new::<FibClaim>(TRACE_LEN, options)
+}
+
+#[test]
+fn proven_security_is_at_most_conjectured_security() {
+    for options in [
+        OPTIONS,
+        ProofOptions::new(40, 8, 16, 4, 8),
+        ProofOptions::new(64, 16, 0, 16, 16).with_fri_folding_schedule(&[8, 4]),
+        OPTIONS.with_zero_knowledge(true),
+    ] {
+        let params = params(options);
+        let conjectured = params.conjectured_security_bits();
+        let proven = params.proven_security_bits();
+
+        assert!(proven > 0, "{options:?}");
+        assert!(
+            proven <= conjectured,
+            "{options:?}: {proven} > {conjectured}"
+        );
+    }
+}
+
+#[test]
+fn security_is_bounded_by_the_field() {
+    // FRI queries alone give 128 * 7 + 16 bits of security
+    let params = params(ProofOptions::new(128, 128, 16, 2, 4));
+
+    // the 64-bit field bounds the soundness of the out-of-domain sampling
+    assert!(params.conjectured_security_bits() < 64);
+    assert!(params.proven_security_bits() < 64);
+}
+
+#[test]
+fn security_increases_with_queries_and_grinding() {
+    let security = |options| params(options).conjectured_security_bits();
+    let options = ProofOptions::new(8, 4, 0, 2, 4);
+
+    assert_eq!(16, security(options));
+    assert_eq!(18, security(ProofOptions::new(9, 4, 0, 2, 4)));
+    assert_eq!(20, security(ProofOptions::new(8, 4, 4, 2, 4)));
+    assert_eq!(8, security(options.with_zero_knowledge(true)));
+}
+
+#[test]
+fn tuned_options_reach_target_security() {
+    for (model, target_bits) in [
+        (SecurityModel::Conjectured, 40),
+        (SecurityModel::Proven, 30),
+    ] {
+        let options = ProofOptionsTuner::<FibClaim>::new(TRACE_LEN, target_bits)
+            .with_security_model(model)
+            .tune()
+            .unwrap();
+        let params = params(options);
+        let security = match model {
+            SecurityModel::Conjectured => params.conjectured_security_bits(),
+            SecurityModel::Proven => params.proven_security_bits(),
+        };
+        assert!(security >= target_bits, "{model:?}: {options:?}");
+
+        let claim = FibClaim::new();
+        let trace = gen_fib_trace(TRACE_LEN);
+        let proof = pollster::block_on(claim.prove(options, trace)).unwrap();
+        assert_eq!(
+            security,
+            match model {
+                SecurityModel::Conjectured => proof.security_level_bits(),
+                SecurityModel::Proven => proof.proven_security_level_bits(),
+            }
+        );
+        claim.verify(proof, target_bits).unwrap();
+    }
+}
+
+#[test]
+fn tuned_options_with_zero_knowledge_verify() {
+    let options = ProofOptionsTuner::<FibClaim>::new(TRACE_LEN, 40)
+        .with_zero_knowledge(true)
+        .tune()
+        .unwrap();
+    assert!(options.zero_knowledge);
+
+    let claim = FibClaim::new();
+    let proof = pollster::block_on(claim.prove(options, gen_fib_trace(TRACE_LEN))).unwrap();
+    claim.verify(proof, 40).unwrap();
+}
+
+#[test]
+fn tuner_rejects_unreachable_targets() {
+    let res = ProofOptionsTuner::<FibClaim>::new(TRACE_LEN, 64).tune();
+    assert!(matches!(
+        res,
+        Err(Error::TargetUnreachable { target_bits: 64 })
+    ));
+
+    let res = ProofOptionsTuner::<FibClaim>::new(100, 40).tune();
+    assert!(matches!(
+        res,
+        Err(Error::InvalidTraceLength { trace_len: 100 })
+    ));
+}